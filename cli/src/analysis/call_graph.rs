@@ -0,0 +1,231 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    io::Write,
+};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use ds_decomp::{
+    config::{delinks::Delinks, module::ModuleKind},
+    project::project::Project,
+};
+use serde::Serialize;
+
+use super::xref::{CrossReferenceKind, CrossReferences};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum CallGraphLevel {
+    /// One node per function
+    Function,
+    /// One node per delink file
+    File,
+    /// One node per module
+    Module,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum NodeKey {
+    Function(ModuleKind, u32),
+    File(ModuleKind, usize),
+    Module(ModuleKind),
+}
+
+#[derive(Clone, Serialize)]
+pub struct CallGraphNode {
+    pub name: String,
+    pub module: String,
+    /// Delink file containing this node, if the graph is at function or file level
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CallGraphEdge {
+    pub from: usize,
+    pub to: usize,
+    /// Number of call sites between the two nodes
+    pub calls: usize,
+}
+
+/// Calls between functions, delink files or modules, built from function calls and relocations.
+#[derive(Serialize)]
+pub struct CallGraph {
+    #[serde(skip)]
+    level: CallGraphLevel,
+    nodes: Vec<CallGraphNode>,
+    edges: Vec<CallGraphEdge>,
+    /// Maps each function to its node
+    #[serde(skip)]
+    functions: HashMap<(ModuleKind, u32), usize>,
+}
+
+impl CallGraph {
+    pub fn analyze(
+        project: &Project,
+        delinks: &BTreeMap<ModuleKind, Delinks>,
+        xrefs: &CrossReferences,
+        level: CallGraphLevel,
+    ) -> Self {
+        let mut nodes = vec![];
+        let mut node_indices = HashMap::new();
+        let mut functions = HashMap::new();
+
+        for module in project.modules() {
+            let module_kind = module.kind();
+            let files = delinks.get(&module_kind).map(|delinks| delinks.files.as_slice()).unwrap_or_default();
+            for function in module.sections().functions() {
+                let address = function.start_address();
+                let file_index = files.iter().position(|file| file.sections.get_by_contained_address(address).is_some());
+                let file_name = file_index.map(|index| files[index].name.clone());
+
+                let (key, name) = match level {
+                    CallGraphLevel::Function => (NodeKey::Function(module_kind, address), function.name().to_string()),
+                    CallGraphLevel::File => match &file_name {
+                        Some(file_name) => (NodeKey::File(module_kind, file_index.unwrap()), file_name.clone()),
+                        None => continue,
+                    },
+                    CallGraphLevel::Module => (NodeKey::Module(module_kind), module.name().to_string()),
+                };
+                let index = *node_indices.entry(key).or_insert_with(|| {
+                    nodes.push(CallGraphNode {
+                        name,
+                        module: module.name().to_string(),
+                        file: if level == CallGraphLevel::Module { None } else { file_name },
+                    });
+                    nodes.len() - 1
+                });
+                functions.insert((module_kind, address), index);
+            }
+        }
+
+        let symbol_maps = project.symbol_maps();
+        let function_node = |module_kind: ModuleKind, address: u32| {
+            let (_, symbol) = symbol_maps.get(module_kind)?.get_function_containing(address)?;
+            functions.get(&(module_kind, symbol.addr)).copied()
+        };
+
+        let mut calls: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+        for xref in xrefs.iter().filter(|xref| xref.kind == CrossReferenceKind::Call) {
+            let Some(from) = function_node(xref.from_module, xref.from) else { continue };
+            for &to_module in &xref.to_modules {
+                let Some(to) = function_node(to_module, xref.to) else { continue };
+                // Recursion is only interesting at function level
+                if from == to && level != CallGraphLevel::Function {
+                    continue;
+                }
+                *calls.entry((from, to)).or_default() += 1;
+            }
+        }
+        let edges = calls.into_iter().map(|((from, to), calls)| CallGraphEdge { from, to, calls }).collect();
+
+        Self { level, nodes, edges, functions }
+    }
+
+    /// Returns the node containing a function.
+    pub fn function_node(&self, module_kind: ModuleKind, address: u32) -> Option<usize> {
+        self.functions.get(&(module_kind, address)).copied()
+    }
+
+    /// Returns the subgraph of nodes reachable from the roots within `depth` calls, or any number of calls if `None`.
+    pub fn reachable_from(&self, roots: &[usize], depth: Option<usize>) -> Self {
+        let mut callees: HashMap<usize, Vec<usize>> = HashMap::new();
+        for edge in &self.edges {
+            callees.entry(edge.from).or_default().push(edge.to);
+        }
+
+        let mut distances = HashMap::new();
+        let mut queue = VecDeque::new();
+        for &root in roots {
+            if distances.insert(root, 0).is_none() {
+                queue.push_back(root);
+            }
+        }
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[&node];
+            if depth.is_some_and(|depth| distance >= depth) {
+                continue;
+            }
+            for &callee in callees.get(&node).into_iter().flatten() {
+                if !distances.contains_key(&callee) {
+                    distances.insert(callee, distance + 1);
+                    queue.push_back(callee);
+                }
+            }
+        }
+
+        let mut new_indices = HashMap::new();
+        let mut nodes = vec![];
+        for (index, node) in self.nodes.iter().enumerate() {
+            if distances.contains_key(&index) {
+                new_indices.insert(index, nodes.len());
+                nodes.push(node.clone());
+            }
+        }
+        let edges = self
+            .edges
+            .iter()
+            .filter_map(|edge| {
+                let from = *new_indices.get(&edge.from)?;
+                let to = *new_indices.get(&edge.to)?;
+                Some(CallGraphEdge { from, to, calls: edge.calls })
+            })
+            .collect();
+        let functions =
+            self.functions.iter().filter_map(|(&function, index)| Some((function, *new_indices.get(index)?))).collect();
+
+        Self { level: self.level, nodes, edges, functions }
+    }
+
+    pub fn write_json<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// Writes the graph in the DOT format of Graphviz. Nodes are clustered by module, unless the graph is at module level.
+    pub fn write_dot<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writeln!(writer, "digraph call_graph {{")?;
+        writeln!(writer, "    node [shape=box];")?;
+
+        if self.level == CallGraphLevel::Module {
+            for (index, node) in self.nodes.iter().enumerate() {
+                writeln!(writer, "    n{index} [label={}];", Self::dot_string(&node.name))?;
+            }
+        } else {
+            let mut modules: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+            for (index, node) in self.nodes.iter().enumerate() {
+                modules.entry(&node.module).or_default().push(index);
+            }
+            for (module, indices) in modules {
+                writeln!(writer, "    subgraph {} {{", Self::dot_string(&format!("cluster_{module}")))?;
+                writeln!(writer, "        label={};", Self::dot_string(module))?;
+                for index in indices {
+                    writeln!(writer, "        n{index} [label={}];", Self::dot_string(&self.nodes[index].name))?;
+                }
+                writeln!(writer, "    }}")?;
+            }
+        }
+
+        for edge in &self.edges {
+            if edge.calls > 1 {
+                writeln!(writer, "    n{} -> n{} [label=\"{}\"];", edge.from, edge.to, edge.calls)?;
+            } else {
+                writeln!(writer, "    n{} -> n{};", edge.from, edge.to)?;
+            }
+        }
+
+        writeln!(writer, "}}")?;
+        Ok(())
+    }
+
+    fn dot_string(text: &str) -> String {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    pub fn nodes(&self) -> &[CallGraphNode] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[CallGraphEdge] {
+        &self.edges
+    }
+}
@@ -1,12 +1,20 @@
 use std::io;
 
 use anyhow::{bail, Result};
-use ds_decomp::analysis::functions::Function;
-use unarm::{ArmVersion, DisplayOptions, Endian, ParseFlags, ParseMode, Parser, RegNames};
+use ds_decomp::analysis::{
+    functions::Function,
+    io_registers::{IoRegister, IoRegisterKind},
+    secure_area::SwiFunction,
+};
+use unarm::{
+    args::{Argument, OffsetImm, Reg, Register},
+    ArmVersion, DisplayOptions, Endian, ParseFlags, ParseMode, ParsedIns, Parser, RegNames,
+};
 
 use crate::config::symbol::{SymDataExt, SymbolLookup};
 
 pub trait FunctionExt {
+    /// Returns whether I/O register names were written, which are defined in io_registers.inc.
     fn write_assembly<W: io::Write>(
         &self,
         w: &mut W,
@@ -14,7 +22,7 @@ pub trait FunctionExt {
         module_code: &[u8],
         base_address: u32,
         ual: bool,
-    ) -> Result<()>;
+    ) -> Result<bool>;
 }
 
 impl FunctionExt for Function {
@@ -25,7 +33,7 @@ impl FunctionExt for Function {
         module_code: &[u8],
         base_address: u32,
         ual: bool,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let mode = if self.is_thumb() { ParseMode::Thumb } else { ParseMode::Arm };
         let mut parser = Parser::new(
             mode,
@@ -40,6 +48,9 @@ impl FunctionExt for Function {
         }
 
         let mut jump_table = None;
+        let mut uses_io_registers = false;
+        // Registers holding an I/O address loaded from a pool constant
+        let mut io_bases = vec![];
 
         while let Some((address, ins, parsed_ins)) = parser.next() {
             if address == self.first_instruction_address() {
@@ -58,6 +69,7 @@ impl FunctionExt for Function {
             // write label
             if let Some(label) = symbols.symbol_map.get_label(address)? {
                 writeln!(w, "{}:", label.name)?;
+                io_bases.clear();
             }
             if let Some((table, sym)) = symbols.symbol_map.get_jump_table(address)? {
                 jump_table = Some((table, sym));
@@ -111,9 +123,27 @@ impl FunctionExt for Function {
                             unarm::Symbols { lookup: symbols, program_counter: address, pc_load_offset }
                         )
                     )?;
-                    if let Some(reference) = parsed_ins.pc_relative_reference(address, pc_load_offset) {
+                    let reference = parsed_ins.pc_relative_reference(address, pc_load_offset);
+                    if let Some(reference) = reference {
                         symbols.write_ambiguous_symbols_comment(w, address, reference)?;
                     }
+                    if let Some(swi) = SwiFunction::from_ins(&parsed_ins, self.is_thumb()) {
+                        write!(w, " ; {}", swi.interrupt_name())?;
+                    }
+
+                    let pool_value = reference
+                        .filter(|reference| {
+                            parsed_ins.mnemonic.starts_with("ldr") && self.pool_constants().contains(reference)
+                        })
+                        .map(|reference| {
+                            let start = (reference - base_address) as usize;
+                            u32::from_le_bytes(module_code[start..start + 4].try_into().unwrap())
+                        });
+                    let register = pool_value.and_then(io_register).or_else(|| io_register_access(&parsed_ins, &io_bases));
+                    if let Some(register) = register {
+                        write!(w, " ; {}", register.name)?;
+                    }
+                    update_io_bases(&mut io_bases, &parsed_ins, pool_value);
                 }
             }
 
@@ -141,7 +171,13 @@ impl FunctionExt for Function {
                     write!(w, "{}: ", pool_symbol.name)?;
 
                     if !symbols.write_symbol(w, pool_address, const_value, &mut false, "")? {
-                        writeln!(w, ".word {const_value:#x}")?;
+                        match io_register(const_value) {
+                            Some(register) => {
+                                writeln!(w, ".word {}", register.name)?;
+                                uses_io_registers = true;
+                            }
+                            None => writeln!(w, ".word {const_value:#x}")?,
+                        }
                     }
                 } else {
                     if pool_address > parser.address {
@@ -164,6 +200,43 @@ impl FunctionExt for Function {
 
         writeln!(w)?;
 
-        Ok(())
+        Ok(uses_io_registers)
+    }
+}
+
+/// Returns the I/O register at an address. Memory regions are left out, as their addresses are also common integers.
+fn io_register(address: u32) -> Option<&'static IoRegister> {
+    IoRegister::by_address(address).filter(|register| register.kind == IoRegisterKind::Register)
+}
+
+/// Returns the I/O register which a load or store accesses through a register holding an I/O address.
+fn io_register_access(parsed_ins: &ParsedIns, io_bases: &[(Register, u32)]) -> Option<&'static IoRegister> {
+    if !parsed_ins.mnemonic.starts_with("ldr") && !parsed_ins.mnemonic.starts_with("str") {
+        return None;
+    }
+    let Argument::Reg(Reg { reg: base, deref: true, .. }) = parsed_ins.args[1] else { return None };
+    let offset = match parsed_ins.args[2] {
+        Argument::OffsetImm(OffsetImm { value, post_indexed: false }) => value,
+        Argument::None => 0,
+        _ => return None,
+    };
+    let &(_, base_address) = io_bases.iter().find(|&&(reg, _)| reg == base)?;
+    io_register(base_address.wrapping_add_signed(offset))
+}
+
+fn update_io_bases(io_bases: &mut Vec<(Register, u32)>, parsed_ins: &ParsedIns, pool_value: Option<u32>) {
+    let mnemonic = parsed_ins.mnemonic;
+    if mnemonic.starts_with("bl") {
+        // Calls clobber the argument registers
+        io_bases.clear();
+        return;
+    }
+    if ["str", "stm", "cmp", "cmn", "tst", "teq", "b"].iter().any(|prefix| mnemonic.starts_with(prefix)) {
+        return;
+    }
+    let Argument::Reg(Reg { reg: dest, deref: false, .. }) = parsed_ins.args[0] else { return };
+    io_bases.retain(|&(reg, _)| reg != dest);
+    if let Some(value) = pool_value.filter(|value| (0x04000000..0x05000000).contains(value)) {
+        io_bases.push((dest, value));
     }
 }
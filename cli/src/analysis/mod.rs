@@ -1,3 +1,5 @@
-pub mod data;
+pub mod call_graph;
 pub mod functions;
-pub mod overlay_groups;
+pub mod overlay_loads;
+pub mod symbols;
+pub mod xref;
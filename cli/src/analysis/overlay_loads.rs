@@ -0,0 +1,305 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Result};
+use ds_decomp::{
+    analysis::functions::Function,
+    config::{
+        module::{Module, ModuleKind},
+        relocations::RelocationModule,
+    },
+    project::project::Project,
+};
+use unarm::{
+    args::{Argument, Reg, Register},
+    ArmVersion, Endian, Ins, ParseFlags, ParseMode, ParsedIns, Parser,
+};
+
+/// A function which loads an overlay, such as `FS_LoadOverlay`.
+#[derive(Clone, Debug)]
+pub struct OverlayLoader {
+    pub name: String,
+    /// Argument register containing the overlay ID
+    pub id_register: Register,
+}
+
+impl OverlayLoader {
+    /// Parses `name:register`, where the register is one of the argument registers `r0` to `r3`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let Some((name, register)) = text.split_once(':') else {
+            bail!("Expected overlay loader '{text}' to be formatted as 'name:register'");
+        };
+        let id_register = match register {
+            "r0" => Register::R0,
+            "r1" => Register::R1,
+            "r2" => Register::R2,
+            "r3" => Register::R3,
+            _ => bail!("Overlay ID register '{register}' of '{name}' must be one of: r0, r1, r2, r3"),
+        };
+        Ok(Self { name: name.to_string(), id_register })
+    }
+
+    /// Loaders from the Nitro SDK.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self { name: "FS_LoadOverlay".to_string(), id_register: Register::R1 },
+            Self { name: "FS_LoadOverlayInfo".to_string(), id_register: Register::R2 },
+        ]
+    }
+}
+
+/// Overlays loaded with constant IDs, used to determine which overlays can be loaded at the same time.
+pub struct OverlayLoads {
+    /// Overlays loaded by each function, keyed by module and function address
+    by_function: BTreeMap<(ModuleKind, u32), BTreeSet<u16>>,
+    /// Calls to overlay loaders whose overlay ID is not a constant
+    pub unknown_calls: Vec<(ModuleKind, u32)>,
+    /// Functions containing calls in `unknown_calls`, keyed by module and function address
+    unknown_functions: BTreeSet<(ModuleKind, u32)>,
+}
+
+impl OverlayLoads {
+    pub fn analyze(project: &Project, loaders: &[OverlayLoader]) -> Result<Self> {
+        let mut loader_addresses = BTreeMap::new();
+        for loader in loaders {
+            let mut found = false;
+            for module in project.modules() {
+                let Some(symbol_map) = project.symbol_maps().get(module.kind()) else { continue };
+                let Some(symbols) = symbol_map.for_name(&loader.name) else { continue };
+                for (_, symbol) in symbols {
+                    loader_addresses.insert((module.kind(), symbol.addr), loader.id_register);
+                    found = true;
+                }
+            }
+            if !found {
+                log::warn!("Overlay loader '{}' not found in any symbol map", loader.name);
+            }
+        }
+
+        let num_overlays = project.overlays().len();
+        let mut by_function: BTreeMap<_, BTreeSet<u16>> = BTreeMap::new();
+        let mut unknown_calls = vec![];
+        let mut unknown_functions = BTreeSet::new();
+        for module in project.modules() {
+            for function in module.sections().functions() {
+                for (&from, called_function) in function.function_calls() {
+                    let Some(to_module) = Self::called_module(module, from, called_function.address) else { continue };
+                    let Some(&id_register) = loader_addresses.get(&(to_module, called_function.address)) else {
+                        continue;
+                    };
+
+                    let id = RegisterValues::at(module, function, from)?.get(id_register);
+                    match id {
+                        Some(id) if (id as usize) < num_overlays => {
+                            by_function.entry((module.kind(), function.start_address())).or_default().insert(id as u16);
+                        }
+                        _ => {
+                            unknown_calls.push((module.kind(), from));
+                            unknown_functions.insert((module.kind(), function.start_address()));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(Self { by_function, unknown_calls, unknown_functions })
+    }
+
+    fn called_module(module: &Module, from: u32, to: u32) -> Option<ModuleKind> {
+        if module.sections().get_by_contained_address(to).is_some() {
+            return Some(module.kind());
+        }
+        let relocation = module.relocations().get(from)?;
+        match relocation.module() {
+            RelocationModule::Overlays { .. } => None,
+            relocation_module => relocation_module.first_module(),
+        }
+    }
+
+    /// Returns the overlays which may be loaded at the same time as the given overlay. These are the overlays it loads,
+    /// the overlays which load it, and the overlays loaded by the same function as it.
+    pub fn coresident(&self, id: u16) -> BTreeSet<u16> {
+        let module_kind = ModuleKind::Overlay(id);
+        let mut coresident = BTreeSet::new();
+        for (&(loader_module, _), ids) in &self.by_function {
+            if loader_module == module_kind {
+                coresident.extend(ids);
+            }
+            if ids.contains(&id) {
+                coresident.extend(ids);
+                if let ModuleKind::Overlay(loader_id) = loader_module {
+                    coresident.insert(loader_id);
+                }
+            }
+        }
+        coresident.remove(&id);
+        coresident
+    }
+
+    /// Returns whether every overlay which may be loaded at the same time as the given overlay is known. This is not the
+    /// case if the overlay, or a function which loads it, loads overlays by an ID which is not a constant.
+    pub fn is_coresident_known(&self, id: u16) -> bool {
+        let module_kind = ModuleKind::Overlay(id);
+        if self.unknown_calls.iter().any(|&(call_module, _)| call_module == module_kind) {
+            return false;
+        }
+        !self.by_function.iter().any(|(loader, ids)| ids.contains(&id) && self.unknown_functions.contains(loader))
+    }
+
+    pub fn num_loads(&self) -> usize {
+        self.by_function.values().map(|ids| ids.len()).sum()
+    }
+}
+
+/// Constant values of registers, found by scanning a function up to some address.
+struct RegisterValues {
+    values: Vec<(Register, u32)>,
+}
+
+impl RegisterValues {
+    fn at(module: &Module, function: &Function, address: u32) -> Result<Self> {
+        let labels = function.labels().copied().collect::<BTreeSet<_>>();
+        let pool_constants = function
+            .iter_pool_constants(module.code(), module.base_address())
+            .map(|constant| (constant.address, constant.value))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut parser = Parser::new(
+            if function.is_thumb() { ParseMode::Thumb } else { ParseMode::Arm },
+            function.start_address(),
+            Endian::Little,
+            ParseFlags { ual: false, version: ArmVersion::V5Te },
+            function.code(module.code(), module.base_address()),
+        );
+        let thumb = function.is_thumb();
+
+        let mut values = Self { values: vec![] };
+        while let Some((ins_address, ins, parsed_ins)) = parser.next() {
+            if ins_address >= address {
+                return Ok(values);
+            }
+            if pool_constants.range(ins_address.saturating_sub(3)..=ins_address).next().is_some() {
+                continue;
+            }
+            if labels.contains(&ins_address) {
+                // Values may differ depending on which branch was taken
+                values.values.clear();
+            }
+            values.handle(ins_address, thumb, ins, &parsed_ins, &pool_constants);
+        }
+        bail!("Call site {address:#010x} is outside of function '{}'", function.name());
+    }
+
+    fn get(&self, register: Register) -> Option<u32> {
+        self.values.iter().find(|&&(reg, _)| reg == register).map(|&(_, value)| value)
+    }
+
+    fn set(&mut self, register: Register, value: Option<u32>) {
+        self.values.retain(|&(reg, _)| reg != register);
+        if let Some(value) = value {
+            self.values.push((register, value));
+        }
+    }
+
+    fn handle(&mut self, address: u32, thumb: bool, ins: Ins, parsed_ins: &ParsedIns, pool_constants: &BTreeMap<u32, u32>) {
+        let mnemonic = ins.mnemonic();
+        match mnemonic {
+            "bl" | "blx" => {
+                // Argument registers are not preserved across calls
+                for register in [Register::R0, Register::R1, Register::R2, Register::R3] {
+                    self.set(register, None);
+                }
+                return;
+            }
+            "pop" => {
+                self.values.clear();
+                return;
+            }
+            _ if mnemonic.starts_with("ldm") => {
+                self.values.clear();
+                return;
+            }
+            "push" | "cmp" | "cmn" | "tst" | "teq" | "b" | "bx" => return,
+            _ if mnemonic.starts_with("str") || mnemonic.starts_with("stm") => return,
+            _ => {}
+        }
+
+        let args = &parsed_ins.args;
+        let Argument::Reg(Reg { reg: dest, deref: false, .. }) = args[0] else { return };
+
+        let value = match (mnemonic, args[1], args[2], args[3]) {
+            ("mov" | "movs", Argument::UImm(value), Argument::None, _) => Some(value),
+            ("mov" | "movs", Argument::Reg(Reg { reg, deref: false, .. }), Argument::None, _) => self.get(reg),
+            ("add" | "adds", Argument::UImm(value), Argument::None, _) => self.get(dest).map(|base| base.wrapping_add(value)),
+            ("add" | "adds", Argument::Reg(Reg { reg, deref: false, .. }), Argument::UImm(value), Argument::None) => {
+                self.get(reg).map(|base| base.wrapping_add(value))
+            }
+            ("sub" | "subs", Argument::UImm(value), Argument::None, _) => self.get(dest).map(|base| base.wrapping_sub(value)),
+            ("sub" | "subs", Argument::Reg(Reg { reg, deref: false, .. }), Argument::UImm(value), Argument::None) => {
+                self.get(reg).map(|base| base.wrapping_sub(value))
+            }
+            (
+                "ldr",
+                Argument::Reg(Reg { reg: Register::Pc, deref: true, .. }),
+                Argument::OffsetImm(offset),
+                Argument::None,
+            ) if !offset.post_indexed => {
+                let load_address = (address as i32 + offset.value) as u32 & !3;
+                let load_address = load_address + if thumb { 4 } else { 8 };
+                pool_constants.get(&load_address).copied()
+            }
+            _ => None,
+        };
+
+        // Conditional instructions may or may not have written to the register
+        self.set(dest, if ins.is_conditional() { None } else { value });
+    }
+}
+
+/// Narrows a relocation to multiple overlays down to the overlays which can be loaded at the same time as the overlay
+/// containing the relocation. Returns `None` if it couldn't be narrowed, which includes when some of those overlays are
+/// loaded by a non-constant ID unless `force` is set.
+pub fn narrow_overlays(
+    overlay_loads: &OverlayLoads,
+    module_kind: ModuleKind,
+    ids: &[u16],
+    force: bool,
+) -> Option<RelocationModule> {
+    let ModuleKind::Overlay(id) = module_kind else { return None };
+    if !force && !overlay_loads.is_coresident_known(id) {
+        return None;
+    }
+    let coresident = overlay_loads.coresident(id);
+    let narrowed = ids.iter().copied().filter(|id| coresident.contains(id)).collect::<Vec<_>>();
+    match narrowed.len() {
+        0 => None,
+        1 => Some(RelocationModule::Overlay { id: narrowed[0] }),
+        n if n < ids.len() => Some(RelocationModule::Overlays { ids: narrowed }),
+        _ => None,
+    }
+}
+
+/// Narrows relocations to multiple overlays down to the overlays which can be loaded at the same time as the module
+/// containing the relocation. Returns the number of relocations which were narrowed.
+pub fn narrow_relocations(project: &mut Project, overlay_loads: &OverlayLoads, force: bool) -> usize {
+    let mut num_narrowed = 0;
+    for index in 0..project.num_modules() {
+        let module = project.module(index);
+        let module_kind = module.kind();
+        let narrowed = module
+            .relocations()
+            .iter()
+            .filter_map(|relocation| {
+                let RelocationModule::Overlays { ids } = relocation.module() else { return None };
+                let narrowed = narrow_overlays(overlay_loads, module_kind, ids, force)?;
+                Some((relocation.from_address(), narrowed))
+            })
+            .collect::<Vec<_>>();
+
+        num_narrowed += narrowed.len();
+        let relocations = project.relocations_mut(index);
+        for (from, relocation_module) in narrowed {
+            relocations.get_mut(from).unwrap().set_module(relocation_module);
+        }
+    }
+    num_narrowed
+}
@@ -0,0 +1,47 @@
+use std::collections::BTreeMap;
+
+use ds_decomp::{
+    config::{
+        delinks::Delinks,
+        module::ModuleKind,
+        symbol::{Symbol, SymbolKind},
+    },
+    project::project::Project,
+};
+
+/// Returns the name of the delink file which contains the address, or `-` if it's not in any file.
+pub fn file_name(delinks: &BTreeMap<ModuleKind, Delinks>, module_kind: ModuleKind, address: u32) -> &str {
+    delinks
+        .get(&module_kind)
+        .and_then(|delinks| delinks.files.iter().find(|file| file.sections.get_by_contained_address(address).is_some()))
+        .map(|file| file.name.as_str())
+        .unwrap_or("-")
+}
+
+/// Finds the function, data or bss symbol which contains the address.
+pub fn containing_symbol<'a>(project: &'a Project, module_kind: ModuleKind, address: u32) -> Option<&'a Symbol> {
+    let symbol_map = project.symbol_maps().get(module_kind)?;
+    // Only the closest function, data or bss symbol at or before the address can contain it
+    let symbol = symbol_map.iter_by_address(0..address.saturating_add(1)).rev().find(|symbol| is_sized(symbol))?;
+    (address < symbol.addr + symbol_size(project, module_kind, symbol).max(1)).then_some(symbol)
+}
+
+/// Returns whether the symbol spans a range of addresses, as opposed to labels and other markers.
+pub fn is_sized(symbol: &Symbol) -> bool {
+    matches!(symbol.kind, SymbolKind::Function(_) | SymbolKind::Data(_) | SymbolKind::Bss(_))
+}
+
+/// Returns the size of a symbol, bounded by the next symbol or the end of its section if it has no known size.
+pub fn symbol_size(project: &Project, module_kind: ModuleKind, symbol: &Symbol) -> u32 {
+    let section_end = project
+        .module_by_kind(module_kind)
+        .and_then(|module| module.sections().get_by_contained_address(symbol.addr))
+        .map(|(_, section)| section.end_address())
+        .unwrap_or(symbol.addr);
+    let next_symbol = project
+        .symbol_maps()
+        .get(module_kind)
+        .and_then(|symbol_map| symbol_map.iter_by_address(symbol.addr + 1..section_end).find(|next| is_sized(next)))
+        .map(|next| next.addr);
+    symbol.size(next_symbol.unwrap_or(section_end))
+}
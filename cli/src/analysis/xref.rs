@@ -0,0 +1,125 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use ds_decomp::{
+    config::{module::ModuleKind, relocations::RelocationKind, section::SectionKind},
+    project::project::Project,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum CrossReferenceKind {
+    /// Function call or branch
+    Call,
+    /// Address loaded by code, usually from a pool constant
+    Load,
+    /// Address stored in data, such as a vtable or a table of function pointers
+    Pointer,
+}
+
+#[derive(Clone, Debug)]
+pub struct CrossReference {
+    pub from_module: ModuleKind,
+    pub from: u32,
+    /// Every module the destination may be in, more than one if the relocation is ambiguous
+    pub to_modules: Vec<ModuleKind>,
+    pub to: u32,
+    pub kind: CrossReferenceKind,
+}
+
+/// All references between addresses in a program, indexed by both source and destination.
+pub struct CrossReferences {
+    references: Vec<CrossReference>,
+    by_from: BTreeMap<(ModuleKind, u32), Vec<usize>>,
+    by_to: BTreeMap<(ModuleKind, u32), Vec<usize>>,
+}
+
+impl CrossReferences {
+    pub fn analyze(project: &Project) -> Self {
+        let mut references = vec![];
+
+        for module in project.modules() {
+            let module_kind = module.kind();
+
+            for relocation in module.relocations().iter() {
+                let from = relocation.from_address();
+                let kind = match relocation.kind() {
+                    RelocationKind::Load => {
+                        let in_code = module
+                            .sections()
+                            .get_by_contained_address(from)
+                            .is_some_and(|(_, section)| section.kind() == SectionKind::Code);
+                        if in_code {
+                            CrossReferenceKind::Load
+                        } else {
+                            CrossReferenceKind::Pointer
+                        }
+                    }
+                    _ => CrossReferenceKind::Call,
+                };
+                let relocation_module = relocation.module();
+                let to_modules = relocation_module
+                    .first_module()
+                    .into_iter()
+                    .chain(relocation_module.other_modules().into_iter().flatten())
+                    .collect();
+                references.push(CrossReference {
+                    from_module: module_kind,
+                    from,
+                    to_modules,
+                    to: relocation.to_address(),
+                    kind,
+                });
+            }
+
+            // Conditional calls have no relocations, but are always local
+            for function in module.sections().functions() {
+                for (&from, called_function) in function.function_calls() {
+                    if module.relocations().get(from).is_some() {
+                        continue;
+                    }
+                    references.push(CrossReference {
+                        from_module: module_kind,
+                        from,
+                        to_modules: vec![module_kind],
+                        to: called_function.address,
+                        kind: CrossReferenceKind::Call,
+                    });
+                }
+            }
+        }
+
+        let mut by_from: BTreeMap<_, Vec<usize>> = BTreeMap::new();
+        let mut by_to: BTreeMap<_, Vec<usize>> = BTreeMap::new();
+        for (index, reference) in references.iter().enumerate() {
+            by_from.entry((reference.from_module, reference.from)).or_default().push(index);
+            for &to_module in &reference.to_modules {
+                by_to.entry((to_module, reference.to)).or_default().push(index);
+            }
+        }
+
+        Self { references, by_from, by_to }
+    }
+
+    /// Returns references whose source is within the given address range.
+    pub fn from_range(&self, module: ModuleKind, range: Range<u32>) -> impl Iterator<Item = &CrossReference> + '_ {
+        self.by_from
+            .range((module, range.start)..(module, range.end))
+            .flat_map(|(_, indices)| indices)
+            .map(|&i| &self.references[i])
+    }
+
+    /// Returns references whose destination is within the given address range.
+    pub fn to_range(&self, module: ModuleKind, range: Range<u32>) -> impl Iterator<Item = &CrossReference> + '_ {
+        self.by_to
+            .range((module, range.start)..(module, range.end))
+            .flat_map(|(_, indices)| indices)
+            .map(|&i| &self.references[i])
+    }
+
+    pub fn is_referenced(&self, module: ModuleKind, range: Range<u32>) -> bool {
+        self.to_range(module, range).next().is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CrossReference> {
+        self.references.iter()
+    }
+}
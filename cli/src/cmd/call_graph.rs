@@ -0,0 +1,119 @@
+use std::{io::BufWriter, path::PathBuf};
+
+use anyhow::{bail, Result};
+use clap::{Args, ValueEnum};
+use ds_decomp::{
+    config::{config::Config, delinks::Delinks},
+    project::project::Project,
+};
+use ds_rom::rom::{Rom, RomLoadOptions};
+use serde::Serialize;
+
+use crate::{
+    analysis::{
+        call_graph::{CallGraph, CallGraphLevel},
+        xref::CrossReferences,
+    },
+    util::{io::create_file, output},
+};
+
+/// Exports the call graph of a project.
+#[derive(Args)]
+pub struct ExportCallGraph {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Output path for the graph.
+    #[arg(long, short = 'o')]
+    pub output_path: PathBuf,
+
+    /// Output format.
+    #[arg(long, short = 'f', value_enum, default_value_t = CallGraphFormat::Dot)]
+    pub format: CallGraphFormat,
+
+    /// Whether nodes are functions, delink files or modules.
+    #[arg(long, short = 'l', value_enum, default_value_t = CallGraphLevel::Function)]
+    pub level: CallGraphLevel,
+
+    /// Only include what is reachable from these symbols. Can be repeated.
+    #[arg(long, short = 'r')]
+    pub root: Vec<String>,
+
+    /// Maximum number of calls away from the roots.
+    #[arg(long, short = 'd', requires = "root")]
+    pub depth: Option<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum CallGraphFormat {
+    /// Graphviz DOT
+    Dot,
+    Json,
+}
+
+#[derive(Serialize)]
+struct CallGraphResult {
+    output_path: String,
+    num_nodes: usize,
+    num_edges: usize,
+}
+
+impl ExportCallGraph {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+        let rom_autoloads = rom.arm9().autoloads()?;
+        let project = Project::from_config(config_path, &config, &rom, &rom_autoloads)?;
+        let delinks = Delinks::from_config_with_gaps(config_path, &config)?;
+        let xrefs = CrossReferences::analyze(&project);
+
+        let mut graph = CallGraph::analyze(&project, &delinks, &xrefs, self.level);
+        if !self.root.is_empty() {
+            let roots = self.find_roots(&project, &graph)?;
+            graph = graph.reachable_from(&roots, self.depth);
+        }
+
+        let mut writer = BufWriter::new(create_file(&self.output_path)?);
+        match self.format {
+            CallGraphFormat::Dot => graph.write_dot(&mut writer)?,
+            CallGraphFormat::Json => graph.write_json(&mut writer)?,
+        }
+
+        log::info!("Wrote {} nodes and {} edges to {}", graph.nodes().len(), graph.edges().len(), self.output_path.display());
+        output::result(
+            "call-graph",
+            &CallGraphResult {
+                output_path: self.output_path.display().to_string(),
+                num_nodes: graph.nodes().len(),
+                num_edges: graph.edges().len(),
+            },
+        );
+
+        Ok(())
+    }
+
+    fn find_roots(&self, project: &Project, graph: &CallGraph) -> Result<Vec<usize>> {
+        let mut roots = vec![];
+        for name in &self.root {
+            let nodes = project
+                .modules()
+                .iter()
+                .filter_map(|module| {
+                    let (_, symbol) = project.symbol_maps().get(module.kind())?.for_name(name)?.next()?;
+                    graph.function_node(module.kind(), symbol.addr)
+                })
+                .collect::<Vec<_>>();
+            if nodes.is_empty() {
+                bail!("No function named '{name}' found");
+            }
+            roots.extend(nodes);
+        }
+        Ok(roots)
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use ds_decomp::config::{config::Config, delinks::Delinks};
+use serde::Serialize;
+
+use crate::util::{
+    diff::ObjectDiff,
+    output::{self, Diagnostic},
+};
+
+/// Diffs built objects against delinked ones and reports which files are fully matching.
+#[derive(Args)]
+pub struct CheckComplete {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Sets `complete` in delinks.txt for matching files and unsets it for other built files.
+    #[arg(long, short = 'w')]
+    pub write: bool,
+}
+
+#[derive(Serialize)]
+struct CheckCompleteResult {
+    num_files: usize,
+    num_matching: usize,
+}
+
+impl CheckComplete {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let mut num_files = 0;
+        let mut num_matching = 0;
+        for (module_kind, module) in config.modules() {
+            // Gap files are not in delinks.txt, so they are not generated here
+            let delinks_path = config_path.join(&module.delinks);
+            let mut delinks = Delinks::from_file(&delinks_path, module_kind)?;
+
+            let mut changed = false;
+            for file in &mut delinks.files {
+                num_files += 1;
+                let (file_path, _) = file.split_file_ext();
+                let built_path = config_path.join(&config.build_path).join(file_path).with_extension("o");
+                let target_path = config_path.join(&config.delinks_path).join(file_path).with_extension("o");
+
+                if !built_path.exists() || !target_path.exists() {
+                    log::debug!("Skipping {} as it has not been built or delinked", file.name);
+                    continue;
+                }
+                let matching = ObjectDiff::new(&target_path, &built_path)?.is_full_match();
+
+                if matching {
+                    num_matching += 1;
+                    log::info!("{} is matching{}", file.name, if file.complete { "" } else { ", but not marked complete" });
+                } else if file.complete {
+                    Diagnostic::warn(format!("{} is marked complete but is not matching", file.name))
+                        .module(module_kind)
+                        .file(&file.name)
+                        .emit();
+                }
+
+                if self.write && file.complete != matching {
+                    file.complete = matching;
+                    changed = true;
+                }
+            }
+
+            if changed {
+                delinks.write_to_file(&delinks_path)?;
+            }
+        }
+
+        log::info!("{num_matching} of {num_files} file(s) are matching");
+        output::result("check complete", &CheckCompleteResult { num_files, num_matching });
+
+        Ok(())
+    }
+}
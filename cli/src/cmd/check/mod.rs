@@ -1,7 +1,13 @@
+mod complete;
 mod modules;
+mod objects;
+mod relocs;
 mod symbols;
 
+pub use complete::*;
 pub use modules::*;
+pub use objects::*;
+pub use relocs::*;
 pub use symbols::*;
 
 use anyhow::Result;
@@ -17,7 +23,10 @@ pub struct CheckArgs {
 impl CheckArgs {
     pub fn run(&self) -> Result<()> {
         match &self.command {
+            CheckCommand::Complete(complete) => complete.run(),
             CheckCommand::Modules(modules) => modules.run(),
+            CheckCommand::Objects(objects) => objects.run(),
+            CheckCommand::Relocs(relocs) => relocs.run(),
             CheckCommand::Symbols(symbols) => symbols.run(),
         }
     }
@@ -25,6 +34,9 @@ impl CheckArgs {
 
 #[derive(Subcommand)]
 enum CheckCommand {
+    Complete(CheckComplete),
     Modules(CheckModules),
+    Objects(CheckObjects),
+    Relocs(CheckRelocs),
     Symbols(CheckSymbols),
 }
@@ -10,7 +10,12 @@ use ds_decomp::config::{
     module::ModuleKind,
 };
 
-use crate::util::io::read_file;
+use serde::Serialize;
+
+use crate::util::{
+    io::read_file,
+    output::{self, Diagnostic},
+};
 
 /// Verifies that built modules are matching the base ROM.
 #[derive(Args)]
@@ -24,6 +29,12 @@ pub struct CheckModules {
     pub fail: bool,
 }
 
+#[derive(Default, Serialize)]
+struct CheckModulesResult {
+    num_checked: usize,
+    num_failed: usize,
+}
+
 #[derive(PartialEq, Eq)]
 enum CheckResult {
     ChecksumFailed,
@@ -35,27 +46,40 @@ impl CheckModules {
         let config = Config::from_file(&self.config_path)?;
         let config_path = self.config_path.parent().unwrap();
 
-        let mut success = true;
+        let mut result = CheckModulesResult::default();
 
-        success &= self.print_check_module(&config.main_module, ModuleKind::Arm9, config_path)?;
+        self.print_check_module(&config.main_module, ModuleKind::Arm9, config_path, &mut result)?;
         for autoload in &config.autoloads {
-            success &= self.print_check_module(&autoload.module, ModuleKind::Autoload(autoload.kind), config_path)?;
+            self.print_check_module(&autoload.module, ModuleKind::Autoload(autoload.kind), config_path, &mut result)?;
         }
         for overlay in &config.overlays {
-            success &= self.print_check_module(&overlay.module, ModuleKind::Overlay(overlay.id), config_path)?;
+            self.print_check_module(&overlay.module, ModuleKind::Overlay(overlay.id), config_path, &mut result)?;
         }
+        output::result("check modules", &result);
 
-        if self.fail && !success {
+        if self.fail && result.num_failed > 0 {
             bail!("Some module(s) didn't pass the checks.");
         }
 
         Ok(())
     }
 
-    fn print_check_module(&self, module: &ConfigModule, module_kind: ModuleKind, config_path: &Path) -> Result<bool> {
-        let result = self.check_module(module, config_path)?;
-        log::info!("Check {module_kind}: {result}");
-        Ok(result == CheckResult::Ok)
+    fn print_check_module(
+        &self,
+        module: &ConfigModule,
+        module_kind: ModuleKind,
+        config_path: &Path,
+        result: &mut CheckModulesResult,
+    ) -> Result<()> {
+        let check_result = self.check_module(module, config_path)?;
+        result.num_checked += 1;
+        if check_result == CheckResult::Ok {
+            log::info!("Check {module_kind}: {check_result}");
+        } else {
+            result.num_failed += 1;
+            Diagnostic::error(format!("Check {module_kind}: {check_result}")).module(module_kind).emit();
+        }
+        Ok(())
     }
 
     fn check_module(&self, module: &ConfigModule, config_path: &Path) -> Result<CheckResult> {
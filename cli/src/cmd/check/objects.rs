@@ -0,0 +1,188 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Result};
+use clap::Args;
+use ds_decomp::config::{config::Config, delinks::Delinks, module::ModuleKind};
+use object::{Object, ObjectSection, ObjectSymbol, SectionKind};
+use serde::Serialize;
+
+use crate::util::{
+    io::read_file,
+    output::{self, Diagnostic},
+};
+
+/// Verifies that compiled objects are matching their delinked counterparts, before linking.
+#[derive(Args)]
+pub struct CheckObjects {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Return failing exit code if an object doesn't pass the checks.
+    #[arg(long, short = 'f')]
+    pub fail: bool,
+}
+
+/// A defined global symbol, by section name and offset within all sections of that name
+struct ObjectSymbolInfo {
+    section: String,
+    offset: u64,
+}
+
+/// The parts of an object which are compared
+struct ObjectInfo {
+    section_sizes: BTreeMap<String, u64>,
+    global_symbols: BTreeMap<String, ObjectSymbolInfo>,
+}
+
+#[derive(Serialize)]
+struct CheckObjectsResult {
+    num_checked: usize,
+    num_failed: usize,
+}
+
+impl CheckObjects {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let mut num_checked = 0;
+        let mut num_failed = 0;
+        for (module_kind, module) in config.modules() {
+            let delinks = Delinks::from_module_config(config_path, module, module_kind)?;
+            for file in delinks.files.iter().filter(|file| !file.gap()) {
+                let (file_path, _) = file.split_file_ext();
+                let built_path = config_path.join(&config.build_path).join(file_path).with_extension("o");
+                let target_path = config_path.join(&config.delinks_path).join(file_path).with_extension("o");
+                if !built_path.exists() {
+                    log::debug!("Skipping {} as it has not been built", file.name);
+                    continue;
+                }
+                if !target_path.exists() {
+                    Diagnostic::error(format!("Delinked object {} not found, run `dsd delink` first", target_path.display()))
+                        .module(module_kind)
+                        .file(&file.name)
+                        .emit();
+                    num_failed += 1;
+                    continue;
+                }
+
+                let built_file = read_file(&built_path)?;
+                let built = ObjectInfo::parse(&object::File::parse(&*built_file)?)?;
+                let target_file = read_file(&target_path)?;
+                let target = ObjectInfo::parse(&object::File::parse(&*target_file)?)?;
+
+                num_checked += 1;
+                if !Self::check_object(&built, &target, &file.name, module_kind) {
+                    num_failed += 1;
+                }
+            }
+        }
+
+        log::info!("Checked {num_checked} object(s), {num_failed} failed");
+        output::result("check objects", &CheckObjectsResult { num_checked, num_failed });
+
+        if self.fail && num_failed > 0 {
+            bail!("Some object(s) did not match.");
+        }
+
+        Ok(())
+    }
+
+    fn check_object(built: &ObjectInfo, target: &ObjectInfo, file_name: &str, module_kind: ModuleKind) -> bool {
+        let mut success = true;
+        let diagnostic = |message: String| Diagnostic::error(message).module(module_kind).file(file_name);
+
+        for (name, &target_size) in &target.section_sizes {
+            match built.section_sizes.get(name) {
+                Some(&size) if size == target_size => {}
+                Some(&size) => {
+                    success = false;
+                    diagnostic(format!(
+                        "Section {name} in {file_name} is expected to be {target_size:#x} bytes but is {size:#x} bytes"
+                    ))
+                    .emit();
+                }
+                None => {
+                    success = false;
+                    diagnostic(format!("Section {name} in {file_name} is missing")).emit();
+                }
+            }
+        }
+        for name in built.section_sizes.keys().filter(|name| !target.section_sizes.contains_key(*name)) {
+            success = false;
+            diagnostic(format!("Section {name} in {file_name} is not in the delinked object")).emit();
+        }
+
+        for (name, target_symbol) in &target.global_symbols {
+            let Some(symbol) = built.global_symbols.get(name) else {
+                success = false;
+                diagnostic(format!("Global symbol '{name}' in {file_name} is missing")).symbol(name).emit();
+                continue;
+            };
+            if symbol.section != target_symbol.section || symbol.offset != target_symbol.offset {
+                success = false;
+                diagnostic(format!(
+                    "Global symbol '{name}' in {file_name} is expected to be at {}+{:#x} but is at {}+{:#x}",
+                    target_symbol.section, target_symbol.offset, symbol.section, symbol.offset
+                ))
+                .symbol(name)
+                .emit();
+            }
+        }
+        for name in built.global_symbols.keys().filter(|name| !target.global_symbols.contains_key(*name)) {
+            success = false;
+            diagnostic(format!("Global symbol '{name}' in {file_name} is not in the delinked object")).symbol(name).emit();
+        }
+
+        success
+    }
+}
+
+impl ObjectInfo {
+    fn parse(object: &object::File) -> Result<Self> {
+        let mut section_sizes = BTreeMap::<String, u64>::new();
+        // Offset of each section from the first section with the same name
+        let mut section_offsets = HashMap::new();
+        for section in object.sections() {
+            if !matches!(
+                section.kind(),
+                SectionKind::Text | SectionKind::Data | SectionKind::ReadOnlyData | SectionKind::UninitializedData
+            ) {
+                continue;
+            }
+            // Same-named sections, such as the ones mwcc emits for inline functions, are placed after each other by the
+            // linker, so they are compared as one section padded the same way
+            let size = section_sizes.entry(section.name()?.to_string()).or_default();
+            let offset = size.next_multiple_of(section.align().max(1));
+            section_offsets.insert(section.index(), offset);
+            *size = offset + section.size();
+        }
+        // Compilers may emit empty sections which the delinker never creates
+        section_sizes.retain(|_, size| *size > 0);
+
+        let mut global_symbols = BTreeMap::new();
+        for symbol in object.symbols() {
+            if !symbol.is_global() || symbol.is_undefined() {
+                continue;
+            }
+            let Some(section_index) = symbol.section_index() else {
+                continue;
+            };
+            let section = object.section_by_index(section_index)?;
+            let section_offset = section_offsets.get(&section_index).copied().unwrap_or(0);
+            global_symbols.insert(
+                symbol.name()?.to_string(),
+                ObjectSymbolInfo {
+                    section: section.name()?.to_string(),
+                    offset: section_offset + symbol.address() - section.address(),
+                },
+            );
+        }
+
+        Ok(Self { section_sizes, global_symbols })
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::Args;
+use ds_decomp::{
+    config::{
+        config::Config,
+        module::Module,
+        relocations::Relocation,
+        symbol::{SymbolKind, SymbolMaps},
+    },
+    link::relocate::{relocate, RelocationTarget},
+    project::project::Project,
+};
+use ds_rom::rom::{Rom, RomLoadOptions};
+use serde::Serialize;
+
+use crate::util::output::{self, Diagnostic};
+
+/// Verifies that linking every relocation would reproduce the original code, without running the linker.
+#[derive(Args)]
+pub struct CheckRelocs {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Return failing exit code if a relocation doesn't reproduce the original code.
+    #[arg(long, short = 'f')]
+    pub fail: bool,
+}
+
+#[derive(Serialize)]
+struct CheckRelocsResult {
+    num_checked: usize,
+    num_failed: usize,
+}
+
+impl CheckRelocs {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+        let rom_autoloads = rom.arm9().autoloads()?;
+        let project = Project::from_config(config_path, &config, &rom, &rom_autoloads)?;
+
+        let mut num_checked = 0;
+        let mut num_failed = 0;
+        for module in project.modules() {
+            for relocation in module.relocations().iter() {
+                num_checked += 1;
+                if !Self::check_relocation(module, relocation, project.symbol_maps()) {
+                    num_failed += 1;
+                }
+            }
+        }
+
+        log::info!("Checked {num_checked} relocation(s), {num_failed} failed");
+        output::result("check relocs", &CheckRelocsResult { num_checked, num_failed });
+
+        if self.fail && num_failed > 0 {
+            bail!("Some relocation(s) did not reproduce the original code.");
+        }
+
+        Ok(())
+    }
+
+    fn check_relocation(module: &Module, relocation: &Relocation, symbol_maps: &SymbolMaps) -> bool {
+        let from = relocation.from_address();
+        let diagnostic = |message: String| Diagnostic::error(message).module(module.kind()).address(from);
+        let Some(reloc_module) = relocation.module().first_module() else {
+            // Relocations to no module are not delinked
+            return true;
+        };
+
+        let offset = (from - module.base_address()) as usize;
+        let Some(bytes) = module.code().get(offset..offset + 4) else {
+            diagnostic(format!("Relocation {relocation} in {} is outside of the module's code", module.kind())).emit();
+            return false;
+        };
+        let original = u32::from_le_bytes(bytes.try_into().unwrap());
+
+        // Resolve the destination symbol the same way as `dsd delink`
+        let Some(symbol_map) = symbol_maps.get(reloc_module) else {
+            diagnostic(format!("Relocation {relocation} in {} points to {reloc_module} which has no symbols", module.kind()))
+                .emit();
+            return false;
+        };
+        let to = relocation.to_address();
+        let symbol = match (symbol_map.by_address(to), symbol_map.get_function(to)) {
+            (Ok(Some((_, symbol))), _) | (_, Ok(Some((_, symbol)))) => symbol,
+            _ => {
+                diagnostic(format!("No symbol found for relocation {relocation} in {}", module.kind())).emit();
+                return false;
+            }
+        };
+        // Labels are in the instruction mode of the function containing them
+        let thumb = match symbol.kind {
+            SymbolKind::Function(function) => function.mode.into_thumb() == Some(true),
+            SymbolKind::Label(_) => symbol_map
+                .get_function_containing(symbol.addr)
+                .is_some_and(|(function, _)| function.mode.into_thumb() == Some(true)),
+            _ => false,
+        };
+        let target = RelocationTarget { address: symbol.addr, thumb };
+
+        let r_type = relocation.kind().as_elf_relocation_type();
+        let linked = match relocate(r_type, original, from, &target, relocation.addend()) {
+            Ok(linked) => linked,
+            Err(error) => {
+                diagnostic(format!(
+                    "Relocation {relocation} in {} to '{}' at {:#010x} can't be linked: {error}",
+                    module.kind(),
+                    symbol.name,
+                    symbol.addr
+                ))
+                .symbol(&symbol.name)
+                .emit();
+                return false;
+            }
+        };
+        if linked != original {
+            diagnostic(format!(
+                "Relocation {relocation} in {} to '{}' at {:#010x} links to {linked:#010x} but the original is {original:#010x}",
+                module.kind(),
+                symbol.name,
+                symbol.addr
+            ))
+            .symbol(&symbol.name)
+            .emit();
+            return false;
+        }
+
+        true
+    }
+}
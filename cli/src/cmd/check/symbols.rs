@@ -8,7 +8,15 @@ use ds_decomp::config::{
     symbol::{SymbolKind, SymbolMap, SymbolMaps},
 };
 
-use crate::{config::symbol::SymbolMapExt, util::io::read_file};
+use serde::Serialize;
+
+use crate::{
+    config::symbol::SymbolMapExt,
+    util::{
+        io::read_file,
+        output::{self, Diagnostic},
+    },
+};
 
 /// Verifies that built modules are matching the base ROM.
 #[derive(Args)]
@@ -26,6 +34,12 @@ pub struct CheckSymbols {
     pub fail: bool,
 }
 
+#[derive(Default, Serialize)]
+struct CheckSymbolsResult {
+    num_checked: usize,
+    num_failed: usize,
+}
+
 impl CheckSymbols {
     pub fn run(&self) -> Result<()> {
         let config = Config::from_file(&self.config_path)?;
@@ -35,53 +49,62 @@ impl CheckSymbols {
         let object = object::File::parse(&*elf_file)?;
         let object_symbols = SymbolMap::from_object(&object)?;
 
-        let mut success = true;
+        let mut result = CheckSymbolsResult::default();
 
         let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
         if let Some(target_symbols) = symbol_maps.get(ModuleKind::Arm9) {
-            success &= self.check_symbol_map(&object_symbols, target_symbols, ModuleKind::Arm9);
+            self.check_symbol_map(&object_symbols, target_symbols, &mut result, ModuleKind::Arm9);
         }
         for autoload in &config.autoloads {
             let module_kind = ModuleKind::Autoload(autoload.kind);
             if let Some(target_symbols) = symbol_maps.get(module_kind) {
-                success &= self.check_symbol_map(&object_symbols, target_symbols, module_kind);
+                self.check_symbol_map(&object_symbols, target_symbols, &mut result, module_kind);
             }
         }
         for overlay in &config.overlays {
             let module_kind = ModuleKind::Overlay(overlay.id);
             if let Some(target_symbols) = symbol_maps.get(module_kind) {
-                success &= self.check_symbol_map(&object_symbols, target_symbols, module_kind);
+                self.check_symbol_map(&object_symbols, target_symbols, &mut result, module_kind);
             }
         }
 
-        if self.fail && !success {
+        output::result("check symbols", &result);
+
+        if self.fail && result.num_failed > 0 {
             bail!("Some symbol(s) did not match.");
         }
 
         Ok(())
     }
 
-    fn check_symbol_map(&self, object: &SymbolMap, target: &SymbolMap, module_kind: ModuleKind) -> bool {
-        let mut success = true;
-
+    fn check_symbol_map(
+        &self,
+        object: &SymbolMap,
+        target: &SymbolMap,
+        result: &mut CheckSymbolsResult,
+        module_kind: ModuleKind,
+    ) {
         for target_symbol in target.iter_by_address(0..u32::MAX) {
+            result.num_checked += 1;
+            let diagnostic = |message: String| {
+                Diagnostic::error(message).module(module_kind).symbol(&target_symbol.name).address(target_symbol.addr)
+            };
+
             let Some(symbol_iter) = object.for_name(&target_symbol.name) else {
-                success = false;
-                log::error!("Symbol '{}' in {} not found in linked binary", target_symbol.name, module_kind);
+                result.num_failed += 1;
+                diagnostic(format!("Symbol '{}' in {} not found in linked binary", target_symbol.name, module_kind)).emit();
                 continue;
             };
             let symbols = symbol_iter.map(|(_, symbol)| symbol).collect::<Vec<_>>();
 
             let Some(matching_symbol) = symbols.iter().find(|symbol| symbol.addr == target_symbol.addr) else {
-                success = false;
+                result.num_failed += 1;
                 let addresses = symbols.iter().map(|symbol| format!("{:#010x}", symbol.addr)).collect::<Vec<_>>().join(", ");
-                log::error!(
+                diagnostic(format!(
                     "Symbol '{}' in {} is expected to be at {:#010x} but is at {}",
-                    target_symbol.name,
-                    module_kind,
-                    target_symbol.addr,
-                    addresses
-                );
+                    target_symbol.name, module_kind, target_symbol.addr, addresses
+                ))
+                .emit();
                 continue;
             };
 
@@ -90,28 +113,24 @@ impl CheckSymbols {
             // The object crate always interprets labels as local for some reason
             if !is_label {
                 if matching_symbol.local && !target_symbol.local {
-                    success = false;
-                    log::error!(
+                    result.num_failed += 1;
+                    diagnostic(format!(
                         "Symbol '{}' at {:#010x} in {} is expected to be global but is local",
-                        target_symbol.name,
-                        target_symbol.addr,
-                        module_kind
-                    );
+                        target_symbol.name, target_symbol.addr, module_kind
+                    ))
+                    .emit();
                     continue;
                 }
                 if !matching_symbol.local && target_symbol.local {
-                    success = false;
-                    log::error!(
+                    result.num_failed += 1;
+                    diagnostic(format!(
                         "Symbol '{}' at {:#010x} in {} is expected to be local but is global",
-                        target_symbol.name,
-                        target_symbol.addr,
-                        module_kind
-                    );
+                        target_symbol.name, target_symbol.addr, module_kind
+                    ))
+                    .emit();
                     continue;
                 }
             }
         }
-
-        success
     }
 }
@@ -0,0 +1,182 @@
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Result};
+use clap::Args;
+use ds_decomp::config::{config::Config, delinks::Delinks, symbol::SymbolMap};
+use objdiff_core::{
+    diff::{
+        display::{display_diff, DiffText},
+        ObjInsDiff, ObjInsDiffKind, ObjSectionDiff, ObjSymbolDiff,
+    },
+    obj::SymbolRef,
+};
+use serde::Serialize;
+
+use crate::util::{diff::ObjectDiff, output};
+
+#[derive(Serialize)]
+struct DiffResult<'a> {
+    symbol: &'a str,
+    target_path: String,
+    built_path: String,
+    rows: Vec<DiffRow>,
+    /// Whether the function is missing from the built object
+    missing: bool,
+    match_percent: Option<f32>,
+}
+
+/// A row of the diff, where `marker` is `|` for mismatches, `<` for deletions and `>` for insertions
+#[derive(Serialize)]
+struct DiffRow {
+    target: String,
+    marker: char,
+    built: String,
+}
+
+/// Prints an instruction diff of a function between its delinked and built object.
+#[derive(Args)]
+pub struct Diff {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Name of the function to diff.
+    pub symbol: String,
+}
+
+impl Diff {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let Some(file_path) = self.find_delink_file(config_path, &config)? else {
+            bail!("Symbol '{}' is not in any file of delinks.txt", self.symbol);
+        };
+        let built_path = config_path.join(&config.build_path).join(&file_path).with_extension("o");
+        let target_path = config_path.join(&config.delinks_path).join(&file_path).with_extension("o");
+        if !target_path.exists() {
+            bail!("Delinked object {} not found, run `dsd delink` first", target_path.display());
+        }
+        if !built_path.exists() {
+            bail!("Built object {} not found", built_path.display());
+        }
+
+        let object_diff = ObjectDiff::new(&target_path, &built_path)?;
+        let Some(target_ref) = Self::find_symbol(&object_diff, &self.symbol) else {
+            bail!("Symbol '{}' not found in {}", self.symbol, target_path.display());
+        };
+        let target_diff = Self::symbol_diff(&object_diff.target_diff.sections, target_ref);
+        let base_diff = target_diff.target_symbol.map(|base_ref| Self::symbol_diff(&object_diff.base_diff.sections, base_ref));
+
+        let target_rows = Self::format_instructions(&target_diff.instructions)?;
+        let base_rows = match base_diff {
+            Some(base_diff) => Self::format_instructions(&base_diff.instructions)?,
+            None => vec![],
+        };
+        let rows = target_diff
+            .instructions
+            .iter()
+            .enumerate()
+            .map(|(i, target_ins)| DiffRow {
+                target: target_rows.get(i).cloned().unwrap_or_default(),
+                marker: match target_ins.kind {
+                    ObjInsDiffKind::None => ' ',
+                    ObjInsDiffKind::OpMismatch | ObjInsDiffKind::ArgMismatch | ObjInsDiffKind::Replace => '|',
+                    ObjInsDiffKind::Delete => '<',
+                    ObjInsDiffKind::Insert => '>',
+                },
+                built: base_rows.get(i).cloned().unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+
+        if output::is_json() {
+            output::result(
+                "diff",
+                &DiffResult {
+                    symbol: &self.symbol,
+                    target_path: target_path.display().to_string(),
+                    built_path: built_path.display().to_string(),
+                    rows,
+                    missing: base_diff.is_none(),
+                    match_percent: target_diff.match_percent,
+                },
+            );
+            return Ok(());
+        }
+
+        let width = rows.iter().map(|row| row.target.len()).max().unwrap_or(0);
+        println!("{:width$}   {}", target_path.display(), built_path.display());
+        for row in &rows {
+            println!("{:width$} {} {}", row.target, row.marker, row.built);
+        }
+
+        match (base_diff, target_diff.match_percent) {
+            (None, _) => println!("\n{} is missing from the built object", self.symbol),
+            (Some(_), Some(percent)) => println!("\n{}: {percent:.2}% match", self.symbol),
+            (Some(_), None) => println!("\n{}: no match percentage", self.symbol),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the path of the delink file containing the symbol, without its extension.
+    fn find_delink_file(&self, config_path: &Path, config: &Config) -> Result<Option<String>> {
+        for (module_kind, module) in config.modules() {
+            let symbol_map = SymbolMap::from_file(config_path.join(&module.symbols))?;
+            let Some(mut symbols) = symbol_map.for_name(&self.symbol) else {
+                continue;
+            };
+            let (_, symbol) = symbols.next().unwrap();
+
+            let delinks = Delinks::from_module_config(config_path, module, module_kind)?;
+            let file = delinks
+                .files
+                .iter()
+                .filter(|file| !file.gap())
+                .find(|file| file.sections.get_by_contained_address(symbol.addr).is_some());
+            if let Some(file) = file {
+                let (file_path, _) = file.split_file_ext();
+                return Ok(Some(file_path.to_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    fn find_symbol(object_diff: &ObjectDiff, name: &str) -> Option<SymbolRef> {
+        object_diff.target.sections.iter().enumerate().find_map(|(section_idx, section)| {
+            let symbol_idx = section.symbols.iter().position(|symbol| symbol.name == name)?;
+            Some(SymbolRef { section_idx, symbol_idx })
+        })
+    }
+
+    fn symbol_diff(sections: &[ObjSectionDiff], symbol_ref: SymbolRef) -> &ObjSymbolDiff {
+        &sections[symbol_ref.section_idx].symbols[symbol_ref.symbol_idx]
+    }
+
+    /// Formats each instruction as a line of text. Padding rows, where the other side has an instruction, are empty.
+    fn format_instructions(instructions: &[ObjInsDiff]) -> Result<Vec<String>> {
+        let base_address = instructions.iter().find_map(|ins_diff| ins_diff.ins.as_ref()).map_or(0, |ins| ins.address);
+
+        let mut rows = vec![];
+        for ins_diff in instructions {
+            let mut row = String::new();
+            display_diff(ins_diff, base_address, |text| -> std::fmt::Result {
+                match text {
+                    DiffText::Basic(text) | DiffText::BasicColor(text, ..) => write!(row, "{text}"),
+                    DiffText::Line(_) | DiffText::Eol => Ok(()),
+                    DiffText::Address(address) => write!(row, "{address:>4x}: "),
+                    DiffText::Opcode(mnemonic, ..) => write!(row, "{mnemonic} "),
+                    DiffText::Argument(value, ..) => write!(row, "{value}"),
+                    DiffText::BranchDest(address, ..) => write!(row, "{address:x}"),
+                    DiffText::Symbol(symbol, ..) => write!(row, "{}", symbol.name),
+                    DiffText::Spacing(count) => write!(row, "{:count$}", ""),
+                }
+            })?;
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+}
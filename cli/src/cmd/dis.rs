@@ -1,5 +1,5 @@
 use std::{
-    fs::{create_dir_all, File},
+    fs::create_dir_all,
     io::{BufWriter, Write},
     path::{Path, PathBuf},
 };
@@ -15,14 +15,15 @@ use ds_decomp::config::{
     symbol::{InstructionMode, Symbol, SymbolKind, SymbolMaps},
 };
 use ds_rom::rom::{raw::AutoloadKind, Rom, RomLoadOptions};
+use serde::Serialize;
 
 use crate::{
     analysis::functions::FunctionExt,
-    config::{
-        delinks::DelinksExt,
-        symbol::{SymDataExt, SymbolLookup},
+    config::symbol::{SymDataExt, SymbolLookup},
+    util::{
+        io::{create_file, read_file},
+        output,
     },
-    util::io::{create_file, read_file},
 };
 
 /// Disassembles an extracted ROM.
@@ -41,6 +42,12 @@ pub struct Disassemble {
     pub ual: bool,
 }
 
+#[derive(Serialize)]
+struct DisassembleResult {
+    num_files: usize,
+    asm_path: String,
+}
+
 impl Disassemble {
     pub fn run(&self) -> Result<()> {
         let config = Config::from_file(&self.config_path)?;
@@ -53,14 +60,16 @@ impl Disassemble {
 
         let mut symbol_maps = SymbolMaps::from_config(config_path, &config)?;
 
-        self.disassemble_arm9(&config.main_module, &mut symbol_maps, &rom, extract_path)?;
-        self.disassemble_autoloads(&config.autoloads, &mut symbol_maps, &rom, extract_path)?;
+        let mut num_files = self.disassemble_arm9(&config.main_module, &mut symbol_maps, &rom, extract_path)?;
+        num_files += self.disassemble_autoloads(&config.autoloads, &mut symbol_maps, &rom, extract_path)?;
         if let Some(arm9_overlays) = &rom.config().arm9_overlays {
             let overlays_path = extract_path.join(arm9_overlays);
             let overlays_path = overlays_path.parent().unwrap();
-            self.disassemble_overlays(&config.overlays, &mut symbol_maps, overlays_path)?;
+            num_files += self.disassemble_overlays(&config.overlays, &mut symbol_maps, overlays_path)?;
         }
 
+        output::result("dis", &DisassembleResult { num_files, asm_path: self.asm_path.display().to_string() });
+
         Ok(())
     }
 
@@ -70,11 +79,11 @@ impl Disassemble {
         symbol_maps: &mut SymbolMaps,
         rom: &Rom,
         extract_path: &Path,
-    ) -> Result<()> {
+    ) -> Result<usize> {
         let config_path = self.config_path.parent().unwrap();
 
         let module_kind = ModuleKind::Arm9;
-        let delinks = Delinks::from_file_and_generate_gaps(config_path.join(&config.delinks), module_kind)?;
+        let delinks = Delinks::from_module_config(config_path, config, module_kind)?;
         let symbol_map = symbol_maps.get_mut(module_kind);
         let relocations = Relocations::from_file(config_path.join(&config.relocations))?;
 
@@ -91,7 +100,7 @@ impl Disassemble {
             )?;
         }
 
-        Ok(())
+        Ok(delinks.files.len())
     }
 
     fn disassemble_autoloads(
@@ -100,12 +109,13 @@ impl Disassemble {
         symbol_maps: &mut SymbolMaps,
         rom: &Rom,
         extract_path: &Path,
-    ) -> Result<()> {
+    ) -> Result<usize> {
+        let mut num_files = 0;
         for autoload in autoloads {
             let config_path = self.config_path.parent().unwrap();
 
             let module_kind = ModuleKind::Autoload(autoload.kind);
-            let delinks = Delinks::from_file_and_generate_gaps(config_path.join(&autoload.module.delinks), module_kind)?;
+            let delinks = Delinks::from_module_config(config_path, &autoload.module, module_kind)?;
             let symbol_map = symbol_maps.get_mut(module_kind);
             let relocations = Relocations::from_file(config_path.join(&autoload.module.relocations))?;
 
@@ -134,9 +144,10 @@ impl Disassemble {
                     symbol_maps,
                 )?;
             }
+            num_files += delinks.files.len();
         }
 
-        Ok(())
+        Ok(num_files)
     }
 
     fn disassemble_overlays(
@@ -144,12 +155,13 @@ impl Disassemble {
         overlays: &[ConfigOverlay],
         symbol_maps: &mut SymbolMaps,
         overlays_path: &Path,
-    ) -> Result<()> {
+    ) -> Result<usize> {
         let config_path = self.config_path.parent().unwrap();
 
+        let mut num_files = 0;
         for overlay in overlays {
             let module_kind = ModuleKind::Overlay(overlay.id);
-            let delinks = Delinks::from_file_and_generate_gaps(config_path.join(&overlay.module.delinks), module_kind)?;
+            let delinks = Delinks::from_module_config(config_path, &overlay.module, module_kind)?;
             let symbol_map = symbol_maps.get_mut(module_kind);
             let relocations = Relocations::from_file(config_path.join(&overlay.module.relocations))?;
 
@@ -172,9 +184,10 @@ impl Disassemble {
                     symbol_maps,
                 )?;
             }
+            num_files += delinks.files.len();
         }
 
-        Ok(())
+        Ok(num_files)
     }
 
     fn create_assembly_file<P: AsRef<Path>>(
@@ -186,25 +199,32 @@ impl Disassemble {
     ) -> Result<()> {
         let path = path.as_ref();
 
+        let mut body = vec![];
+        let uses_io_registers = self.disassemble(module, delink_file, &mut body, symbol_maps)?;
+
         create_dir_all(path.parent().unwrap())?;
         let asm_file = create_file(path)?;
         let mut writer = BufWriter::new(asm_file);
 
-        self.disassemble(module, delink_file, &mut writer, symbol_maps)?;
+        writeln!(writer, "    .include \"macros/function.inc\"")?;
+        if uses_io_registers {
+            writeln!(writer, "    .include \"io_registers.inc\"")?;
+        }
+        writeln!(writer)?;
+        writer.write_all(&body)?;
 
         Ok(())
     }
 
+    /// Returns whether I/O register names were written, which are defined in io_registers.inc.
     fn disassemble(
         &self,
         module: &Module,
         delink_file: &DelinkFile,
-        writer: &mut BufWriter<File>,
+        writer: &mut impl Write,
         symbol_maps: &SymbolMaps,
-    ) -> Result<()> {
-        writeln!(writer, "    .include \"macros/function.inc\"")?;
-        writeln!(writer)?;
-
+    ) -> Result<bool> {
+        let mut uses_io_registers = false;
         let symbol_map = symbol_maps.get(module.kind()).unwrap();
 
         for section in delink_file.sections.sorted_by_address() {
@@ -252,7 +272,13 @@ impl Disassemble {
                                 writeln!(writer)?;
                             }
 
-                            function.write_assembly(writer, &symbol_lookup, module.code(), module.base_address(), self.ual)?;
+                            uses_io_registers |= function.write_assembly(
+                                writer,
+                                &symbol_lookup,
+                                module.code(),
+                                module.base_address(),
+                                self.ual,
+                            )?;
                             offset = function.end_address() - section.start_address();
                         }
                     }
@@ -294,7 +320,7 @@ impl Disassemble {
             }
         }
 
-        Ok(())
+        Ok(uses_io_registers)
     }
 
     fn size_to_next_symbol(section: &Section, symbol: &Symbol, next: Option<&&Symbol>) -> u32 {
@@ -305,7 +331,7 @@ impl Disassemble {
         }
     }
 
-    fn dump_bytes(code: &[u8], mut offset: u32, end_offset: u32, writer: &mut BufWriter<File>) -> Result<()> {
+    fn dump_bytes(code: &[u8], mut offset: u32, end_offset: u32, writer: &mut impl Write) -> Result<()> {
         while offset < end_offset {
             write!(writer, "    .byte ")?;
             for i in 0..16.min(end_offset - offset) {
@@ -0,0 +1,248 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::Result;
+use clap::Args;
+use ds_decomp::{
+    config::{
+        config::Config,
+        module::ModuleKind,
+        section::SectionKind,
+        symbol::{InstructionMode, SymFunction, SymbolKind},
+    },
+    project::project::Project,
+};
+use ds_rom::rom::{Rom, RomLoadOptions};
+use object::{
+    elf,
+    write::{
+        elf::{FileHeader, SectionHeader, SectionIndex, Sym, Writer},
+        StringId,
+    },
+    Endianness,
+};
+use serde::Serialize;
+
+use crate::util::{io::create_file_and_dirs, output};
+
+/// Writes an ELF of the original code with every known symbol, for debuggers.
+#[derive(Args)]
+pub struct DebugElf {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Path to output ELF file.
+    #[arg(long, short = 'o')]
+    pub elf_path: PathBuf,
+}
+
+/// A section of a module, placed at its load address
+struct ElfSection<'a> {
+    name: String,
+    address: u32,
+    size: u32,
+    alignment: u32,
+    kind: SectionKind,
+    /// Initialized data, or `None` for .bss
+    data: Option<&'a [u8]>,
+    flags: u32,
+}
+
+struct ElfSymbol {
+    name: String,
+    /// Index into the list of [`ElfSection`]s
+    section: usize,
+    value: u32,
+    size: u32,
+    st_type: u8,
+    local: bool,
+}
+
+#[derive(Serialize)]
+struct DebugElfResult {
+    elf_path: String,
+    num_sections: usize,
+    num_symbols: usize,
+}
+
+impl DebugElf {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+        let rom_autoloads = rom.arm9().autoloads()?;
+        let project = Project::from_config(config_path, &config, &rom, &rom_autoloads)?;
+        let entry = rom.arm9_build_config()?.offsets.entry_function;
+
+        let mut sections = vec![];
+        let mut symbols = vec![];
+        for module in project.modules() {
+            let symbol_map = project.symbol_maps().get(module.kind()).unwrap();
+            for section in module.sections().sorted_by_address() {
+                // Overlays and autoloads share section names with the main module, so they are suffixed by module name
+                let name = match module.kind() {
+                    ModuleKind::Arm9 => section.name().to_string(),
+                    _ => format!("{}.{}", section.name(), module.name()),
+                };
+                let flags = match section.kind() {
+                    SectionKind::Code => elf::SHF_ALLOC | elf::SHF_EXECINSTR,
+                    SectionKind::Data if section.is_read_only() => elf::SHF_ALLOC,
+                    SectionKind::Data | SectionKind::Bss => elf::SHF_ALLOC | elf::SHF_WRITE,
+                };
+                let section_index = sections.len();
+                sections.push(ElfSection {
+                    name,
+                    address: section.start_address(),
+                    size: section.size(),
+                    alignment: section.alignment(),
+                    kind: section.kind(),
+                    data: section.code(module.code(), module.base_address())?,
+                    flags,
+                });
+
+                let mut section_symbols = symbol_map.iter_by_address(section.address_range()).peekable();
+                while let Some(symbol) = section_symbols.next() {
+                    if symbol.kind == SymbolKind::Undefined {
+                        continue;
+                    }
+                    let max_address = section_symbols.peek().map(|s| s.addr).unwrap_or(section.end_address());
+                    let thumb_bit = match symbol.kind {
+                        SymbolKind::Function(SymFunction { mode: InstructionMode::Thumb, .. }) => 1,
+                        _ => 0,
+                    };
+                    symbols.push(ElfSymbol {
+                        name: symbol.name.clone(),
+                        section: section_index,
+                        value: symbol.addr | thumb_bit,
+                        size: symbol.size(max_address),
+                        st_type: symbol.kind.as_elf_symbol_type(),
+                        local: symbol.local,
+                    });
+                    if let Some(name) = symbol.mapping_symbol_name() {
+                        symbols.push(ElfSymbol {
+                            name: name.to_string(),
+                            section: section_index,
+                            value: symbol.addr,
+                            size: 0,
+                            st_type: elf::STT_NOTYPE,
+                            local: true,
+                        });
+                    }
+                }
+            }
+        }
+
+        // Local symbols must come before global ones
+        symbols.sort_by_key(|symbol| !symbol.local);
+
+        let mut buffer = vec![];
+        Self::write(&mut buffer, entry, &sections, &symbols)?;
+        create_file_and_dirs(&self.elf_path)?.write_all(&buffer)?;
+
+        output::result(
+            "elf",
+            &DebugElfResult {
+                elf_path: self.elf_path.display().to_string(),
+                num_sections: sections.len(),
+                num_symbols: symbols.len(),
+            },
+        );
+
+        Ok(())
+    }
+
+    fn write(buffer: &mut Vec<u8>, entry: u32, sections: &[ElfSection], symbols: &[ElfSymbol]) -> Result<()> {
+        let mut writer = Writer::new(Endianness::Little, false, buffer);
+
+        // Reserve the file layout
+        writer.reserve_file_header();
+        writer.reserve_null_section_index();
+        let mut section_names: Vec<StringId> = vec![];
+        let mut section_indices: Vec<SectionIndex> = vec![];
+        let mut section_offsets = vec![];
+        for section in sections {
+            section_names.push(writer.add_section_name(section.name.as_bytes()));
+            section_indices.push(writer.reserve_section_index());
+            let offset = match section.data {
+                Some(data) => writer.reserve(data.len(), section.alignment as usize),
+                None => 0,
+            };
+            section_offsets.push(offset);
+        }
+
+        writer.reserve_null_symbol_index();
+        let mut symbol_names = vec![];
+        for symbol in symbols {
+            symbol_names.push(writer.add_string(symbol.name.as_bytes()));
+            writer.reserve_symbol_index(Some(section_indices[symbol.section]));
+        }
+        let num_local = 1 + symbols.iter().filter(|symbol| symbol.local).count() as u32;
+
+        writer.reserve_symtab_section_index();
+        writer.reserve_symtab();
+        writer.reserve_strtab_section_index();
+        writer.reserve_strtab();
+        writer.reserve_shstrtab_section_index();
+        writer.reserve_shstrtab();
+        writer.reserve_section_headers();
+
+        // Write the file
+        writer.write_file_header(&FileHeader {
+            os_abi: elf::ELFOSABI_NONE,
+            abi_version: 0,
+            e_type: elf::ET_EXEC,
+            e_machine: elf::EM_ARM,
+            e_entry: entry as u64,
+            e_flags: elf::EF_ARM_EABI_VER5,
+        })?;
+
+        for section in sections {
+            if let Some(data) = section.data {
+                writer.write_align(section.alignment as usize);
+                writer.write(data);
+            }
+        }
+
+        writer.write_null_symbol();
+        for (symbol, &name) in symbols.iter().zip(symbol_names.iter()) {
+            let bind = if symbol.local { elf::STB_LOCAL } else { elf::STB_GLOBAL };
+            writer.write_symbol(&Sym {
+                name: Some(name),
+                section: Some(section_indices[symbol.section]),
+                st_info: (bind << 4) | symbol.st_type,
+                st_other: elf::STV_DEFAULT,
+                st_shndx: 0,
+                st_value: symbol.value as u64,
+                st_size: symbol.size as u64,
+            });
+        }
+        writer.write_strtab();
+        writer.write_shstrtab();
+
+        writer.write_null_section_header();
+        for (i, section) in sections.iter().enumerate() {
+            let sh_type = if section.kind == SectionKind::Bss { elf::SHT_NOBITS } else { elf::SHT_PROGBITS };
+            writer.write_section_header(&SectionHeader {
+                name: Some(section_names[i]),
+                sh_type,
+                sh_flags: section.flags as u64,
+                sh_addr: section.address as u64,
+                sh_offset: section_offsets[i] as u64,
+                sh_size: section.size as u64,
+                sh_link: 0,
+                sh_info: 0,
+                sh_addralign: section.alignment as u64,
+                sh_entsize: 0,
+            });
+        }
+        writer.write_symtab_section_header(num_local);
+        writer.write_strtab_section_header();
+        writer.write_shstrtab_section_header();
+
+        Ok(())
+    }
+}
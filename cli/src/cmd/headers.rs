@@ -0,0 +1,96 @@
+use std::{
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+use ds_decomp::analysis::io_registers::{IoRegister, IoRegisterKind};
+use serde::Serialize;
+
+use crate::util::{io::create_file, output};
+
+/// Writes definitions of the hardware memory regions and the I/O registers which `dis` refers to by name.
+#[derive(Args)]
+pub struct Headers {
+    /// Output path for the header.
+    #[arg(long, short = 'o')]
+    pub output_path: PathBuf,
+
+    /// Output format.
+    #[arg(long, short = 'f', value_enum, default_value_t = HeaderFormat::C)]
+    pub format: HeaderFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum HeaderFormat {
+    /// C header with volatile register accesses
+    C,
+    /// Assembly include file with `.set` directives
+    Asm,
+}
+
+#[derive(Serialize)]
+struct HeadersResult {
+    output_path: String,
+    num_registers: usize,
+}
+
+impl Headers {
+    pub fn run(&self) -> Result<()> {
+        let mut writer = BufWriter::new(create_file(&self.output_path)?);
+        match self.format {
+            HeaderFormat::C => Self::write_c(&mut writer)?,
+            HeaderFormat::Asm => Self::write_asm(&mut writer)?,
+        }
+        writer.flush()?;
+
+        output::result(
+            "headers",
+            &HeadersResult { output_path: self.output_path.display().to_string(), num_registers: IoRegister::iter().count() },
+        );
+        Ok(())
+    }
+
+    fn write_c(w: &mut impl Write) -> Result<()> {
+        writeln!(w, "#ifndef IO_REGISTERS_H")?;
+        writeln!(w, "#define IO_REGISTERS_H")?;
+        writeln!(w)?;
+
+        for register in IoRegister::iter() {
+            let IoRegister { name, address, size, kind, dsi } = *register;
+            match (kind, size) {
+                (IoRegisterKind::Register, 1) => write!(w, "#define {name} (*(volatile unsigned char *){address:#010x})")?,
+                (IoRegisterKind::Register, 2) => write!(w, "#define {name} (*(volatile unsigned short *){address:#010x})")?,
+                (IoRegisterKind::Register, 4) => write!(w, "#define {name} (*(volatile unsigned int *){address:#010x})")?,
+                (IoRegisterKind::Register, 8) => {
+                    write!(w, "#define {name} (*(volatile unsigned long long *){address:#010x})")?
+                }
+                (IoRegisterKind::Register, _) => write!(w, "#define {name} ((volatile unsigned char *){address:#010x})")?,
+                (IoRegisterKind::Memory, _) => {
+                    writeln!(w, "#define {name}_SIZE {size:#x}")?;
+                    write!(w, "#define {name} {address:#010x}")?;
+                }
+            }
+            if dsi {
+                write!(w, " // DSi only")?;
+            }
+            writeln!(w)?;
+        }
+
+        writeln!(w)?;
+        writeln!(w, "#endif")?;
+        Ok(())
+    }
+
+    fn write_asm(w: &mut impl Write) -> Result<()> {
+        for register in IoRegister::iter() {
+            write!(w, "    .set {}, {:#010x}", register.name, register.address)?;
+            if register.dsi {
+                write!(w, " ; DSi only")?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
@@ -5,8 +5,9 @@ use clap::Args;
 use ds_decomp::config::{config::Config, module::ModuleKind, symbol::SymbolMaps};
 use ds_rom::rom::raw::AutoloadKind;
 use object::{Object, ObjectSection, ObjectSymbol};
+use serde::Serialize;
 
-use crate::util::io::read_file;
+use crate::util::{io::read_file, output};
 
 /// Imports symbol names from a previously built ELF file.
 #[derive(Args, Clone)]
@@ -28,6 +29,14 @@ pub struct ImportSymbols {
     dry: bool,
 }
 
+#[derive(Serialize)]
+struct SymbolRename {
+    module: String,
+    address: u32,
+    old_name: String,
+    new_name: String,
+}
+
 impl ImportSymbols {
     pub fn run(&self) -> Result<()> {
         let config = Config::from_file(&self.config_path)?;
@@ -38,6 +47,7 @@ impl ImportSymbols {
         let file = read_file(&self.elf_path)?;
         let object = object::File::parse(&*file)?;
 
+        let mut renames = vec![];
         for section in object.sections() {
             let section_name = section.name()?;
             log::debug!("Section: {section_name}");
@@ -78,11 +88,20 @@ impl ImportSymbols {
                     continue;
                 }
 
+                let old_name = symbol_map.by_address(address).ok().flatten().map(|(_, symbol)| symbol.name.clone());
                 let result = symbol_map.rename_by_address(address, &name);
                 if !is_default_name {
                     log::debug!("{address:#x} {name}");
                     result?;
                 }
+                if let Some(old_name) = old_name.filter(|old_name| *old_name != name) {
+                    renames.push(SymbolRename {
+                        module: module_kind.to_string(),
+                        address,
+                        old_name,
+                        new_name: name.into_owned(),
+                    });
+                }
             }
         }
 
@@ -90,6 +109,8 @@ impl ImportSymbols {
             symbol_maps.to_files(&config, config_path)?;
         }
 
+        log::info!("Renamed {} symbol(s)", renames.len());
+        output::result("import symbols", &renames);
         Ok(())
     }
 
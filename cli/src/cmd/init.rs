@@ -1,20 +1,32 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
 
 use anyhow::{bail, Result};
 use clap::Args;
-use ds_decomp::config::{
-    config::{Config, ConfigAutoload, ConfigModule, ConfigOverlay},
-    delinks::Delinks,
-    module::{AnalysisOptions, Module, ModuleKind},
-    symbol::SymbolMaps,
+use ds_decomp::{
+    config::{
+        config::{Config, ConfigAutoload, ConfigModule, ConfigOverlay},
+        delinks::Delinks,
+        hints::Hints,
+        module::{AnalysisOptions, Module, ModuleKind},
+        relocations::{Relocation, Relocations},
+        symbol::{SymbolMap, SymbolMaps},
+    },
+    project::project::Project,
 };
 use ds_rom::rom::{raw::AutoloadKind, Rom, RomConfig, RomLoadOptions};
 use path_slash::PathBufExt;
 use pathdiff::diff_paths;
+use serde::Serialize;
 
 use crate::{
-    config::program::Program,
-    util::io::{create_dir_all, create_file, open_file},
+    config::update::{compare_sections, merge_relocations, merge_symbols, UpdateReport},
+    util::{
+        io::{create_dir_all, create_file, open_file},
+        output::{self, Diagnostic},
+    },
 };
 
 /// Generates a config for the given extracted ROM.
@@ -48,6 +60,48 @@ pub struct Init {
     /// Adds a comment to every relocation in relocs.txt explaining where/why it was generated.
     #[arg(long, hide = true)]
     pub provide_reloc_source: bool,
+
+    /// Merges the analysis into an existing config instead of overwriting it. Conflicts are written to
+    /// update_report.txt in the output path.
+    #[arg(long, short = 'u')]
+    pub update: bool,
+}
+
+#[derive(Serialize)]
+struct InitResult {
+    modules: Vec<ModuleStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_conflicts: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct ModuleStats {
+    name: String,
+    kind: String,
+    num_functions: usize,
+    num_symbols: usize,
+    num_relocations: usize,
+}
+
+impl InitResult {
+    fn new(project: &Project, num_conflicts: Option<usize>) -> Self {
+        let modules = project
+            .modules()
+            .iter()
+            .map(|module| ModuleStats {
+                name: module.name().to_string(),
+                kind: module.kind().to_string(),
+                num_functions: module.sections().functions().count(),
+                num_symbols: project
+                    .symbol_maps()
+                    .get(module.kind())
+                    .map(|symbol_map| symbol_map.iter_by_address(0..u32::MAX).count())
+                    .unwrap_or(0),
+                num_relocations: module.relocations().iter().count(),
+            })
+            .collect();
+        Self { modules, num_conflicts }
+    }
 }
 
 impl Init {
@@ -60,12 +114,17 @@ impl Init {
         let arm9_output_path = self.output_path.join("arm9");
         let arm9_overlays_output_path = arm9_output_path.join("overlays");
         let arm9_config_path = arm9_output_path.join("config.yaml");
+        if self.update && !arm9_config_path.exists() {
+            bail!("Cannot update, no config found at {}", arm9_config_path.display());
+        }
 
         let mut symbol_maps = SymbolMaps::new();
 
         let analysis_options = AnalysisOptions {
             allow_unknown_function_calls: self.allow_unknown_function_calls,
             provide_reloc_source: self.provide_reloc_source,
+            not_pointers: Self::previous_not_pointers(&arm9_config_path)?,
+            hints: Self::load_hints(&rom, &arm9_output_path, &arm9_overlays_output_path)?,
         };
 
         let main = Module::analyze_arm9(rom.arm9(), &mut symbol_maps, &analysis_options)?;
@@ -84,9 +143,14 @@ impl Init {
             })
             .collect::<Result<Vec<_>>>()?;
 
-        let mut program = Program::new(main, overlays, autoloads, symbol_maps);
+        let mut project = Project::new(main, overlays, autoloads, symbol_maps);
         if !self.skip_reloc_analysis {
-            program.analyze_cross_references(&analysis_options)?;
+            project.analyze_cross_references(&analysis_options)?;
+        }
+        let ignored_relocations = project.apply_relocation_hints();
+
+        if self.update {
+            return self.update_configs(&arm9_config_path, &project, &ignored_relocations);
         }
 
         // Generate configs
@@ -100,19 +164,19 @@ impl Init {
         let overlay_configs = self.overlay_configs(
             &arm9_output_path,
             &arm9_overlays_output_path,
-            program.overlays(),
+            project.overlays(),
             "arm9",
-            program.symbol_maps(),
+            project.symbol_maps(),
         )?;
         let autoload_configs =
-            self.autoload_configs(&arm9_output_path, &rom_config, program.autoloads(), program.symbol_maps())?;
+            self.autoload_configs(&arm9_output_path, &rom_config, project.autoloads(), project.symbol_maps())?;
         let arm9_config = self.arm9_config(
             &arm9_output_path,
             &rom_config,
-            program.main(),
+            project.main(),
             overlay_configs,
             autoload_configs,
-            program.symbol_maps(),
+            project.symbol_maps(),
         )?;
 
         if !self.dry {
@@ -120,6 +184,121 @@ impl Init {
             serde_yml::to_writer(create_file(arm9_config_path)?, &arm9_config)?;
         }
 
+        output::result("init", &InitResult::new(&project, None));
+        Ok(())
+    }
+
+    /// Reads the hints.txt file of each module, which are next to its other config files.
+    fn load_hints(
+        rom: &Rom,
+        arm9_output_path: &Path,
+        arm9_overlays_output_path: &Path,
+    ) -> Result<BTreeMap<ModuleKind, Hints>> {
+        let mut hint_paths = vec![(ModuleKind::Arm9, arm9_output_path.join("hints.txt"))];
+        for autoload in rom.arm9().autoloads()?.iter() {
+            let name = match autoload.kind() {
+                AutoloadKind::Itcm => "itcm",
+                AutoloadKind::Dtcm => "dtcm",
+                AutoloadKind::Unknown(_) => continue,
+            };
+            hint_paths.push((ModuleKind::Autoload(autoload.kind()), arm9_output_path.join(name).join("hints.txt")));
+        }
+        for overlay in rom.arm9_overlays() {
+            let path = arm9_overlays_output_path.join(format!("ov{:03}", overlay.id())).join("hints.txt");
+            hint_paths.push((ModuleKind::Overlay(overlay.id()), path));
+        }
+
+        let mut hints = BTreeMap::new();
+        for (module_kind, path) in hint_paths {
+            if path.exists() {
+                log::info!("Using hints from {}", path.display());
+                hints.insert(module_kind, Hints::from_file(path)?);
+            }
+        }
+        Ok(hints)
+    }
+
+    /// Reads the words marked as not being pointers in the relocs.txt files of a previous `init`, so they stay marked.
+    fn previous_not_pointers(config_path: &Path) -> Result<BTreeMap<ModuleKind, BTreeSet<u32>>> {
+        let mut not_pointers = BTreeMap::new();
+        if !config_path.exists() {
+            return Ok(not_pointers);
+        }
+        let config = Config::from_file(config_path)?;
+        let config_dir = config_path.parent().unwrap();
+
+        for (module_kind, module) in config.modules() {
+            let relocations_path = config_dir.join(&module.relocations);
+            if !relocations_path.exists() {
+                continue;
+            }
+            let relocations = Relocations::from_file(relocations_path)?;
+            not_pointers.insert(module_kind, relocations.not_pointers().collect());
+        }
+        Ok(not_pointers)
+    }
+
+    /// Merges the analysis into the existing symbols.txt and relocs.txt files. Edits made by the user and delinks.txt files
+    /// are kept as they are, and anything that the analysis disagrees with is reported.
+    fn update_configs(
+        &self,
+        config_path: &Path,
+        project: &Project,
+        ignored_relocations: &BTreeMap<ModuleKind, Vec<Relocation>>,
+    ) -> Result<()> {
+        let config = Config::from_file(config_path)?;
+        let config_dir = config_path.parent().unwrap();
+        let mut report = UpdateReport::new();
+
+        for (module_kind, config_module) in config.modules() {
+            let name = &config_module.name;
+            let Some(module) = project.module_by_kind(module_kind) else {
+                report.add(name, "module is in the config but not in the ROM");
+                continue;
+            };
+            let code_hash = format!("{:016x}", fxhash::hash64(module.code()));
+            if code_hash != config_module.hash {
+                report.add(name, format!("code hash is {code_hash} but the config expects {}", config_module.hash));
+            }
+
+            let symbols_path = config_dir.join(&config_module.symbols);
+            let mut symbol_map = SymbolMap::from_file(&symbols_path)?;
+            merge_symbols(&mut symbol_map, project.symbol_maps().get(module_kind).unwrap(), name, &mut report);
+
+            let relocations_path = config_dir.join(&config_module.relocations);
+            let mut relocations = Relocations::from_file(&relocations_path)?;
+            let ignored = ignored_relocations.get(&module_kind).map_or(&[][..], |ignored| ignored.as_slice());
+            merge_relocations(&mut relocations, module.relocations(), ignored, name, &mut report)?;
+
+            let delinks = Delinks::from_file(config_dir.join(&config_module.delinks), module_kind)?;
+            compare_sections(&delinks.sections, module.sections(), name, &mut report);
+
+            if !self.dry {
+                symbol_map.to_file(&symbols_path)?;
+                relocations.to_file(&relocations_path)?;
+            }
+        }
+        for module in project.modules() {
+            if !config.modules().any(|(module_kind, _)| module_kind == module.kind()) {
+                report.add(module.name(), "module is in the ROM but not in the config, run init in a new directory to add it");
+            }
+        }
+
+        if output::is_json() {
+            for (module_name, message) in report.conflicts() {
+                Diagnostic::warn(message).module(module_name).emit();
+            }
+        }
+        if report.is_empty() {
+            log::info!("Config updated without conflicts");
+        } else {
+            let report_path = self.output_path.join("update_report.txt");
+            log::warn!("Config updated with {} conflicts, see {}", report.len(), report_path.display());
+            if !self.dry {
+                report.to_file(report_path)?;
+            }
+        }
+        output::result("init", &InitResult::new(project, Some(report.len())));
         Ok(())
     }
 
@@ -1,5 +1,4 @@
 use std::{
-    borrow::Cow,
     fs::File,
     io::{BufWriter, Write},
     path::{Path, PathBuf},
@@ -7,20 +6,21 @@ use std::{
 
 use anyhow::{bail, Result};
 use clap::Args;
-use ds_decomp::config::{
-    config::{Config, ConfigModule},
-    delinks::Delinks,
-    module::ModuleKind,
+use ds_decomp::{
+    analysis::overlay_groups::OverlayGroups,
+    config::{
+        config::{Config, ConfigModule},
+        delinks::Delinks,
+        module::ModuleKind,
+    },
 };
 use ds_rom::rom::{raw::AutoloadKind, Rom, RomLoadOptions};
+use serde::Serialize;
 
-use crate::{
-    analysis::overlay_groups::OverlayGroups,
-    config::{delinks::DelinksExt, section::SectionExt},
-    util::{
-        io::{create_dir_all, create_file_and_dirs},
-        path::PathExt,
-    },
+use crate::util::{
+    io::{create_dir_all, create_file_and_dirs},
+    output,
+    path::PathExt,
 };
 
 /// Generates linker scripts for all modules in a dsd config.
@@ -39,6 +39,12 @@ pub struct Lcf {
     pub objects_file: PathBuf,
 }
 
+#[derive(Serialize)]
+struct LcfResult {
+    lcf_file: String,
+    objects_file: String,
+}
+
 impl Lcf {
     pub fn run(&self) -> Result<()> {
         let config = Config::from_file(&self.config_path)?;
@@ -64,6 +70,14 @@ impl Lcf {
         self.write_keep_section_section(&mut lcf)?;
         self.write_sections_section(&mut lcf, &mut objects, config_dir, &config, &build_path, &delinks_path)?;
 
+        output::result(
+            "lcf",
+            &LcfResult {
+                lcf_file: self.lcf_file.display().to_string(),
+                objects_file: self.objects_file.display().to_string(),
+            },
+        );
+
         Ok(())
     }
 
@@ -173,6 +187,17 @@ impl Lcf {
         Ok(())
     }
 
+    /// Name of a module's memory region, e.g. ARM9, ITCM or OV000.
+    pub fn memory_name(module_kind: ModuleKind) -> Result<String> {
+        match module_kind {
+            ModuleKind::Arm9 => Ok("ARM9".to_string()),
+            ModuleKind::Overlay(id) => Ok(format!("OV{:03}", id)),
+            ModuleKind::Autoload(AutoloadKind::Itcm) => Ok("ITCM".to_string()),
+            ModuleKind::Autoload(AutoloadKind::Dtcm) => Ok("DTCM".to_string()),
+            ModuleKind::Autoload(_) => bail!("Unknown autoload kind"),
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn write_module_section(
         &self,
@@ -184,16 +209,17 @@ impl Lcf {
         build_path: &Path,
         delinks_path: &Path,
     ) -> Result<()> {
-        let (module_name, memory_name): (Cow<str>, Cow<str>) = match module_kind {
-            ModuleKind::Arm9 => (".arm9".into(), "ARM9".into()),
-            ModuleKind::Overlay(id) => (format!(".ov{:03}", id).into(), format!("OV{:03}", id).into()),
-            ModuleKind::Autoload(AutoloadKind::Itcm) => (".itcm".into(), "ITCM".into()),
-            ModuleKind::Autoload(AutoloadKind::Dtcm) => (".dtcm".into(), "DTCM".into()),
+        let module_name = match module_kind {
+            ModuleKind::Arm9 => ".arm9".to_string(),
+            ModuleKind::Overlay(id) => format!(".ov{:03}", id),
+            ModuleKind::Autoload(AutoloadKind::Itcm) => ".itcm".to_string(),
+            ModuleKind::Autoload(AutoloadKind::Dtcm) => ".dtcm".to_string(),
             ModuleKind::Autoload(_) => bail!("Unknown autoload kind"),
         };
+        let memory_name = Self::memory_name(module_kind)?;
 
         writeln!(lcf, "    {module_name} : {{")?;
-        let delinks = Delinks::from_file_and_generate_gaps(config_dir.join(&module.delinks), module_kind)?;
+        let delinks = Delinks::from_module_config(config_dir, module, module_kind)?;
         for section in delinks.sections.sorted_by_address() {
             writeln!(lcf, "        . = ALIGN({});", section.alignment())?;
             let section_boundary_name = section.boundary_name();
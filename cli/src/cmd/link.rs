@@ -0,0 +1,168 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use ds_decomp::{
+    analysis::overlay_groups::OverlayGroups,
+    config::{config::Config, delinks::Delinks, module::ModuleKind, symbol::SymbolMap},
+    link::{
+        layout::{LinkLayout, LinkModule, LinkObject, LinkOrigin, LinkSection},
+        linker,
+    },
+};
+use ds_rom::rom::{Rom, RomLoadOptions};
+use serde::Serialize;
+
+use crate::{
+    cmd::Lcf,
+    util::{
+        io::{create_file_and_dirs, read_file},
+        output,
+    },
+};
+
+/// Links delinked and built objects without mwld, writing the module binaries and a linked ELF.
+#[derive(Args)]
+pub struct Link {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Path to output ELF file.
+    #[arg(long, short = 'o')]
+    pub elf_path: PathBuf,
+}
+
+#[derive(Serialize)]
+struct LinkResult {
+    elf_path: String,
+    modules: Vec<LinkedModuleResult>,
+}
+
+#[derive(Serialize)]
+struct LinkedModuleResult {
+    name: String,
+    base_address: u32,
+    code_size: usize,
+    bss_size: u32,
+}
+
+impl Link {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+        let rom_autoloads = rom.arm9().autoloads()?;
+        let overlay_groups = OverlayGroups::analyze(rom.arm9().end_address()?, rom.arm9_overlays())?;
+
+        // Modules in the same order as the LCF, so that overlays come after the modules they are placed after
+        let mut modules = vec![(ModuleKind::Arm9, &config.main_module, LinkOrigin::Address(rom.arm9().base_address()))];
+        for autoload in &config.autoloads {
+            let rom_autoload = rom_autoloads
+                .iter()
+                .find(|rom_autoload| rom_autoload.kind() == autoload.kind)
+                .with_context(|| format!("Failed to find autoload {} in ROM", autoload.kind))?;
+            modules.push((
+                ModuleKind::Autoload(autoload.kind),
+                &autoload.module,
+                LinkOrigin::Address(rom_autoload.base_address()),
+            ));
+        }
+        for group in overlay_groups.iter() {
+            let after = if group.after.is_empty() {
+                vec!["ARM9".to_string()]
+            } else {
+                group.after.iter().map(|&id| Lcf::memory_name(ModuleKind::Overlay(id))).collect::<Result<_>>()?
+            };
+            for &overlay_id in &group.overlays {
+                let overlay = config
+                    .overlays
+                    .iter()
+                    .find(|overlay| overlay.id == overlay_id)
+                    .with_context(|| format!("Failed to find overlay {overlay_id} in config"))?;
+                modules.push((ModuleKind::Overlay(overlay_id), &overlay.module, LinkOrigin::After(after.clone())));
+            }
+        }
+
+        let build_path = config_path.join(&config.build_path);
+        let delinks_path = config_path.join(&config.delinks_path);
+        let mut objects = vec![];
+        let mut layout = LinkLayout { modules: vec![], entry: "Entry".to_string() };
+        let mut binary_paths = vec![];
+        for (module_kind, module, origin) in modules {
+            let delinks = Delinks::from_module_config(config_path, module, module_kind)?;
+
+            // Veneers are left out of every object, so linking without them would shift everything after them
+            let symbol_map = SymbolMap::from_file(config_path.join(&module.symbols))?;
+            if let Some((_, veneer)) = symbol_map.functions().find(|(function, _)| function.veneer) {
+                bail!(
+                    "The built-in linker can't generate veneers, but {} has veneer {} at {:#010x}. Link with mwldarm.exe \
+                     instead.",
+                    module_kind,
+                    veneer.name,
+                    veneer.addr
+                );
+            }
+
+            let first_object = objects.len();
+            for file in &delinks.files {
+                let (file_path, _) = file.split_file_ext();
+                let base_path = if file.complete { &build_path } else { &delinks_path };
+                let path = base_path.join(file_path).with_extension("o");
+                objects.push(LinkObject { name: path.display().to_string(), data: read_file(&path)? });
+            }
+
+            let sections = delinks
+                .sections
+                .sorted_by_address()
+                .into_iter()
+                .map(|section| LinkSection {
+                    name: section.name().to_string(),
+                    boundary_name: section.boundary_name(),
+                    alignment: section.alignment(),
+                    objects: delinks
+                        .files
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, file)| file.sections.by_name(section.name()).is_some())
+                        .map(|(index, _)| first_object + index)
+                        .collect(),
+                })
+                .collect();
+            layout.modules.push(LinkModule { name: Lcf::memory_name(module_kind)?, origin, sections });
+            binary_paths.push(config_path.join(&module.object));
+        }
+
+        let output = linker::link(&layout, &objects)?;
+
+        for (linked_module, binary_path) in output.modules.iter().zip(binary_paths) {
+            create_file_and_dirs(binary_path)?.write_all(&linked_module.code)?;
+            log::info!(
+                "Linked {} at {:#010x}, {:#x} bytes and {:#x} bytes of .bss",
+                linked_module.name,
+                linked_module.base_address,
+                linked_module.code.len(),
+                linked_module.bss_size
+            );
+        }
+        create_file_and_dirs(&self.elf_path)?.write_all(&output.to_elf()?)?;
+
+        let modules = output
+            .modules
+            .iter()
+            .map(|module| LinkedModuleResult {
+                name: module.name.clone(),
+                base_address: module.base_address,
+                code_size: module.code.len(),
+                bss_size: module.bss_size,
+            })
+            .collect();
+        output::result("link", &LinkResult { elf_path: self.elf_path.display().to_string(), modules });
+
+        Ok(())
+    }
+}
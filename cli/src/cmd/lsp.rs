@@ -0,0 +1,565 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::Display,
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use clap::Args;
+use ds_decomp::config::{
+    config::{Config, ConfigModule},
+    delinks::{CompilerOptions, DelinkFile},
+    module::ModuleKind,
+    relocations::{parse_not_pointer, Relocation},
+    section::{Section, Sections},
+    symbol::{Symbol, SymbolKind},
+    ParseContext,
+};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics},
+    request::{Completion, GotoDefinition, HoverRequest, Request as _},
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse, Diagnostic,
+    DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability, Location,
+    MarkupContent, MarkupKind, OneOf, Position, PublishDiagnosticsParams, Range, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+
+/// Runs a language server for symbols.txt, relocs.txt and delinks.txt over stdio.
+#[derive(Args)]
+pub struct Lsp {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+}
+
+impl Lsp {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_dir = self.config_path.parent().unwrap();
+        let mut server = Server::new(config_dir, &config);
+
+        let (connection, io_threads) = Connection::stdio();
+        let capabilities = ServerCapabilities {
+            text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
+            definition_provider: Some(OneOf::Left(true)),
+            completion_provider: Some(CompletionOptions {
+                trigger_characters: Some(vec![":".to_string(), "(".to_string(), ",".to_string()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        connection.initialize(serde_json::to_value(capabilities)?)?;
+        log::info!("Language server initialized");
+
+        server.main_loop(&connection)?;
+        io_threads.join()?;
+
+        Ok(())
+    }
+}
+
+/// Options which may be set for the module or for a file in delinks.txt
+const COMPILER_OPTIONS: [&str; 3] = ["compiler:", "flags:", "preset:"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    Symbols,
+    Relocations,
+    Delinks,
+}
+
+struct ConfigFile {
+    path: PathBuf,
+    kind: FileKind,
+    module_kind: ModuleKind,
+}
+
+struct Server {
+    files: Vec<ConfigFile>,
+    overlay_ids: Vec<u16>,
+    /// Contents of documents currently opened by the client, these take precedence over the files on disk
+    documents: HashMap<PathBuf, String>,
+}
+
+impl Server {
+    fn new(config_dir: &Path, config: &Config) -> Self {
+        let mut files = vec![];
+        let mut add_module = |module: &ConfigModule, module_kind: ModuleKind| {
+            for (path, kind) in [
+                (&module.symbols, FileKind::Symbols),
+                (&module.relocations, FileKind::Relocations),
+                (&module.delinks, FileKind::Delinks),
+            ] {
+                let path = config_dir.join(path);
+                let path = path.canonicalize().unwrap_or(path);
+                files.push(ConfigFile { path, kind, module_kind });
+            }
+        };
+        for (module_kind, module) in config.modules() {
+            add_module(module, module_kind);
+        }
+
+        let overlay_ids = config.overlays.iter().map(|overlay| overlay.id).collect();
+
+        Self { files, overlay_ids, documents: HashMap::new() }
+    }
+
+    fn main_loop(&mut self, connection: &Connection) -> Result<()> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request)?;
+                    connection.sender.send(response.into())?;
+                }
+                Message::Notification(notification) => {
+                    if let Some(diagnostics) = self.handle_notification(notification)? {
+                        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), diagnostics);
+                        connection.sender.send(notification.into())?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Result<Response> {
+        let method = request.method.clone();
+        match method.as_str() {
+            HoverRequest::METHOD => {
+                let (id, params) = request.extract::<HoverParams>(HoverRequest::METHOD)?;
+                Ok(Response::new_ok(id, self.hover(params)))
+            }
+            GotoDefinition::METHOD => {
+                let (id, params) = request.extract::<GotoDefinitionParams>(GotoDefinition::METHOD)?;
+                Ok(Response::new_ok(id, self.definition(params)))
+            }
+            Completion::METHOD => {
+                let (id, params) = request.extract::<CompletionParams>(Completion::METHOD)?;
+                Ok(Response::new_ok(id, self.completion(params)))
+            }
+            method => {
+                Ok(Response::new_err(request.id, ErrorCode::MethodNotFound as i32, format!("Unsupported request '{method}'")))
+            }
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<Option<PublishDiagnosticsParams>> {
+        let method = notification.method.clone();
+        let uri = match method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = notification.extract::<DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD)?;
+                let Some(path) = Self::uri_to_path(&params.text_document.uri) else { return Ok(None) };
+                self.documents.insert(path, params.text_document.text);
+                params.text_document.uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let params = notification.extract::<DidChangeTextDocumentParams>(DidChangeTextDocument::METHOD)?;
+                let Some(path) = Self::uri_to_path(&params.text_document.uri) else { return Ok(None) };
+                // Full sync, so the last change contains the whole document
+                let Some(change) = params.content_changes.into_iter().last() else { return Ok(None) };
+                self.documents.insert(path, change.text);
+                params.text_document.uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let params = notification.extract::<DidCloseTextDocumentParams>(DidCloseTextDocument::METHOD)?;
+                if let Some(path) = Self::uri_to_path(&params.text_document.uri) {
+                    self.documents.remove(&path);
+                }
+                return Ok(Some(PublishDiagnosticsParams::new(params.text_document.uri, vec![], None)));
+            }
+            _ => return Ok(None),
+        };
+
+        let Some(path) = Self::uri_to_path(&uri) else { return Ok(None) };
+        let Some(kind) = self.file_kind(&path) else { return Ok(None) };
+        let Some(text) = self.documents.get(&path) else { return Ok(None) };
+
+        let diagnostics = Self::diagnostics(&path, kind, text);
+        Ok(Some(PublishDiagnosticsParams::new(uri, diagnostics, None)))
+    }
+
+    fn uri_to_path(uri: &Url) -> Option<PathBuf> {
+        let path = uri.to_file_path().ok()?;
+        Some(path.canonicalize().unwrap_or(path))
+    }
+
+    fn file_kind(&self, path: &Path) -> Option<FileKind> {
+        if let Some(file) = self.files.iter().find(|file| file.path == path) {
+            return Some(file.kind);
+        }
+        // Not part of the loaded config, but we can still check the syntax
+        match path.file_name()?.to_str()? {
+            "symbols.txt" => Some(FileKind::Symbols),
+            "relocs.txt" => Some(FileKind::Relocations),
+            "delinks.txt" => Some(FileKind::Delinks),
+            _ => None,
+        }
+    }
+
+    fn module_file(&self, module_kind: ModuleKind, kind: FileKind) -> Option<&ConfigFile> {
+        self.files.iter().find(|file| file.module_kind == module_kind && file.kind == kind)
+    }
+
+    fn text(&self, path: &Path) -> Option<Cow<str>> {
+        match self.documents.get(path) {
+            Some(text) => Some(Cow::Borrowed(text)),
+            None => std::fs::read_to_string(path).ok().map(Cow::Owned),
+        }
+    }
+
+    fn strip_comment(line: &str) -> &str {
+        let comment_start = line.find("//").unwrap_or(line.len());
+        &line[..comment_start]
+    }
+
+    fn diagnostics(path: &Path, kind: FileKind, text: &str) -> Vec<Diagnostic> {
+        let mut context = ParseContext { file_path: path.to_string_lossy().to_string(), row: 0 };
+        let mut diagnostics = vec![];
+
+        match kind {
+            FileKind::Symbols => {
+                for line in text.lines() {
+                    context.row += 1;
+                    if let Err(error) = Symbol::parse(Self::strip_comment(line), &context) {
+                        diagnostics.push(Self::diagnostic(&context, text, error));
+                    }
+                }
+            }
+            FileKind::Relocations => {
+                for line in text.lines() {
+                    context.row += 1;
+                    let line = Self::strip_comment(line);
+                    let result = match parse_not_pointer(line, &context) {
+                        Ok(Some(_)) => continue,
+                        Ok(None) => Relocation::parse(line, &context),
+                        Err(error) => Err(error),
+                    };
+                    if let Err(error) = result {
+                        diagnostics.push(Self::diagnostic(&context, text, error));
+                    }
+                }
+            }
+            FileKind::Delinks => {
+                let mut sections = Sections::new();
+                let mut in_files = false;
+                let mut lines = text.as_bytes().lines();
+                while let Some(Ok(line)) = lines.next() {
+                    context.row += 1;
+                    let line = Self::strip_comment(&line);
+
+                    if line.chars().next().is_some_and(|c| !c.is_whitespace()) {
+                        in_files = true;
+                        if let Err(error) = DelinkFile::parse(line, &mut lines, &mut context, &sections) {
+                            diagnostics.push(Self::diagnostic(&context, text, error));
+
+                            // Skip the rest of this file's sections
+                            for line in lines.by_ref() {
+                                context.row += 1;
+                                if line.map_or(true, |line| line.trim().is_empty()) {
+                                    break;
+                                }
+                            }
+                        }
+                    } else if !in_files {
+                        match CompilerOptions::is_option_line(line.trim(), &context) {
+                            Ok(true) => continue,
+                            Ok(false) => {}
+                            Err(error) => {
+                                diagnostics.push(Self::diagnostic(&context, text, error));
+                                continue;
+                            }
+                        }
+                        match Section::parse(line, &context) {
+                            Ok(Some(section)) => {
+                                if let Err(error) = sections.add(section) {
+                                    diagnostics.push(Self::diagnostic(&context, text, error));
+                                }
+                            }
+                            Ok(None) => {}
+                            Err(error) => diagnostics.push(Self::diagnostic(&context, text, error)),
+                        }
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    fn diagnostic(context: &ParseContext, text: &str, error: impl Display) -> Diagnostic {
+        let message = error.to_string();
+        let message = message.strip_prefix(&format!("{context}: ")).unwrap_or(&message);
+        // Leave out the backtrace
+        let message = message.split_once('\n').map_or(message, |(message, _)| message);
+        let message = message.strip_suffix(':').unwrap_or(message);
+
+        let row = context.row.saturating_sub(1);
+        Diagnostic {
+            range: Self::line_range(text, row),
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("dsd".to_string()),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn line_range(text: &str, row: usize) -> Range {
+        let length = text.lines().nth(row).map_or(0, |line| line.encode_utf16().count());
+        Range::new(Position::new(row as u32, 0), Position::new(row as u32, length as u32))
+    }
+
+    /// Returns the relocation at the given position as well as the module which the relocations file belongs to
+    fn relocation_at(&self, uri: &Url, position: Position) -> Option<(Relocation, ModuleKind, String)> {
+        let path = Self::uri_to_path(uri)?;
+        let file = self.files.iter().find(|file| file.path == path && file.kind == FileKind::Relocations)?;
+        let text = self.text(&path)?;
+        let line = text.lines().nth(position.line as usize)?;
+
+        let context = ParseContext { file_path: path.to_string_lossy().to_string(), row: position.line as usize + 1 };
+        let relocation = Relocation::parse(Self::strip_comment(line), &context).ok()??;
+        Some((relocation, file.module_kind, line.to_string()))
+    }
+
+    /// Parses the symbols of a module, skipping invalid lines. Each symbol is paired with its row in symbols.txt.
+    fn symbols(&self, module_kind: ModuleKind) -> Option<(&Path, Vec<(usize, Symbol)>)> {
+        let file = self.module_file(module_kind, FileKind::Symbols)?;
+        let text = self.text(&file.path)?;
+
+        let mut context = ParseContext { file_path: file.path.to_string_lossy().to_string(), row: 0 };
+        let mut symbols = vec![];
+        for line in text.lines() {
+            context.row += 1;
+            if let Ok(Some(symbol)) = Symbol::parse(Self::strip_comment(line), &context) {
+                symbols.push((context.row - 1, symbol));
+            }
+        }
+        Some((file.path.as_path(), symbols))
+    }
+
+    fn find_symbol(symbols: &[(usize, Symbol)], address: u32) -> Option<&(usize, Symbol)> {
+        symbols.iter().find(|(_, symbol)| symbol.addr == address).or_else(|| {
+            symbols.iter().find(|(_, symbol)| matches!(symbol.kind, SymbolKind::Function(_)) && symbol.addr == address & !1)
+        })
+    }
+
+    fn find_function_containing(symbols: &[(usize, Symbol)], address: u32) -> Option<&(usize, Symbol)> {
+        symbols.iter().find(|(_, symbol)| match symbol.kind {
+            SymbolKind::Function(function) => (symbol.addr..symbol.addr + function.size).contains(&address),
+            _ => false,
+        })
+    }
+
+    fn target_modules(relocation: &Relocation) -> Vec<ModuleKind> {
+        let module = relocation.module();
+        module.first_module().into_iter().chain(module.other_modules().into_iter().flatten()).collect()
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let (relocation, module_kind, _) = self.relocation_at(&position.text_document.uri, position.position)?;
+
+        let mut value = String::new();
+
+        let from = relocation.from_address();
+        let from_symbol = self.symbols(module_kind).and_then(|(_, symbols)| {
+            Self::find_function_containing(&symbols, from).map(|(_, symbol)| (symbol.name.clone(), from - symbol.addr))
+        });
+        match from_symbol {
+            Some((name, offset)) => value += &format!("**from** `{name}+{offset:#x}` in {module_kind}\n\n"),
+            None => value += &format!("**from** `{from:#010x}` in {module_kind}\n\n"),
+        }
+
+        let to = relocation.to_address();
+        let target_modules = Self::target_modules(&relocation);
+        if target_modules.is_empty() {
+            value += &format!("**to** `{to:#010x}`, unknown module");
+        }
+        for target_module in target_modules {
+            let to_symbol = self.symbols(target_module).and_then(|(_, symbols)| {
+                Self::find_symbol(&symbols, to).map(|(_, symbol)| format!("`{}` ({})", symbol.name, symbol.kind))
+            });
+            match to_symbol {
+                Some(symbol) => value += &format!("**to** {symbol} in {target_module}\n\n"),
+                None => value += &format!("**to** `{to:#010x}` in {target_module}, no symbol found\n\n"),
+            }
+        }
+
+        Some(Hover { contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }), range: None })
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let (relocation, module_kind, line) = self.relocation_at(&position.text_document.uri, position.position)?;
+
+        let word = Self::word_at(&line, position.position.character as usize)?;
+        let locations = if word.starts_with("to:") {
+            let to = relocation.to_address();
+            Self::target_modules(&relocation)
+                .into_iter()
+                .filter_map(|target_module| {
+                    let (path, symbols) = self.symbols(target_module)?;
+                    let &(row, _) = Self::find_symbol(&symbols, to)?;
+                    self.location(path, row)
+                })
+                .collect::<Vec<_>>()
+        } else if word.starts_with("from:") {
+            let (path, symbols) = self.symbols(module_kind)?;
+            let &(row, _) = Self::find_function_containing(&symbols, relocation.from_address())?;
+            self.location(path, row).into_iter().collect()
+        } else {
+            return None;
+        };
+
+        Some(GotoDefinitionResponse::Array(locations))
+    }
+
+    fn location(&self, path: &Path, row: usize) -> Option<Location> {
+        let uri = Url::from_file_path(path).ok()?;
+        let text = self.text(path)?;
+        Some(Location::new(uri, Self::line_range(&text, row)))
+    }
+
+    /// Returns the whitespace-separated word which contains the given column
+    fn word_at(line: &str, column: usize) -> Option<&str> {
+        let mut start = 0;
+        for word in line.split(char::is_whitespace) {
+            let end = start + word.len();
+            if (start..=end).contains(&column) && !word.is_empty() {
+                return Some(word);
+            }
+            start = end + 1;
+        }
+        None
+    }
+
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let position = params.text_document_position;
+        let path = Self::uri_to_path(&position.text_document.uri)?;
+        let kind = self.file_kind(&path)?;
+        let text = self.text(&path)?;
+
+        let row = position.position.line as usize;
+        let line = text.lines().nth(row).unwrap_or("");
+        let mut column = (position.position.character as usize).min(line.len());
+        while !line.is_char_boundary(column) {
+            column -= 1;
+        }
+        let before = &line[..column];
+        let (word, word_index) = if before.is_empty() || before.ends_with(char::is_whitespace) {
+            ("", before.split_whitespace().count())
+        } else {
+            (before.split_whitespace().last().unwrap(), before.split_whitespace().count() - 1)
+        };
+
+        let items = match kind {
+            FileKind::Symbols => Self::complete_symbol(word, word_index),
+            FileKind::Relocations => self.complete_relocation(word),
+            FileKind::Delinks => Self::complete_delinks(&text, row, word, word_index),
+        };
+
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn completion_items(labels: &[&str], kind: CompletionItemKind) -> Vec<CompletionItem> {
+        labels
+            .iter()
+            .map(|label| CompletionItem { label: label.to_string(), kind: Some(kind), ..Default::default() })
+            .collect()
+    }
+
+    fn complete_symbol(word: &str, word_index: usize) -> Vec<CompletionItem> {
+        // The first word is the symbol name
+        if word_index == 0 {
+            return vec![];
+        }
+        let Some(kind) = word.strip_prefix("kind:") else {
+            return Self::completion_items(&["kind:", "addr:", "ambiguous", "local"], CompletionItemKind::PROPERTY);
+        };
+        let Some((kind, _)) = kind.split_once('(') else {
+            return Self::completion_items(&["function", "data", "bss", "label"], CompletionItemKind::ENUM_MEMBER);
+        };
+        match kind {
+            "function" => Self::completion_items(&["arm", "thumb", "size=", "unknown", "veneer"], CompletionItemKind::VALUE),
+            "label" => Self::completion_items(&["arm", "thumb"], CompletionItemKind::VALUE),
+            "data" => Self::completion_items(&["any", "byte", "short", "word"], CompletionItemKind::VALUE),
+            "bss" => Self::completion_items(&["size="], CompletionItemKind::VALUE),
+            _ => vec![],
+        }
+    }
+
+    fn complete_relocation(&self, word: &str) -> Vec<CompletionItem> {
+        if word.starts_with("kind:") {
+            return Self::completion_items(
+                &["arm_call", "thumb_call", "arm_call_thumb", "thumb_call_arm", "arm_branch", "load"],
+                CompletionItemKind::ENUM_MEMBER,
+            );
+        }
+        let Some(module) = word.strip_prefix("module:") else {
+            let mut items =
+                Self::completion_items(&["from:", "kind:", "to:", "add:", "module:"], CompletionItemKind::PROPERTY);
+            items.extend(Self::completion_items(&["not_pointer"], CompletionItemKind::KEYWORD));
+            return items;
+        };
+        if module.starts_with("overlay(") || module.starts_with("overlays(") {
+            let ids = self.overlay_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+            let ids = ids.iter().map(|id| id.as_str()).collect::<Vec<_>>();
+            return Self::completion_items(&ids, CompletionItemKind::VALUE);
+        }
+        Self::completion_items(&["none", "overlay", "overlays", "main", "itcm", "dtcm"], CompletionItemKind::ENUM_MEMBER)
+    }
+
+    fn complete_delinks(text: &str, row: usize, word: &str, word_index: usize) -> Vec<CompletionItem> {
+        // Nothing to complete in file names
+        if word_index == 0
+            && !word.is_empty()
+            && text.lines().nth(row).is_some_and(|line| !line.starts_with(char::is_whitespace))
+        {
+            return vec![];
+        }
+
+        let mut header = vec![];
+        let mut in_files = false;
+        for line in text.lines().take(row) {
+            let line = Self::strip_comment(line);
+            if line.chars().next().is_some_and(|c| !c.is_whitespace()) {
+                in_files = true;
+                break;
+            }
+            if let Some(name) = line.split_whitespace().next().filter(|name| !name.contains(':')) {
+                header.push(name);
+            }
+        }
+
+        if !in_files {
+            if word_index == 0 {
+                let mut items = Self::completion_items(
+                    &[".text", ".init", ".ctor", ".rodata", ".data", ".bss", ".sbss"],
+                    CompletionItemKind::FIELD,
+                );
+                items.extend(Self::completion_items(&COMPILER_OPTIONS, CompletionItemKind::PROPERTY));
+                return items;
+            }
+            if word.starts_with("kind:") {
+                return Self::completion_items(&["code", "data", "bss"], CompletionItemKind::ENUM_MEMBER);
+            }
+            return Self::completion_items(&["kind:", "start:", "end:", "align:"], CompletionItemKind::PROPERTY);
+        }
+
+        if word_index == 0 {
+            let mut items = Self::completion_items(&header, CompletionItemKind::FIELD);
+            items.extend(Self::completion_items(&["complete"], CompletionItemKind::KEYWORD));
+            items.extend(Self::completion_items(&COMPILER_OPTIONS, CompletionItemKind::PROPERTY));
+            return items;
+        }
+        Self::completion_items(&["start:", "end:"], CompletionItemKind::PROPERTY)
+    }
+}
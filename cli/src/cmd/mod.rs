@@ -1,17 +1,35 @@
+mod call_graph;
 mod check;
 mod delink;
+mod diff;
 mod dis;
+mod elf;
+mod headers;
 mod import;
 mod init;
 mod lcf;
+mod link;
+mod lsp;
+mod ninja;
 mod objdiff;
+mod relocs;
 mod rom;
+mod xref;
 
+pub use call_graph::*;
 pub use check::*;
 pub use delink::*;
+pub use diff::*;
 pub use dis::*;
+pub use elf::*;
+pub use headers::*;
 pub use import::*;
 pub use init::*;
 pub use lcf::*;
+pub use link::*;
+pub use lsp::*;
+pub use ninja::*;
 pub use objdiff::*;
+pub use relocs::*;
 pub use rom::*;
+pub use xref::*;
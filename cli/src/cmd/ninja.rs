@@ -0,0 +1,302 @@
+use std::{
+    io::{BufWriter, Write},
+    iter,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use ds_decomp::config::{
+    config::{Config, ConfigModule},
+    delinks::{CompilerOptions, Delinks},
+    module::ModuleKind,
+};
+use ds_rom::rom::raw::AutoloadKind;
+use serde::Serialize;
+
+use crate::util::{io::create_file, output, path::PathExt};
+
+/// Generates a build.ninja which delinks, compiles, links, builds the ROM and checks the result.
+#[derive(Args)]
+pub struct Ninja {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Path to build.ninja. Commands are run from its directory.
+    #[arg(long, short = 'o', default_value = "build.ninja")]
+    pub output_path: PathBuf,
+
+    /// Directory of compilers, containing `<compiler>/mwccarm.exe` and `<compiler>/mwldarm.exe` for each compiler ID used
+    /// in delinks.txt.
+    #[arg(long, short = 'C')]
+    pub compilers_path: PathBuf,
+
+    /// Compiler ID whose linker to use. Defaults to the compiler of the main module.
+    #[arg(long, short = 'l')]
+    pub linker: Option<String>,
+
+    /// Command to run the compiler and linker with, such as `wine`.
+    #[arg(long, short = 'w')]
+    pub wrapper: Option<String>,
+
+    /// Path to the built ROM.
+    #[arg(long, short = 'r', default_value = "build/rom.nds")]
+    pub rom_path: PathBuf,
+
+    /// Nintendo DS ARM7 BIOS file, needed to build encrypted ROMs.
+    #[arg(long, short = '7')]
+    pub arm7_bios: Option<PathBuf>,
+
+    /// Path to dsd. Defaults to the currently running executable.
+    #[arg(long)]
+    pub dsd: Option<PathBuf>,
+}
+
+/// A file in delinks.txt, with the paths of its objects and the options to compile it with
+struct NinjaUnit {
+    source: PathBuf,
+    delinked_object: PathBuf,
+    built_object: PathBuf,
+    complete: bool,
+    gap: bool,
+    options: CompilerOptions,
+}
+
+#[derive(Serialize)]
+struct NinjaResult {
+    output_path: String,
+    num_units: usize,
+    /// Number of files with a compile statement
+    num_compiled: usize,
+}
+
+impl Ninja {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_dir = self.config_path.parent().unwrap();
+
+        let output_dir = self.output_path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let abs_output_dir = std::path::absolute(output_dir)?;
+        let path = |path: &Path| path.normalize_diff_paths(&abs_output_dir);
+
+        let modules = config.modules().collect::<Vec<_>>();
+
+        let mut units = vec![];
+        let mut main_options = CompilerOptions::default();
+        for &(module_kind, module) in &modules {
+            let delinks = Delinks::from_module_config(config_dir, module, module_kind)?;
+            if module_kind == ModuleKind::Arm9 {
+                main_options = delinks.options.clone();
+            }
+            for file in &delinks.files {
+                let (file_path, _) = file.split_file_ext();
+                units.push(NinjaUnit {
+                    source: path(Path::new(&file.name))?,
+                    delinked_object: path(&config_dir.join(&config.delinks_path).join(file_path).with_extension("o"))?,
+                    built_object: path(&config_dir.join(&config.build_path).join(file_path).with_extension("o"))?,
+                    complete: file.complete,
+                    gap: file.gap(),
+                    options: file.options.or(&delinks.options),
+                });
+            }
+        }
+
+        let linker = self
+            .linker
+            .clone()
+            .or(main_options.compiler)
+            .context("No linker given, pass --linker or set the compiler of the main module in its delinks.txt")?;
+
+        let config_path = path(&self.config_path)?;
+        let build_path = path(&config_dir.join(&config.build_path))?;
+        let rom_config_path = path(&config_dir.join(&config.rom_config))?;
+        let lcf_path = build_path.join("linker_script.lcf");
+        let objects_path = build_path.join("objects.txt");
+        let elf_path = build_path.join("arm9.o");
+        let module_objects =
+            modules.iter().map(|(_, module)| path(&config_dir.join(&module.object))).collect::<Result<Vec<_>, _>>()?;
+        let built_rom_config_path = module_objects[0].parent().unwrap().join("rom_config.yaml");
+        // Other files written by `dsd rom config` next to the module binaries
+        let rom_config_outputs = [
+            module_objects[0].parent().unwrap().join("arm9.yaml"),
+            module_objects[0].parent().unwrap().join("arm9_overlays.yaml"),
+        ]
+        .into_iter()
+        .chain(modules.iter().zip(&module_objects).filter_map(|((module_kind, _), object)| match module_kind {
+            ModuleKind::Autoload(AutoloadKind::Itcm) => Some(object.parent().unwrap().join("itcm.yaml")),
+            ModuleKind::Autoload(AutoloadKind::Dtcm) => Some(object.parent().unwrap().join("dtcm.yaml")),
+            _ => None,
+        }))
+        .collect::<Vec<_>>();
+        let config_files = iter::once(Ok(config_path.clone()))
+            .chain(
+                modules
+                    .iter()
+                    .flat_map(|(_, module)| Self::module_config_files(module))
+                    .map(|file| path(&config_dir.join(file))),
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let dsd = match &self.dsd {
+            Some(dsd) => dsd.clone(),
+            None => std::env::current_exe()?,
+        };
+        let wrapper = self.wrapper.as_ref().map(|wrapper| format!("{wrapper} ")).unwrap_or_default();
+
+        let mut writer = BufWriter::new(create_file(&self.output_path)?);
+        let w = &mut writer;
+        writeln!(w, "# Generated by `dsd ninja`, do not edit")?;
+        writeln!(w)?;
+        writeln!(w, "dsd = {}", Self::escape_value(&dsd.display().to_string()))?;
+        writeln!(w, "wrapper = {}", Self::escape_value(&wrapper))?;
+        writeln!(w, "compilers = {}", Self::escape_value(&path(&self.compilers_path)?.display().to_string()))?;
+        writeln!(w, "config = {}", Self::escape_path(&config_path))?;
+        writeln!(w)?;
+
+        writeln!(w, "rule delink")?;
+        writeln!(w, "  command = $dsd delink -c $config")?;
+        writeln!(w, "  description = DELINK")?;
+        writeln!(w)?;
+        writeln!(w, "rule compile")?;
+        writeln!(w, "  command = $wrapper$compilers/$compiler/mwccarm.exe $cflags -gccdep -MD -c $in -o $out")?;
+        writeln!(w, "  description = CC $in")?;
+        writeln!(w, "  depfile = $depfile")?;
+        writeln!(w, "  deps = gcc")?;
+        writeln!(w)?;
+        writeln!(w, "rule lcf")?;
+        writeln!(w, "  command = $dsd lcf -c $config -l $lcf -o $objects")?;
+        writeln!(w, "  description = LCF")?;
+        writeln!(w)?;
+        writeln!(w, "rule link")?;
+        writeln!(
+            w,
+            "  command = $wrapper$compilers/$linker/mwldarm.exe -proc arm946e -nostdlib -interworking -nodead -m Entry \
+             -map closure,unused @$objects $lcf -o $elf"
+        )?;
+        writeln!(w, "  description = LINK $elf")?;
+        writeln!(w)?;
+        writeln!(w, "rule rom_config")?;
+        writeln!(w, "  command = $dsd rom config -e $in -c $config")?;
+        writeln!(w, "  description = ROM CONFIG")?;
+        writeln!(w)?;
+        writeln!(w, "rule rom_build")?;
+        writeln!(w, "  command = $dsd rom build -c $in -o $out$arm7_bios")?;
+        writeln!(w, "  description = ROM $out")?;
+        writeln!(w)?;
+        writeln!(w, "rule check_modules")?;
+        writeln!(w, "  command = $dsd check modules -c $config -f")?;
+        writeln!(w, "  description = CHECK MODULES")?;
+        writeln!(w)?;
+        writeln!(w, "rule check_symbols")?;
+        writeln!(w, "  command = $dsd check symbols -c $config -e $in -f")?;
+        writeln!(w, "  description = CHECK SYMBOLS")?;
+        writeln!(w)?;
+
+        // Delink
+        write!(w, "build")?;
+        for unit in &units {
+            write!(w, " {}", Self::escape_path(&unit.delinked_object))?;
+        }
+        write!(w, ": delink {}", Self::escape_path(&rom_config_path))?;
+        Self::write_paths(w, &config_files)?;
+        writeln!(w)?;
+        writeln!(w)?;
+
+        // Compile. Incomplete files get compile statements too, so that objdiff can build them.
+        let mut link_objects = vec![];
+        let mut num_compiled = 0;
+        for unit in units.iter().filter(|unit| !unit.gap) {
+            let Some(compiler) = &unit.options.compiler else {
+                if unit.complete {
+                    bail!("No compiler for {}, set it for the file or its module in delinks.txt", unit.source.display());
+                }
+                continue;
+            };
+            writeln!(w, "build {}: compile {}", Self::escape_path(&unit.built_object), Self::escape_path(&unit.source))?;
+            writeln!(w, "  compiler = {}", Self::escape_value(compiler))?;
+            writeln!(w, "  cflags = {}", Self::escape_value(unit.options.flags.as_deref().unwrap_or("")))?;
+            // mwccarm replaces the object's extension with .d rather than appending to it
+            writeln!(w, "  depfile = {}", Self::escape_path(&unit.built_object.with_extension("d")))?;
+            num_compiled += 1;
+        }
+        writeln!(w)?;
+        for unit in &units {
+            link_objects.push(if unit.complete { &unit.built_object } else { &unit.delinked_object });
+        }
+
+        // Link
+        write!(w, "build {} {}: lcf", Self::escape_path(&lcf_path), Self::escape_path(&objects_path))?;
+        Self::write_paths(w, &config_files)?;
+        writeln!(w)?;
+        writeln!(w, "  lcf = {}", Self::escape_path(&lcf_path))?;
+        writeln!(w, "  objects = {}", Self::escape_path(&objects_path))?;
+        writeln!(w)?;
+        write!(w, "build {}", Self::escape_path(&elf_path))?;
+        Self::write_paths(w, &module_objects)?;
+        write!(w, ": link {} {} |", Self::escape_path(&lcf_path), Self::escape_path(&objects_path))?;
+        Self::write_paths(w, &link_objects)?;
+        writeln!(w)?;
+        writeln!(w, "  linker = {}", Self::escape_value(&linker))?;
+        writeln!(w, "  lcf = {}", Self::escape_path(&lcf_path))?;
+        writeln!(w, "  objects = {}", Self::escape_path(&objects_path))?;
+        writeln!(w, "  elf = {}", Self::escape_path(&elf_path))?;
+        writeln!(w)?;
+
+        // ROM
+        write!(w, "build {} |", Self::escape_path(&built_rom_config_path))?;
+        Self::write_paths(w, &rom_config_outputs)?;
+        writeln!(w, ": rom_config {} | {}", Self::escape_path(&elf_path), Self::escape_path(&config_path))?;
+        write!(
+            w,
+            "build {}: rom_build {} |",
+            Self::escape_path(&path(&self.rom_path)?),
+            Self::escape_path(&built_rom_config_path)
+        )?;
+        Self::write_paths(w, &module_objects)?;
+        writeln!(w)?;
+        if let Some(arm7_bios) = &self.arm7_bios {
+            writeln!(w, "  arm7_bios = $ -7 {}", Self::escape_path(&path(arm7_bios)?))?;
+        }
+        writeln!(w)?;
+
+        // Checks have no outputs, so they run on every build
+        write!(w, "build check_modules: check_modules |")?;
+        Self::write_paths(w, &module_objects)?;
+        writeln!(w)?;
+        writeln!(w, "build check_symbols: check_symbols {}", Self::escape_path(&elf_path))?;
+        writeln!(w, "build check: phony check_modules check_symbols")?;
+        writeln!(w)?;
+
+        writeln!(w, "default {} check", Self::escape_path(&path(&self.rom_path)?))?;
+
+        writer.flush()?;
+
+        output::result(
+            "ninja",
+            &NinjaResult { output_path: self.output_path.display().to_string(), num_units: units.len(), num_compiled },
+        );
+        Ok(())
+    }
+
+    fn module_config_files(module: &ConfigModule) -> [&Path; 3] {
+        [module.delinks.as_path(), module.symbols.as_path(), module.relocations.as_path()]
+    }
+
+    fn write_paths<P: AsRef<Path>>(w: &mut impl Write, paths: &[P]) -> Result<()> {
+        for path in paths {
+            write!(w, " {}", Self::escape_path(path.as_ref()))?;
+        }
+        Ok(())
+    }
+
+    /// Escapes a path for use in a build statement.
+    fn escape_path(path: &Path) -> String {
+        path.display().to_string().replace('$', "$$").replace(' ', "$ ").replace(':', "$:")
+    }
+
+    /// Escapes a variable value.
+    fn escape_value(value: &str) -> String {
+        value.replace('$', "$$")
+    }
+}
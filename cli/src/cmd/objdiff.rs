@@ -4,16 +4,14 @@ use anyhow::Result;
 use clap::Args;
 use ds_decomp::config::{
     config::{Config, ConfigModule},
-    delinks::Delinks,
+    delinks::{CompilerOptions, Delinks},
     module::ModuleKind,
 };
 use globset::Glob;
 use objdiff_core::config::ProjectObject;
+use serde::Serialize;
 
-use crate::{
-    config::delinks::DelinksExt,
-    util::{io::create_dir_all, path::PathExt},
-};
+use crate::util::{io::create_dir_all, output, path::PathExt};
 
 const MIN_OBJDIFF_VERSION: &str = "2.3.2";
 
@@ -32,15 +30,16 @@ pub struct Objdiff {
     #[arg(long, short = 's')]
     scratch: bool,
 
-    /// See https://decomp.me/api/compiler with compilers for the `nds_arm9` platform.
+    /// See https://decomp.me/api/compiler with compilers for the `nds_arm9` platform. Overridden by `compiler` in
+    /// delinks.txt.
     #[arg(long, short = 'C')]
     compiler: Option<String>,
 
-    /// Flags to pass to the compiler in decomp.me.
+    /// Flags to pass to the compiler in decomp.me. Overridden by `flags` in delinks.txt.
     #[arg(long, short = 'f', allow_hyphen_values = true)]
     c_flags: Option<String>,
 
-    /// Preset ID to use in decomp.me.
+    /// Preset ID to use in decomp.me. Overridden by `preset` in delinks.txt.
     #[arg(long, short = 'p')]
     preset_id: Option<u32>,
 
@@ -53,6 +52,12 @@ pub struct Objdiff {
     custom_args: Vec<String>,
 }
 
+#[derive(Serialize)]
+struct ObjdiffResult {
+    path: String,
+    num_units: usize,
+}
+
 impl Objdiff {
     pub fn run(&self) -> Result<()> {
         let config = Config::from_file(&self.config_path)?;
@@ -106,13 +111,17 @@ impl Objdiff {
             units: Some(units),
             progress_categories: None,
         };
+        let num_units = project_config.units.as_ref().map_or(0, |units| units.len());
 
         create_dir_all(&output_path)?;
+        let path = output_path.join("objdiff.json");
         objdiff_core::config::save_project_config(
             &project_config,
-            &objdiff_core::config::ProjectConfigInfo { path: output_path.join("objdiff.json"), timestamp: None },
+            &objdiff_core::config::ProjectConfigInfo { path: path.clone(), timestamp: None },
         )?;
 
+        output::result("objdiff", &ObjdiffResult { path: path.display().to_string(), num_units });
+
         Ok(())
     }
 
@@ -124,7 +133,12 @@ impl Objdiff {
         config: &Config,
         abs_output_path: &Path,
     ) -> Result<Vec<ProjectObject>> {
-        let delinks: Delinks = Delinks::from_file_and_generate_gaps(config_path.join(&module.delinks), module_kind)?;
+        let delinks: Delinks = Delinks::from_module_config(config_path, module, module_kind)?;
+        let default_options = delinks.options.or(&CompilerOptions {
+            compiler: self.compiler.clone(),
+            flags: self.c_flags.clone(),
+            preset: self.preset_id,
+        });
         delinks
             .files
             .iter()
@@ -158,13 +172,14 @@ impl Objdiff {
                         .with_extension(ctx_extension)
                         .normalize_diff_paths(abs_output_path)?;
 
+                    let options = file.options.or(&default_options);
                     Some(objdiff_core::config::ScratchConfig {
                         platform: Some("nds_arm9".to_string()),
-                        compiler: self.compiler.clone(),
-                        c_flags: self.c_flags.clone(),
+                        compiler: options.compiler,
+                        c_flags: options.flags,
                         ctx_path: Some(ctx_path),
                         build_ctx: Some(true),
-                        preset_id: self.preset_id,
+                        preset_id: options.preset,
                     })
                 } else {
                     None
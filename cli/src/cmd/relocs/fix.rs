@@ -0,0 +1,436 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use ds_decomp::{
+    config::{
+        config::Config,
+        delinks::Delinks,
+        module::{Module, ModuleKind},
+        relocations::{Relocation, RelocationModule},
+        section::SectionKind,
+        symbol::SymbolMap,
+        ParseContext,
+    },
+    project::project::Project,
+};
+use ds_rom::rom::{Rom, RomLoadOptions};
+use serde::Serialize;
+use unarm::{ArmVersion, DisplayOptions, Endian, LookupSymbol, ParseFlags, ParseMode, Parser, RegNames};
+
+use crate::{
+    analysis::symbols,
+    util::{bytes::FromSlice, io::read_to_string, output, parse::parse_u32},
+};
+
+/// Resolves relocations to `none` or to multiple overlays, either from a decisions file or interactively.
+#[derive(Args)]
+pub struct FixRelocs {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Decisions file, with one `source:MODULE from:ADDRESS module:MODULE` line per relocation. Prompts for every
+    /// relocation if omitted.
+    #[arg(long, short = 'd')]
+    pub decisions: Option<PathBuf>,
+
+    /// Only list the unresolved relocations, without changing them.
+    #[arg(long, short = 'l')]
+    pub list: bool,
+}
+
+/// A chosen destination module for the relocation from `from` in `source`
+struct Decision {
+    source: ModuleKind,
+    from: u32,
+    module: RelocationModule,
+}
+
+#[derive(Serialize)]
+struct FixRelocsResult {
+    num_unresolved: usize,
+    num_fixed: usize,
+    /// Names of the modules whose relocations.txt was written
+    updated_modules: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct FixRelocsListResult {
+    relocations: Vec<UnresolvedRelocation>,
+}
+
+/// A relocation to `none` or to multiple overlays, with where it is and which modules it may point to
+#[derive(Serialize)]
+struct UnresolvedRelocation {
+    module: String,
+    from: u32,
+    relocation: String,
+    location: String,
+    file: String,
+    candidates: Vec<Candidate>,
+}
+
+#[derive(Serialize)]
+struct Candidate {
+    #[serde(skip)]
+    module_kind: ModuleKind,
+    module: String,
+    description: String,
+    file: String,
+}
+
+impl FixRelocs {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+        let rom_autoloads = rom.arm9().autoloads()?;
+        let mut project = Project::from_config(config_path, &config, &rom, &rom_autoloads)?;
+        let delinks = Delinks::from_config_with_gaps(config_path, &config)?;
+
+        let module_names =
+            config.modules().map(|(module_kind, module)| (module.name.clone(), module_kind)).collect::<BTreeMap<_, _>>();
+
+        let unresolved = project
+            .modules()
+            .iter()
+            .flat_map(|module| {
+                module
+                    .relocations()
+                    .iter()
+                    .filter(|relocation| {
+                        matches!(relocation.module(), RelocationModule::None | RelocationModule::Overlays { .. })
+                    })
+                    .map(|relocation| (module.kind(), relocation.from_address()))
+            })
+            .collect::<Vec<_>>();
+        log::info!("Found {} unresolved or ambiguous relocations", unresolved.len());
+
+        let decisions = if self.list {
+            let context = RelocationContext { project: &project, delinks: &delinks };
+            if output::is_json() {
+                let relocations = unresolved
+                    .iter()
+                    .map(|&(module_kind, from)| context.unresolved_relocation(module_kind, from))
+                    .collect::<Vec<_>>();
+                output::result("relocs fix", &FixRelocsListResult { relocations });
+                return Ok(());
+            }
+            for &(module_kind, from) in &unresolved {
+                context.print(module_kind, from)?;
+            }
+            return Ok(());
+        } else if let Some(decisions_path) = &self.decisions {
+            Self::read_decisions(decisions_path, &module_names)?
+        } else {
+            if output::is_json() {
+                bail!("Prompting for decisions is not supported with JSON output, pass --decisions or --list");
+            }
+            let context = RelocationContext { project: &project, delinks: &delinks };
+            Self::prompt_decisions(&context, &unresolved)?
+        };
+
+        let mut num_fixed = 0;
+        let mut changed_modules = BTreeSet::new();
+        for Decision { source, from, module } in decisions {
+            let Some(module_index) = project.modules().iter().position(|module| module.kind() == source) else {
+                log::warn!("Skipping decision for relocation from {from:#010x} in {source}, module not found");
+                continue;
+            };
+            let Some(relocation) = project.modules()[module_index].relocations().get(from) else {
+                log::warn!("Skipping decision for relocation from {from:#010x} in {source}, relocation not found");
+                continue;
+            };
+            let candidates = RelocationContext::candidates(&project, source, relocation);
+            if let Some(reason) = Self::rejection_reason(relocation, &module, &candidates) {
+                log::warn!("Skipping decision for relocation from {from:#010x} in {source} to {module}, {reason}");
+                continue;
+            }
+            project.relocations_mut(module_index).get_mut(from).unwrap().set_module(module);
+            changed_modules.insert(source);
+            num_fixed += 1;
+        }
+
+        let mut updated_modules = vec![];
+        for (module_kind, config_module) in config.modules().filter(|(module_kind, _)| changed_modules.contains(module_kind)) {
+            let module = project.module_by_kind(module_kind).unwrap();
+            module.relocations().to_file(config_path.join(&config_module.relocations))?;
+            log::info!("Updated relocations of {}", config_module.name);
+            updated_modules.push(config_module.name.clone());
+        }
+
+        output::result("relocs fix", &FixRelocsResult { num_unresolved: unresolved.len(), num_fixed, updated_modules });
+
+        Ok(())
+    }
+
+    /// Returns why a decision can't be applied, if the relocation is already resolved or the chosen module is not one of
+    /// its candidates.
+    fn rejection_reason(relocation: &Relocation, module: &RelocationModule, candidates: &[ModuleKind]) -> Option<String> {
+        if !matches!(relocation.module(), RelocationModule::None | RelocationModule::Overlays { .. }) {
+            return Some(format!("it is already resolved to {}", relocation.module()));
+        }
+        let Some(first) = module.first_module() else {
+            return Some("a relocation can't be resolved to none".to_string());
+        };
+        let mut modules = std::iter::once(first).chain(module.other_modules().into_iter().flatten());
+        if let Some(module_kind) = modules.find(|module_kind| !candidates.contains(module_kind)) {
+            return Some(format!("{module_kind} is not one of its candidates"));
+        }
+        None
+    }
+
+    fn read_decisions(path: &Path, module_names: &BTreeMap<String, ModuleKind>) -> Result<Vec<Decision>> {
+        let text = read_to_string(path)?;
+        let mut context = ParseContext { file_path: path.display().to_string(), row: 0 };
+
+        let mut decisions = vec![];
+        for line in text.lines() {
+            context.row += 1;
+            let line = line.split_once("//").map(|(line, _)| line).unwrap_or(line);
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut source = None;
+            let mut from = None;
+            let mut module = None;
+            for word in line.split_whitespace() {
+                let Some((key, value)) = word.split_once(':') else {
+                    bail!("{context}: expected 'key:value' but got '{word}'");
+                };
+                match key {
+                    "source" => {
+                        source =
+                            Some(*module_names.get(value).with_context(|| format!("{context}: unknown module '{value}'"))?)
+                    }
+                    "from" => from = Some(parse_u32(value).with_context(|| format!("{context}: invalid address '{value}'"))?),
+                    "module" => module = Some(RelocationModule::parse(value, &context)?),
+                    _ => bail!("{context}: expected 'source', 'from' or 'module' but got '{key}'"),
+                }
+            }
+
+            let source = source.with_context(|| format!("{context}: missing 'source' attribute"))?;
+            let from = from.with_context(|| format!("{context}: missing 'from' attribute"))?;
+            let module = module.with_context(|| format!("{context}: missing 'module' attribute"))?;
+            decisions.push(Decision { source, from, module });
+        }
+        Ok(decisions)
+    }
+
+    fn prompt_decisions(context: &RelocationContext, unresolved: &[(ModuleKind, u32)]) -> Result<Vec<Decision>> {
+        let mut decisions = vec![];
+        let mut lines = io::stdin().lock().lines();
+        for &(module_kind, from) in unresolved {
+            let candidates = context.print(module_kind, from)?;
+            if candidates.is_empty() {
+                println!("No candidates, skipping");
+                continue;
+            }
+
+            let choice = loop {
+                print!("Choose 1-{}, s to skip or q to quit: ", candidates.len());
+                io::stdout().flush()?;
+                let Some(line) = lines.next() else { return Ok(decisions) };
+                match line?.trim() {
+                    "s" => break None,
+                    "q" => return Ok(decisions),
+                    choice => match choice.parse::<usize>() {
+                        Ok(index) if index >= 1 && index <= candidates.len() => break Some(candidates[index - 1]),
+                        _ => println!("Invalid choice '{choice}'"),
+                    },
+                }
+            };
+            if let Some(candidate) = choice {
+                decisions.push(Decision { source: module_kind, from, module: candidate.try_into()? });
+            }
+            println!();
+        }
+        Ok(decisions)
+    }
+}
+
+struct RelocationContext<'a> {
+    project: &'a Project<'a>,
+    delinks: &'a BTreeMap<ModuleKind, Delinks>,
+}
+
+impl RelocationContext<'_> {
+    fn unresolved_relocation(&self, module_kind: ModuleKind, from: u32) -> UnresolvedRelocation {
+        let module = self.project.module_by_kind(module_kind).unwrap();
+        let relocation = module.relocations().get(from).unwrap();
+
+        let candidates = Self::candidates(self.project, module_kind, relocation)
+            .into_iter()
+            .map(|candidate| Candidate {
+                module_kind: candidate,
+                module: candidate.to_string(),
+                description: self.describe(candidate, relocation.to_address()),
+                file: symbols::file_name(self.delinks, candidate, relocation.to_address()).to_string(),
+            })
+            .collect();
+        UnresolvedRelocation {
+            module: module_kind.to_string(),
+            from,
+            relocation: relocation.to_string(),
+            location: self.describe(module_kind, from),
+            file: symbols::file_name(self.delinks, module_kind, from).to_string(),
+            candidates,
+        }
+    }
+
+    /// Prints a relocation with the code or data around it and its candidate destinations. Returns the candidate modules.
+    fn print(&self, module_kind: ModuleKind, from: u32) -> Result<Vec<ModuleKind>> {
+        let module = self.project.module_by_kind(module_kind).unwrap();
+        let symbol_map = self.project.symbol_maps().get(module_kind).unwrap();
+        let unresolved = self.unresolved_relocation(module_kind, from);
+
+        println!("{}: {}", unresolved.module, unresolved.relocation);
+        println!("  in {} ({})", unresolved.location, unresolved.file);
+
+        match module.sections().get_by_contained_address(from) {
+            Some((_, section)) if section.kind() == SectionKind::Code => {
+                self.print_code(module, symbol_map, from)?;
+            }
+            Some(_) => {
+                let offset = (from - module.base_address()) as usize;
+                println!("    {from:#010x}  .word {:#010x}", u32::from_le_slice(&module.code()[offset..]));
+            }
+            None => {}
+        }
+
+        println!("  Candidates:");
+        for (i, candidate) in unresolved.candidates.iter().enumerate() {
+            println!("    {}) {}: {} ({})", i + 1, candidate.module, candidate.description, candidate.file);
+        }
+        Ok(unresolved.candidates.iter().map(|candidate| candidate.module_kind).collect())
+    }
+
+    fn candidates(project: &Project, module_kind: ModuleKind, relocation: &Relocation) -> Vec<ModuleKind> {
+        match relocation.module() {
+            RelocationModule::Overlays { ids } => ids.iter().map(|&id| ModuleKind::Overlay(id)).collect(),
+            _ => project
+                .modules()
+                .iter()
+                .filter(|module| module.kind() != module_kind)
+                .filter(|module| module.sections().get_by_contained_address(relocation.to_address()).is_some())
+                .map(|module| module.kind())
+                .collect(),
+        }
+    }
+
+    /// Prints the instructions around the relocation. If the relocation is in a pool constant, the instructions which load
+    /// it are printed instead.
+    fn print_code(&self, module: &Module, symbol_map: &SymbolMap, from: u32) -> Result<()> {
+        let Some(function) = module.sections().functions().find(|f| from >= f.start_address() && from < f.end_address())
+        else {
+            return Ok(());
+        };
+        let pool_constants = function.pool_constants();
+        let pc_load_offset = if function.is_thumb() { 4 } else { 8 };
+
+        let mut parser = Parser::new(
+            if function.is_thumb() { ParseMode::Thumb } else { ParseMode::Arm },
+            function.start_address(),
+            Endian::Little,
+            ParseFlags { ual: false, version: ArmVersion::V5Te },
+            function.code(module.code(), module.base_address()),
+        );
+        let lookup = LocalSymbols { symbol_map };
+        let mut lines = vec![];
+        let mut focus = vec![];
+        while let Some((address, _, parsed_ins)) = parser.next() {
+            if pool_constants.range(address.saturating_sub(3)..=address).next().is_some() {
+                continue;
+            }
+            let reference = parsed_ins.pc_relative_reference(address, pc_load_offset);
+            if address == from || reference == Some(from) {
+                focus.push(lines.len());
+            }
+            let text = parsed_ins
+                .display_with_symbols(
+                    DisplayOptions { reg_names: RegNames { ip: true, ..Default::default() } },
+                    unarm::Symbols { lookup: &lookup, program_counter: address, pc_load_offset },
+                )
+                .to_string();
+            lines.push((address, text));
+        }
+
+        let mut printed_until = 0;
+        for &index in &focus {
+            let start = index.saturating_sub(2).max(printed_until);
+            let end = (index + 3).min(lines.len());
+            if start > printed_until && printed_until > 0 {
+                println!("    ...");
+            }
+            for (i, (address, text)) in lines.iter().enumerate().take(end).skip(start) {
+                let marker = if i == index { ">" } else { " " };
+                println!("  {marker} {address:#010x}  {text}");
+            }
+            printed_until = end;
+        }
+        if pool_constants.contains(&from) {
+            let offset = (from - module.base_address()) as usize;
+            println!("    {from:#010x}  .word {:#010x}", u32::from_le_slice(&module.code()[offset..]));
+        }
+        Ok(())
+    }
+
+    /// Describes an address as the symbol at or containing it plus offset.
+    fn describe(&self, module_kind: ModuleKind, address: u32) -> String {
+        let Some(symbol_map) = self.project.symbol_maps().get(module_kind) else {
+            return "?".to_string();
+        };
+        if let Ok(Some((_, symbol))) = symbol_map.by_address(address) {
+            return symbol.name.clone();
+        }
+        match symbols::containing_symbol(self.project, module_kind, address) {
+            Some(symbol) => format!("{}+{:#x}", symbol.name, address - symbol.addr),
+            None => "no symbol".to_string(),
+        }
+    }
+}
+
+/// Looks up symbols in the local module only, as external relocations may not be resolved yet
+struct LocalSymbols<'a> {
+    symbol_map: &'a SymbolMap,
+}
+
+impl LookupSymbol for LocalSymbols<'_> {
+    fn lookup_symbol_name(&self, _source: u32, destination: u32) -> Option<&str> {
+        match self.symbol_map.by_address(destination) {
+            Ok(Some((_, symbol))) => Some(&symbol.name),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejected_decisions() {
+        let candidates = [ModuleKind::Overlay(1), ModuleKind::Overlay(2)];
+        let ambiguous = Relocation::new_load(0x02000000, 0x02100000, 0, RelocationModule::Overlays { ids: vec![1, 2] });
+        let resolved = Relocation::new_load(0x02000000, 0x02100000, 0, RelocationModule::Overlay { id: 1 });
+
+        let reject = |relocation: &Relocation, module: RelocationModule| {
+            FixRelocs::rejection_reason(relocation, &module, &candidates).is_some()
+        };
+        assert!(!reject(&ambiguous, RelocationModule::Overlay { id: 2 }));
+        assert!(reject(&ambiguous, RelocationModule::Overlay { id: 3 }));
+        assert!(reject(&ambiguous, RelocationModule::Main));
+        assert!(reject(&ambiguous, RelocationModule::None));
+        assert!(reject(&resolved, RelocationModule::Overlay { id: 2 }));
+    }
+}
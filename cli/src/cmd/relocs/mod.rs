@@ -0,0 +1,30 @@
+mod fix;
+mod narrow;
+
+pub use fix::*;
+pub use narrow::*;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+/// Subcommands for analyzing and fixing relocations.
+#[derive(Args)]
+pub struct RelocsArgs {
+    #[command(subcommand)]
+    command: RelocsCommand,
+}
+
+impl RelocsArgs {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            RelocsCommand::Narrow(narrow) => narrow.run(),
+            RelocsCommand::Fix(fix) => fix.run(),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum RelocsCommand {
+    Narrow(NarrowRelocs),
+    Fix(FixRelocs),
+}
@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use ds_decomp::{
+    config::{config::Config, module::ModuleKind, relocations::RelocationModule},
+    project::project::Project,
+};
+use ds_rom::rom::{Rom, RomLoadOptions};
+use serde::Serialize;
+
+use crate::{
+    analysis::overlay_loads::{self, OverlayLoader, OverlayLoads},
+    util::output,
+};
+
+/// Narrows relocations to multiple overlays by finding which overlays are loaded at the same time.
+#[derive(Args)]
+pub struct NarrowRelocs {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Function which loads an overlay and the register containing its ID, formatted as `name:register`. Can be repeated.
+    /// Defaults to `FS_LoadOverlay:r1` and `FS_LoadOverlayInfo:r2`.
+    #[arg(long, short = 'l')]
+    pub loader: Vec<String>,
+
+    /// Dry run, only report the results but don't write any files.
+    #[arg(long, short = 'd')]
+    pub dry: bool,
+
+    /// Narrows relocations in overlays even if some overlays loaded at the same time are loaded by a non-constant ID.
+    #[arg(long, short = 'f')]
+    pub force: bool,
+}
+
+#[derive(Serialize)]
+struct NarrowRelocsResult {
+    num_loads: usize,
+    num_narrowed: usize,
+    num_ambiguous: usize,
+    /// Whether relocations.txt files were written, which they aren't in dry runs or if nothing was narrowed
+    written: bool,
+}
+
+impl NarrowRelocs {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let loaders = if self.loader.is_empty() {
+            OverlayLoader::defaults()
+        } else {
+            self.loader.iter().map(|loader| OverlayLoader::parse(loader)).collect::<Result<Vec<_>>>()?
+        };
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+        let rom_autoloads = rom.arm9().autoloads()?;
+        let mut project = Project::from_config(config_path, &config, &rom, &rom_autoloads)?;
+
+        let overlay_loads = OverlayLoads::analyze(&project, &loaders)?;
+        log::info!("Found {} overlay loads with constant IDs", overlay_loads.num_loads());
+        for &(module_kind, address) in &overlay_loads.unknown_calls {
+            log::warn!("Overlay load at {address:#010x} in {module_kind} has no constant overlay ID");
+        }
+
+        if !self.force {
+            for overlay in project.overlays() {
+                let ModuleKind::Overlay(id) = overlay.kind() else { continue };
+                let has_ambiguous = overlay
+                    .relocations()
+                    .iter()
+                    .any(|relocation| matches!(relocation.module(), RelocationModule::Overlays { .. }));
+                if has_ambiguous && !overlay_loads.is_coresident_known(id) {
+                    log::warn!(
+                        "Not narrowing relocations in overlay {id}, as it or a function loading it loads overlays by a \
+                        non-constant ID. Use --force to narrow them anyway"
+                    );
+                }
+            }
+        }
+
+        let num_narrowed = overlay_loads::narrow_relocations(&mut project, &overlay_loads, self.force);
+        log::info!("Narrowed {num_narrowed} relocations");
+
+        let mut num_ambiguous = 0;
+        for module in project.modules() {
+            for relocation in module.relocations().iter() {
+                if let RelocationModule::Overlays { .. } = relocation.module() {
+                    log::warn!(
+                        "Relocation from {:#010x} in {} to {:#010x} is still ambiguous: {}",
+                        relocation.from_address(),
+                        module.kind(),
+                        relocation.to_address(),
+                        relocation.module()
+                    );
+                    num_ambiguous += 1;
+                }
+            }
+        }
+        log::info!("{num_ambiguous} relocations are still ambiguous");
+
+        let written = !self.dry && num_narrowed > 0;
+        if written {
+            for (module_kind, config_module) in config.modules() {
+                let Some(module) = project.module_by_kind(module_kind) else { continue };
+                module.relocations().to_file(config_path.join(&config_module.relocations))?;
+            }
+        }
+
+        output::result(
+            "relocs narrow",
+            &NarrowRelocsResult { num_loads: overlay_loads.num_loads(), num_narrowed, num_ambiguous, written },
+        );
+
+        Ok(())
+    }
+}
@@ -12,15 +12,15 @@ use ds_rom::{
 pub struct Build {
     /// Path to config YAML
     #[arg(long, short = 'c')]
-    config: PathBuf,
+    pub config: PathBuf,
 
     /// Nintendo DS ARM7 BIOS file
     #[arg(long, short = '7')]
-    arm7_bios: Option<PathBuf>,
+    pub arm7_bios: Option<PathBuf>,
 
     /// Output ROM
     #[arg(long, short = 'o')]
-    rom: PathBuf,
+    pub rom: PathBuf,
 }
 
 impl Build {
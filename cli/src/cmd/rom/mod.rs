@@ -2,7 +2,7 @@ mod build;
 mod config;
 mod extract;
 
-use build::*;
+pub use build::*;
 pub use config::*;
 use extract::*;
 
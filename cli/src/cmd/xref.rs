@@ -0,0 +1,305 @@
+use std::{collections::BTreeMap, ops::Range, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use ds_decomp::{
+    config::{
+        config::Config,
+        delinks::Delinks,
+        module::{Module, ModuleKind},
+        symbol::{Symbol, SymbolKind, SymbolMap},
+    },
+    project::project::Project,
+};
+use ds_rom::rom::{raw::AutoloadKind, Rom, RomLoadOptions};
+use serde::Serialize;
+
+use crate::{
+    analysis::{
+        symbols,
+        xref::{CrossReference, CrossReferenceKind, CrossReferences},
+    },
+    util::{
+        output,
+        parse::{parse_u16, parse_u32},
+    },
+};
+
+/// Lists cross-references to and from a symbol or address.
+#[derive(Args)]
+pub struct Xref {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Symbol name or address to look up.
+    #[arg(required_unless_present = "unreferenced")]
+    pub query: Option<String>,
+
+    /// Only search this module: main, itcm, dtcm or an overlay ID.
+    #[arg(long, short = 'm')]
+    pub module: Option<String>,
+
+    /// List functions, data and bss symbols which nothing references.
+    #[arg(long, short = 'u')]
+    pub unreferenced: bool,
+}
+
+/// A symbol or address range which was looked up
+struct Target<'a> {
+    module_kind: ModuleKind,
+    name: String,
+    range: Range<u32>,
+    symbol: Option<&'a Symbol>,
+}
+
+/// Cross-references of a looked up symbol or address
+#[derive(Serialize)]
+struct TargetResult {
+    name: String,
+    module: String,
+    start: u32,
+    end: u32,
+    file: String,
+    callers: Vec<XrefLocation>,
+    loaders: Vec<XrefLocation>,
+    pointer_holders: Vec<XrefLocation>,
+    callees: Vec<XrefLocation>,
+    loads_pointers: Vec<XrefLocation>,
+}
+
+/// The other end of a cross-reference, described as its containing symbol plus offset, module and file name
+#[derive(Serialize)]
+struct XrefLocation {
+    address: u32,
+    description: String,
+}
+
+#[derive(Serialize)]
+struct UnreferencedSymbol {
+    address: u32,
+    kind: &'static str,
+    name: String,
+    module: String,
+    file: String,
+}
+
+impl TargetResult {
+    fn print(&self) {
+        println!("{} ({}, {:#010x}..{:#010x}, {})", self.name, self.module, self.start, self.end, self.file);
+        let groups = [
+            ("Callers", &self.callers),
+            ("Loaders", &self.loaders),
+            ("Pointer holders", &self.pointer_holders),
+            ("Callees", &self.callees),
+            ("Loads/pointers", &self.loads_pointers),
+        ];
+        for (title, locations) in groups.into_iter().filter(|(_, locations)| !locations.is_empty()) {
+            println!("  {title}:");
+            for location in locations {
+                println!("    {:#010x} {}", location.address, location.description);
+            }
+        }
+        println!();
+    }
+}
+
+impl UnreferencedSymbol {
+    fn print(&self) {
+        println!("{:#010x} {:8} {} ({}, {})", self.address, self.kind, self.name, self.module, self.file);
+    }
+}
+
+impl Xref {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+        let rom_autoloads = rom.arm9().autoloads()?;
+        let project = Project::from_config(config_path, &config, &rom, &rom_autoloads)?;
+        let xrefs = CrossReferences::analyze(&project);
+
+        let delinks = Delinks::from_config_with_gaps(config_path, &config)?;
+
+        let module_filter = self.module.as_deref().map(parse_module_kind).transpose()?;
+        let modules = project.modules().iter().filter(|module| module_filter.map_or(true, |kind| module.kind() == kind));
+
+        let printer = Printer { project: &project, delinks: &delinks };
+        if self.unreferenced {
+            let symbols = modules.flat_map(|module| printer.unreferenced(module, &xrefs)).collect::<Vec<_>>();
+            if output::is_json() {
+                output::result("xref unreferenced", &symbols);
+            } else {
+                symbols.iter().for_each(UnreferencedSymbol::print);
+            }
+            return Ok(());
+        }
+
+        let query = self.query.as_deref().unwrap();
+        let targets = modules.filter_map(|module| printer.find_target(module, query)).collect::<Vec<_>>();
+        if targets.is_empty() {
+            bail!("No symbol or address '{query}' found");
+        }
+        let results = targets.iter().map(|target| printer.target_result(target, &xrefs)).collect::<Vec<_>>();
+        if output::is_json() {
+            output::result("xref", &results);
+        } else {
+            results.iter().for_each(TargetResult::print);
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_module_kind(text: &str) -> Result<ModuleKind> {
+    match text {
+        "main" => Ok(ModuleKind::Arm9),
+        "itcm" => Ok(ModuleKind::Autoload(AutoloadKind::Itcm)),
+        "dtcm" => Ok(ModuleKind::Autoload(AutoloadKind::Dtcm)),
+        _ => {
+            let id = text.strip_prefix("ov").unwrap_or(text);
+            let id = parse_u16(id)
+                .with_context(|| format!("Invalid module '{text}', must be main, itcm, dtcm or an overlay ID"))?;
+            Ok(ModuleKind::Overlay(id))
+        }
+    }
+}
+
+struct Printer<'a> {
+    project: &'a Project<'a>,
+    delinks: &'a BTreeMap<ModuleKind, Delinks>,
+}
+
+impl<'a> Printer<'a> {
+    fn symbol_map(&self, module_kind: ModuleKind) -> Option<&'a SymbolMap> {
+        self.project.symbol_maps().get(module_kind)
+    }
+
+    fn find_target(&self, module: &Module, query: &str) -> Option<Target<'a>> {
+        let module_kind = module.kind();
+        let symbol_map = self.symbol_map(module_kind)?;
+
+        let symbol = match parse_u32(query) {
+            Ok(address) => {
+                if address < module.base_address() || address >= module.end_address() + module.bss_size() {
+                    return None;
+                }
+                match symbols::containing_symbol(self.project, module_kind, address) {
+                    Some(symbol) => symbol,
+                    None => {
+                        return Some(Target {
+                            module_kind,
+                            name: format!("{address:#010x}"),
+                            range: address..address + 1,
+                            symbol: None,
+                        })
+                    }
+                }
+            }
+            Err(_) => symbol_map.for_name(query)?.next().map(|(_, symbol)| symbol)?,
+        };
+        Some(Target {
+            module_kind,
+            name: symbol.name.clone(),
+            range: symbol.addr..symbol.addr + symbols::symbol_size(self.project, module_kind, symbol).max(1),
+            symbol: Some(symbol),
+        })
+    }
+
+    fn target_result(&self, target: &Target, xrefs: &CrossReferences) -> TargetResult {
+        let incoming = xrefs.to_range(target.module_kind, target.range.clone()).collect::<Vec<_>>();
+        let outgoing = match target.symbol {
+            Some(_) => xrefs.from_range(target.module_kind, target.range.clone()).collect::<Vec<_>>(),
+            None => vec![],
+        };
+        let from = |kind: CrossReferenceKind| {
+            incoming.iter().filter(|x| x.kind == kind).map(|x| self.location_from(x)).collect::<Vec<_>>()
+        };
+
+        TargetResult {
+            name: target.name.clone(),
+            module: target.module_kind.to_string(),
+            start: target.range.start,
+            end: target.range.end,
+            file: symbols::file_name(self.delinks, target.module_kind, target.range.start).to_string(),
+            callers: from(CrossReferenceKind::Call),
+            loaders: from(CrossReferenceKind::Load),
+            pointer_holders: from(CrossReferenceKind::Pointer),
+            callees: outgoing.iter().filter(|x| x.kind == CrossReferenceKind::Call).map(|x| self.location_to(x)).collect(),
+            loads_pointers: outgoing
+                .iter()
+                .filter(|x| x.kind != CrossReferenceKind::Call)
+                .map(|x| self.location_to(x))
+                .collect(),
+        }
+    }
+
+    fn location_from(&self, xref: &CrossReference) -> XrefLocation {
+        XrefLocation { address: xref.from, description: self.describe_address(xref.from_module, xref.from) }
+    }
+
+    fn location_to(&self, xref: &CrossReference) -> XrefLocation {
+        if xref.to_modules.is_empty() {
+            return XrefLocation { address: xref.to, description: "(unknown module)".to_string() };
+        }
+        let targets =
+            xref.to_modules.iter().map(|&module_kind| self.describe_address(module_kind, xref.to)).collect::<Vec<_>>();
+        XrefLocation { address: xref.to, description: targets.join(" or ") }
+    }
+
+    /// Describes an address as its containing symbol plus offset, module and delink file name.
+    fn describe_address(&self, module_kind: ModuleKind, address: u32) -> String {
+        let symbol = symbols::containing_symbol(self.project, module_kind, address);
+        let location = match symbol {
+            Some(symbol) if symbol.addr == address => symbol.name.clone(),
+            Some(symbol) => format!("{}+{:#x}", symbol.name, address - symbol.addr),
+            None => "?".to_string(),
+        };
+        format!("{location} ({module_kind}, {})", symbols::file_name(self.delinks, module_kind, address))
+    }
+
+    fn unreferenced(&self, module: &Module, xrefs: &CrossReferences) -> Vec<UnreferencedSymbol> {
+        let module_kind = module.kind();
+        let Some(symbol_map) = self.symbol_map(module_kind) else { return vec![] };
+
+        let mut symbols = vec![];
+        for section in module.sections().iter() {
+            let section_symbols = symbol_map
+                .iter_by_address(section.address_range())
+                .filter(|symbol| symbols::is_sized(symbol))
+                .collect::<Vec<_>>();
+            for (index, symbol) in section_symbols.iter().enumerate() {
+                // Same as `symbols::symbol_size`, but without looking up the next symbol again
+                let next_address = section_symbols[index + 1..]
+                    .iter()
+                    .map(|next| next.addr)
+                    .find(|&address| address > symbol.addr)
+                    .unwrap_or(section.end_address());
+                let range = symbol.addr..symbol.addr + symbol.size(next_address).max(1);
+                if !xrefs.is_referenced(module_kind, range) {
+                    symbols.push(*symbol);
+                }
+            }
+        }
+        symbols.sort_by_key(|symbol| symbol.addr);
+
+        symbols
+            .into_iter()
+            .map(|symbol| UnreferencedSymbol {
+                address: symbol.addr,
+                kind: match symbol.kind {
+                    SymbolKind::Function(_) => "function",
+                    SymbolKind::Data(_) => "data",
+                    _ => "bss",
+                },
+                name: symbol.name.clone(),
+                module: module_kind.to_string(),
+                file: symbols::file_name(self.delinks, module_kind, symbol.addr).to_string(),
+            })
+            .collect()
+    }
+}
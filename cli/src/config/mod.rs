@@ -1,5 +1,3 @@
-pub mod delinks;
-pub mod program;
-pub mod relocation;
 pub mod section;
 pub mod symbol;
+pub mod update;
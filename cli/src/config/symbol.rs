@@ -4,15 +4,13 @@ use anyhow::{bail, Result};
 use ds_decomp::config::{
     module::ModuleKind,
     relocations::Relocations,
-    symbol::{InstructionMode, SymData, SymFunction, SymLabel, Symbol, SymbolKind, SymbolMap, SymbolMaps},
+    symbol::{SymData, Symbol, SymbolKind, SymbolMap, SymbolMaps},
 };
 use object::{Object, ObjectSymbol};
 use unarm::LookupSymbol;
 
 use crate::util::bytes::FromSlice;
 
-use super::relocation::RelocationModuleExt;
-
 pub struct LookupSymbolMap(SymbolMap);
 
 impl LookupSymbol for LookupSymbolMap {
@@ -55,60 +53,6 @@ impl SymbolMapExt for SymbolMap {
 
 pub enum SymbolMapContainsError {}
 
-pub trait SymbolExt {
-    fn mapping_symbol_name(&self) -> Option<&str>;
-
-    fn get_obj_symbol_scope(&self) -> object::SymbolScope;
-}
-
-impl SymbolExt for Symbol {
-    fn mapping_symbol_name(&self) -> Option<&str> {
-        match self.kind {
-            SymbolKind::Undefined => None,
-            SymbolKind::Function(SymFunction { mode, .. }) | SymbolKind::Label(SymLabel { mode, .. }) => match mode {
-                InstructionMode::Arm => Some("$a"),
-                InstructionMode::Thumb => Some("$t"),
-            },
-            SymbolKind::PoolConstant => Some("$d"),
-            SymbolKind::JumpTable(jump_table) => {
-                if jump_table.code {
-                    Some("$a")
-                } else {
-                    Some("$d")
-                }
-            }
-            SymbolKind::Data(_) => Some("$d"),
-            SymbolKind::Bss(_) => None,
-        }
-    }
-
-    fn get_obj_symbol_scope(&self) -> object::SymbolScope {
-        if self.local {
-            object::SymbolScope::Compilation
-        } else {
-            object::SymbolScope::Dynamic
-        }
-    }
-}
-
-pub trait SymbolKindExt {
-    fn as_obj_symbol_kind(&self) -> object::SymbolKind;
-}
-
-impl SymbolKindExt for SymbolKind {
-    fn as_obj_symbol_kind(&self) -> object::SymbolKind {
-        match self {
-            Self::Undefined => object::SymbolKind::Unknown,
-            Self::Function(_) => object::SymbolKind::Text,
-            Self::Label { .. } => object::SymbolKind::Label,
-            Self::PoolConstant => object::SymbolKind::Data,
-            Self::JumpTable(_) => object::SymbolKind::Label,
-            Self::Data(_) => object::SymbolKind::Data,
-            Self::Bss(_) => object::SymbolKind::Data,
-        }
-    }
-}
-
 pub trait SymDataExt {
     fn write_assembly<W: io::Write>(&self, w: &mut W, symbol: &Symbol, bytes: &[u8], symbols: &SymbolLookup) -> Result<()>;
 }
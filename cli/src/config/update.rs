@@ -0,0 +1,413 @@
+use std::{
+    fmt::Display,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::Result;
+use ds_decomp::config::{
+    relocations::{Relocation, Relocations},
+    section::Sections,
+    symbol::{SymBss, SymData, Symbol, SymbolIndex, SymbolKind, SymbolMap},
+};
+
+use crate::util::io::create_file;
+
+/// Conflicts found while merging a new analysis into existing config files. The existing config is kept in every conflict.
+#[derive(Default)]
+pub struct UpdateReport {
+    conflicts: Vec<(String, String)>,
+}
+
+impl UpdateReport {
+    pub fn new() -> Self {
+        Self { conflicts: vec![] }
+    }
+
+    pub fn add(&mut self, module_name: &str, message: impl Display) {
+        self.conflicts.push((module_name.to_string(), message.to_string()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.conflicts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Iterates over the module name and message of each conflict.
+    pub fn conflicts(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.conflicts.iter().map(|(module_name, message)| (module_name.as_str(), message.as_str()))
+    }
+
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(create_file(path)?);
+        for (module_name, message) in &self.conflicts {
+            writeln!(writer, "{module_name}: {message}")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Adds newly discovered symbols to an existing symbol map. Existing symbols keep the names and types given by the user,
+/// but generated names are replaced by the ones found by the analysis, and functions take their size and flags from it.
+pub fn merge_symbols(symbol_map: &mut SymbolMap, new_symbols: &SymbolMap, module_name: &str, report: &mut UpdateReport) {
+    for symbol in new_symbols.iter_by_address(0..u32::MAX).filter(|symbol| symbol.should_write()) {
+        if let Some(existing) = symbol_map.for_address(symbol.addr) {
+            let existing = existing.collect::<Vec<_>>();
+            let Some(&(index, existing)) = matching_symbol(&existing, symbol) else {
+                let existing = existing.iter().map(|(_, existing)| existing.to_string()).collect::<Vec<_>>().join(", ");
+                report.add(module_name, format!("kept {existing}, analysis found {symbol}"));
+                continue;
+            };
+
+            let merge = merge_symbol(existing, symbol);
+            for conflict in merge.conflicts {
+                report.add(module_name, conflict);
+            }
+            if let Some(kind) = merge.kind {
+                symbol_map.set_kind(index, kind);
+            }
+            if let Some(name) = merge.name {
+                if let Some(mut others) = symbol_map.for_name(&name) {
+                    let (_, other) = others.next().unwrap();
+                    report.add(
+                        module_name,
+                        format!(
+                            "analysis named symbol at {:#010x} {name}, which is already used at {:#010x}, not renamed",
+                            symbol.addr, other.addr
+                        ),
+                    );
+                } else {
+                    symbol_map.rename(index, &name);
+                }
+            }
+            continue;
+        }
+
+        if let SymbolKind::Function(_) = symbol.kind {
+            if let Some((_, function)) = symbol_map.get_function_containing(symbol.addr) {
+                report.add(
+                    module_name,
+                    format!("new function {} is inside existing function {}, not added", symbol.name, function.name),
+                );
+                continue;
+            }
+        }
+        if let Some(mut existing) = symbol_map.for_name(&symbol.name) {
+            let (_, existing) = existing.next().unwrap();
+            report.add(
+                module_name,
+                format!(
+                    "new symbol {} at {:#010x} has the same name as existing one at {:#010x}, not added",
+                    symbol.name, symbol.addr, existing.addr
+                ),
+            );
+            continue;
+        }
+        symbol_map.add(symbol.clone());
+    }
+
+    for (_, function) in symbol_map.functions() {
+        if new_symbols.get_function(function.addr).ok().flatten().is_none() {
+            report.add(module_name, format!("function {} was not found by the analysis, kept", function.name));
+        }
+    }
+}
+
+/// Returns the existing symbol to merge with a symbol found by the analysis, which is the one of the same kind or else the
+/// only symbol at that address.
+fn matching_symbol<'a>(existing: &'a [(SymbolIndex, &'a Symbol)], symbol: &Symbol) -> Option<&'a (SymbolIndex, &'a Symbol)> {
+    let same_kind =
+        existing.iter().find(|(_, existing)| std::mem::discriminant(&existing.kind) == std::mem::discriminant(&symbol.kind));
+    same_kind.or(if existing.len() == 1 { existing.first() } else { None })
+}
+
+/// Changes to an existing symbol when merging it with the analysis.
+#[derive(Default)]
+struct SymbolMerge {
+    /// Kind found by the analysis, which the existing symbol takes
+    kind: Option<SymbolKind>,
+    /// Name found by the analysis, which replaces the generated name of the existing symbol
+    name: Option<String>,
+    /// Edits of the user which disagree with the analysis. The existing symbol is kept as the user edited it.
+    conflicts: Vec<String>,
+}
+
+fn merge_symbol(existing: &Symbol, symbol: &Symbol) -> SymbolMerge {
+    let mut merge = SymbolMerge::default();
+
+    if existing.kind != symbol.kind && !is_refinement(existing, symbol) {
+        if is_replaceable(existing, symbol) {
+            merge.kind = Some(symbol.kind);
+        } else {
+            merge.conflicts.push(format!("kept {existing}, analysis found {symbol}"));
+        }
+    }
+
+    if existing.name != symbol.name && !is_generated_name(symbol) {
+        if is_generated_name(existing) {
+            merge.name = Some(symbol.name.clone());
+        } else {
+            merge
+                .conflicts
+                .push(format!("kept name {} at {:#010x}, analysis named it {}", existing.name, existing.addr, symbol.name));
+        }
+    }
+
+    merge
+}
+
+/// Returns whether an existing symbol is a more specific version of the one found by the analysis, such as a data symbol
+/// typed by the user where the analysis only found `data:any`.
+fn is_refinement(existing: &Symbol, symbol: &Symbol) -> bool {
+    matches!(
+        (existing.kind, symbol.kind),
+        (SymbolKind::Data(_), SymbolKind::Data(SymData::Any)) | (SymbolKind::Bss(_), SymbolKind::Bss(SymBss { size: None }))
+    )
+}
+
+/// Returns whether an existing symbol may take the kind found by the analysis. Functions take their size and flags from the
+/// analysis as long as the instruction mode is the same, even if the user named them. Other symbols only take the kind if
+/// they have a generated name and no type given by the user.
+fn is_replaceable(existing: &Symbol, symbol: &Symbol) -> bool {
+    match (existing.kind, symbol.kind) {
+        (SymbolKind::Function(existing), SymbolKind::Function(function)) => existing.mode == function.mode,
+        _ if !is_generated_name(existing) => false,
+        (SymbolKind::Data(SymData::Any), _) => true,
+        (SymbolKind::Bss(SymBss { size: None }), _) => true,
+        _ => false,
+    }
+}
+
+/// Returns whether a symbol has a name generated by `dsd`, such as `func_02001234`, rather than one given by a user.
+fn is_generated_name(symbol: &Symbol) -> bool {
+    symbol.name.ends_with(&format!("{:08x}", symbol.addr))
+}
+
+/// Adds newly discovered relocations and not_pointer marks to existing ones. Existing relocations are only removed where
+/// an `ignore_reloc` hint removed the same relocation from the analysis. Relocations which the user wrote at an ignored or
+/// newly marked address are kept and reported.
+pub fn merge_relocations(
+    relocations: &mut Relocations,
+    new_relocations: &Relocations,
+    ignored_relocations: &[Relocation],
+    module_name: &str,
+    report: &mut UpdateReport,
+) -> Result<()> {
+    for ignored in ignored_relocations {
+        let from = ignored.from_address();
+        match relocations.get(from) {
+            Some(existing) if same_relocation(existing, ignored) => {
+                relocations.remove(from);
+            }
+            Some(existing) => report.add(module_name, format!("kept relocation {existing}, which hints.txt ignores")),
+            None => {}
+        }
+    }
+
+    for from in new_relocations.not_pointers() {
+        if relocations.is_not_pointer(from) {
+            continue;
+        }
+        match relocations.get(from) {
+            Some(existing) => {
+                report.add(module_name, format!("kept relocation {existing}, analysis marked it as not_pointer"))
+            }
+            None => relocations.add_not_pointer(from),
+        }
+    }
+
+    for relocation in new_relocations.iter() {
+        let from = relocation.from_address();
+        if relocations.is_not_pointer(from) {
+            continue;
+        }
+        match relocations.get(from) {
+            Some(existing) if same_relocation(existing, relocation) => {}
+            Some(existing) => report.add(module_name, format!("kept relocation {existing}, analysis found {relocation}")),
+            None => {
+                relocations.add(relocation.clone())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn same_relocation(a: &Relocation, b: &Relocation) -> bool {
+    a.to_address() == b.to_address() && a.kind() == b.kind() && a.module() == b.module() && a.addend() == b.addend()
+}
+
+/// Reports differences between the sections in an existing delinks.txt and the ones found by the analysis.
+pub fn compare_sections(sections: &Sections, new_sections: &Sections, module_name: &str, report: &mut UpdateReport) {
+    for section in new_sections.iter() {
+        match sections.by_name(section.name()) {
+            Some((_, existing)) if existing.address_range() == section.address_range() => {}
+            Some((_, existing)) => report.add(
+                module_name,
+                format!(
+                    "kept section {} at {:#010x}..{:#010x}, analysis found {:#010x}..{:#010x}",
+                    existing.name(),
+                    existing.start_address(),
+                    existing.end_address(),
+                    section.start_address(),
+                    section.end_address()
+                ),
+            ),
+            None => report.add(
+                module_name,
+                format!(
+                    "analysis found section {} at {:#010x}..{:#010x} which is not in delinks.txt",
+                    section.name(),
+                    section.start_address(),
+                    section.end_address()
+                ),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ds_decomp::config::{
+        relocations::RelocationModule,
+        symbol::{InstructionMode, SymFunction},
+    };
+
+    use super::*;
+
+    fn function(name: &str, addr: u32, size: u32) -> Symbol {
+        let function = SymFunction { mode: InstructionMode::Arm, size, unknown: false, veneer: false };
+        Symbol { name: name.to_string(), kind: SymbolKind::Function(function), addr, ambiguous: false, local: false }
+    }
+
+    fn data(name: &str, addr: u32, data: SymData) -> Symbol {
+        Symbol { name: name.to_string(), kind: SymbolKind::Data(data), addr, ambiguous: false, local: false }
+    }
+
+    fn merge(existing: Vec<Symbol>, new: Vec<Symbol>) -> (SymbolMap, UpdateReport) {
+        let mut symbol_map = SymbolMap::from_symbols(existing);
+        let mut report = UpdateReport::new();
+        merge_symbols(&mut symbol_map, &SymbolMap::from_symbols(new), "main", &mut report);
+        (symbol_map, report)
+    }
+
+    fn symbol_at(symbol_map: &SymbolMap, addr: u32) -> &Symbol {
+        symbol_map.by_address(addr).unwrap().unwrap().1
+    }
+
+    #[test]
+    fn test_merge_generated_name() {
+        let (symbol_map, report) =
+            merge(vec![function("func_02000000", 0x02000000, 0x10)], vec![function("SoftReset", 0x02000000, 0x10)]);
+        assert_eq!(symbol_at(&symbol_map, 0x02000000).name, "SoftReset");
+        assert!(symbol_map.for_name("func_02000000").is_none());
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_merge_user_name() {
+        let (symbol_map, report) =
+            merge(vec![function("MyFunc", 0x02000000, 0x10)], vec![function("SoftReset", 0x02000000, 0x10)]);
+        assert_eq!(symbol_at(&symbol_map, 0x02000000).name, "MyFunc");
+        assert_eq!(report.len(), 1);
+
+        let (symbol_map, report) =
+            merge(vec![function("MyFunc", 0x02000000, 0x10)], vec![function("func_02000000", 0x02000000, 0x20)]);
+        let symbol = symbol_at(&symbol_map, 0x02000000);
+        assert_eq!(symbol.name, "MyFunc");
+        assert!(matches!(symbol.kind, SymbolKind::Function(SymFunction { size: 0x20, .. })));
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_merge_data_kind() {
+        let word = SymData::Word { count: None };
+        let (symbol_map, report) =
+            merge(vec![data("data_02000100", 0x02000100, SymData::Any)], vec![data("data_02000100", 0x02000100, word)]);
+        assert!(symbol_at(&symbol_map, 0x02000100).kind == SymbolKind::Data(word));
+        assert!(report.is_empty());
+
+        let (symbol_map, report) =
+            merge(vec![data("data_02000100", 0x02000100, word)], vec![data("data_02000100", 0x02000100, SymData::Any)]);
+        assert!(symbol_at(&symbol_map, 0x02000100).kind == SymbolKind::Data(word));
+        assert!(report.is_empty());
+
+        let (symbol_map, report) =
+            merge(vec![data("gTable", 0x02000100, SymData::Any)], vec![function("func_02000100", 0x02000100, 0x10)]);
+        assert!(symbol_at(&symbol_map, 0x02000100).kind == SymbolKind::Data(SymData::Any));
+        assert_eq!(report.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_new_symbols() {
+        let (symbol_map, report) = merge(
+            vec![function("func_02000000", 0x02000000, 0x10)],
+            vec![
+                function("func_02000000", 0x02000000, 0x10),
+                function("func_02000004", 0x02000004, 0x4),
+                function("func_02000010", 0x02000010, 0x10),
+            ],
+        );
+        assert!(symbol_map.by_address(0x02000004).unwrap().is_none());
+        assert_eq!(symbol_at(&symbol_map, 0x02000010).name, "func_02000010");
+        assert_eq!(report.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_relocations() {
+        let mut relocations = Relocations::new();
+        relocations.add_load(0x02000100, 0x02000000, 0, RelocationModule::Main).unwrap();
+        relocations.add_load(0x02000104, 0x02000010, 0, RelocationModule::Main).unwrap();
+
+        let mut new_relocations = Relocations::new();
+        new_relocations.add_load(0x02000100, 0x02000000, 0, RelocationModule::Main).unwrap();
+        new_relocations.add_load(0x02000104, 0x02000020, 0, RelocationModule::Main).unwrap();
+        new_relocations.add_load(0x02000108, 0x02000030, 0, RelocationModule::Main).unwrap();
+
+        let mut report = UpdateReport::new();
+        merge_relocations(&mut relocations, &new_relocations, &[], "main", &mut report).unwrap();
+        assert_eq!(relocations.get(0x02000104).unwrap().to_address(), 0x02000010);
+        assert_eq!(relocations.get(0x02000108).unwrap().to_address(), 0x02000030);
+        assert_eq!(report.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_ignored_relocations() {
+        let mut relocations = Relocations::new();
+        relocations.add_load(0x02000100, 0x02000000, 0, RelocationModule::Main).unwrap();
+        relocations.add_load(0x02000104, 0x02000010, 0, RelocationModule::Main).unwrap();
+
+        let ignored = [
+            Relocation::new_load(0x02000100, 0x02000000, 0, RelocationModule::Main),
+            Relocation::new_load(0x02000104, 0x02000020, 0, RelocationModule::Main),
+        ];
+        let mut report = UpdateReport::new();
+        merge_relocations(&mut relocations, &Relocations::new(), &ignored, "main", &mut report).unwrap();
+        assert!(relocations.get(0x02000100).is_none());
+        assert!(!relocations.is_not_pointer(0x02000100));
+        assert_eq!(relocations.get(0x02000104).unwrap().to_address(), 0x02000010);
+        assert_eq!(report.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_not_pointers() {
+        let mut relocations = Relocations::new();
+        relocations.add_load(0x02000100, 0x02000000, 0, RelocationModule::Main).unwrap();
+        relocations.add_not_pointer(0x02000108);
+
+        let mut new_relocations = Relocations::new();
+        new_relocations.add_not_pointer(0x02000100);
+        new_relocations.add_not_pointer(0x02000104);
+        new_relocations.add_load(0x02000108, 0x02000000, 0, RelocationModule::Main).unwrap();
+
+        let mut report = UpdateReport::new();
+        merge_relocations(&mut relocations, &new_relocations, &[], "main", &mut report).unwrap();
+        assert!(relocations.get(0x02000100).is_some());
+        assert!(relocations.is_not_pointer(0x02000104));
+        assert!(relocations.get(0x02000108).is_none());
+        assert_eq!(report.len(), 1);
+    }
+}
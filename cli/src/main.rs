@@ -1,6 +1,12 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::{Parser, Subcommand};
-use ds_decomp_cli::cmd::{CheckArgs, Delink, Disassemble, ImportArgs, Init, Lcf, Objdiff, RomArgs};
+use ds_decomp_cli::{
+    cmd::{
+        CheckArgs, DebugElf, Delink, Diff, Disassemble, ExportCallGraph, Headers, ImportArgs, Init, Lcf, Link, Lsp, Ninja,
+        Objdiff, RelocsArgs, RomArgs, Xref,
+    },
+    util::output::{self, OutputFormat},
+};
 use log::LevelFilter;
 
 /// Command-line toolkit for decompiling DS games.
@@ -11,6 +17,10 @@ struct Cli {
     #[arg(long, short)]
     debug: bool,
 
+    /// Format of logs, results and diagnostics.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -26,6 +36,16 @@ enum Command {
     Import(ImportArgs),
     Check(CheckArgs),
     Objdiff(Objdiff),
+    Lsp(Lsp),
+    Xref(Xref),
+    CallGraph(ExportCallGraph),
+    Relocs(RelocsArgs),
+    Headers(Headers),
+    Ninja(Ninja),
+    #[command(name = "elf")]
+    DebugElf(DebugElf),
+    Diff(Diff),
+    Link(Link),
 }
 
 impl Command {
@@ -39,15 +59,36 @@ impl Command {
             Command::Import(import) => import.run(),
             Command::Check(check) => check.run(),
             Command::Objdiff(objdiff) => objdiff.run(),
+            Command::Lsp(lsp) => lsp.run(),
+            Command::Xref(xref) => xref.run(),
+            Command::CallGraph(call_graph) => call_graph.run(),
+            Command::Relocs(relocs) => relocs.run(),
+            Command::Headers(headers) => headers.run(),
+            Command::Ninja(ninja) => ninja.run(),
+            Command::DebugElf(debug_elf) => debug_elf.run(),
+            Command::Diff(diff) => diff.run(),
+            Command::Link(link) => link.run(),
         }
     }
 }
 
 fn main() -> Result<()> {
     let args: Cli = Cli::parse();
+    if args.output == OutputFormat::Json && matches!(args.command, Command::Lsp(_)) {
+        // The language server protocol uses stdout, so logs must stay on stderr
+        bail!("The lsp command doesn't support --output json");
+    }
 
     let level = if args.debug { LevelFilter::Debug } else { LevelFilter::Info };
-    env_logger::builder().filter_level(level).init();
+    output::set_format(args.output);
+    let mut logger = env_logger::builder();
+    logger.filter_level(level);
+    if args.output == OutputFormat::Json {
+        logger.target(env_logger::Target::Stdout).format(|buf, record| output::write_log_record(buf, record));
+    }
+    logger.init();
 
-    args.command.run()
+    let result = args.command.run();
+    output::done(result.as_ref().err());
+    result
 }
@@ -0,0 +1,48 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use objdiff_core::{
+    diff::{self, DiffObjConfig, MappingConfig, ObjDiff},
+    obj::{self, ObjInfo},
+};
+
+/// A delinked target object and a built base object, diffed with objdiff.
+pub struct ObjectDiff {
+    pub target: ObjInfo,
+    pub base: ObjInfo,
+    pub target_diff: ObjDiff,
+    pub base_diff: ObjDiff,
+}
+
+impl ObjectDiff {
+    pub fn new(target_path: &Path, base_path: &Path) -> Result<Self> {
+        let config = DiffObjConfig::default();
+        let target = obj::read::read(target_path, &config)
+            .with_context(|| format!("Failed to read delinked object {}", target_path.display()))?;
+        let base = obj::read::read(base_path, &config)
+            .with_context(|| format!("Failed to read built object {}", base_path.display()))?;
+
+        let result = diff::diff_objs(&config, &MappingConfig::default(), Some(&target), Some(&base), None)?;
+        let target_diff = result.left.context("objdiff returned no diff for the delinked object")?;
+        let base_diff = result.right.context("objdiff returned no diff for the built object")?;
+
+        Ok(Self { target, base, target_diff, base_diff })
+    }
+
+    /// Returns whether every section and sized symbol of both objects have a fully matching counterpart.
+    pub fn is_full_match(&self) -> bool {
+        Self::is_side_matching(&self.target, &self.target_diff) && Self::is_side_matching(&self.base, &self.base_diff)
+    }
+
+    fn is_side_matching(obj: &ObjInfo, obj_diff: &ObjDiff) -> bool {
+        obj.sections.iter().zip(obj_diff.sections.iter()).all(|(section, section_diff)| {
+            let section_matching = section_diff.match_percent.map_or(true, |percent| percent >= 100.0);
+            let symbols_matching = section.symbols.iter().zip(section_diff.symbols.iter()).all(|(symbol, symbol_diff)| {
+                symbol.size == 0
+                    || (symbol_diff.target_symbol.is_some()
+                        && symbol_diff.match_percent.map_or(true, |percent| percent >= 100.0))
+            });
+            section_matching && symbols_matching
+        })
+    }
+}
@@ -1,5 +1,7 @@
 pub mod bytes;
 pub mod debug;
+pub mod diff;
 pub mod io;
+pub mod output;
 pub mod parse;
 pub mod path;
@@ -0,0 +1,144 @@
+use std::{fmt::Display, io::Write, sync::OnceLock};
+
+use clap::ValueEnum;
+use log::Level;
+use serde::Serialize;
+
+/// Format of the results and diagnostics that commands print.
+#[derive(Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable log lines.
+    #[default]
+    Text,
+    /// One JSON object per line on stdout, including log lines.
+    Json,
+}
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// Sets the output format for the rest of the process. Only the first call has an effect.
+pub fn set_format(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+pub fn format() -> OutputFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+pub fn is_json() -> bool {
+    format() == OutputFormat::Json
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Line<'a, T: Serialize> {
+    Log {
+        level: &'a str,
+        target: &'a str,
+        message: String,
+    },
+    Diagnostic(&'a Diagnostic),
+    Result {
+        command: &'a str,
+        result: &'a T,
+    },
+    Done {
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+fn print_line<T: Serialize>(line: &Line<T>) {
+    let mut stdout = std::io::stdout().lock();
+    // Errors are ignored like with `println!`, there is nowhere else to report them
+    let _ = serde_json::to_writer(&mut stdout, line);
+    let _ = writeln!(stdout);
+}
+
+/// Formats a log record as a JSON line, for `env_logger` in JSON mode.
+pub fn write_log_record(buf: &mut impl Write, record: &log::Record) -> std::io::Result<()> {
+    let level = record.level().as_str().to_lowercase();
+    let line: Line<()> = Line::Log { level: &level, target: record.target(), message: record.args().to_string() };
+    serde_json::to_writer(&mut *buf, &line)?;
+    writeln!(buf)
+}
+
+/// Prints the structured result of a command. Nothing is printed in text mode, where commands log their results instead.
+pub fn result<T: Serialize>(command: &str, result: &T) {
+    if is_json() {
+        print_line(&Line::Result { command, result });
+    }
+}
+
+/// Prints whether the command succeeded, and its error if it failed. Nothing is printed in text mode.
+pub fn done(error: Option<&anyhow::Error>) {
+    if is_json() {
+        let line: Line<()> = Line::Done { success: error.is_none(), error: error.map(|error| format!("{error:#}")) };
+        print_line(&line);
+    }
+}
+
+/// A problem found by a command, such as a failed check, with where it was found.
+#[derive(Serialize)]
+pub struct Diagnostic {
+    #[serde(serialize_with = "serialize_level")]
+    level: Level,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    module: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<u32>,
+}
+
+fn serialize_level<S: serde::Serializer>(level: &Level, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&level.as_str().to_lowercase())
+}
+
+impl Diagnostic {
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Self { level, message: message.into(), module: None, file: None, symbol: None, address: None }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Level::Error, message)
+    }
+
+    pub fn warn(message: impl Into<String>) -> Self {
+        Self::new(Level::Warn, message)
+    }
+
+    pub fn module(mut self, module: impl Display) -> Self {
+        self.module = Some(module.to_string());
+        self
+    }
+
+    pub fn file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    pub fn address(mut self, address: u32) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// Logs the message in text mode, or prints the diagnostic with its fields in JSON mode.
+    pub fn emit(self) {
+        if is_json() {
+            let line: Line<()> = Line::Diagnostic(&self);
+            print_line(&line);
+        } else {
+            log::log!(self.level, "{}", self.message);
+        }
+    }
+}
@@ -1 +1 @@
-
+pub use ds_decomp::util::parse::{parse_u16, parse_u32};
@@ -9,10 +9,9 @@ use std::{
 };
 
 use anyhow::Result;
-use ds_decomp::config::config::Config;
+use ds_decomp::{analysis::data::AnalyzeExternalReferencesError, config::config::Config};
 use ds_decomp_cli::{
-    analysis::data::AnalyzeExternalReferencesError,
-    cmd::{CheckModules, CheckSymbols, ConfigRom, Delink, Disassemble, Init, Lcf},
+    cmd::{Build, CheckModules, CheckSymbols, ConfigRom, Delink, Disassemble, Init, Lcf, Link},
     util::io::read_to_string,
 };
 use ds_rom::{
@@ -24,7 +23,7 @@ use zip::ZipArchive;
 
 #[test]
 fn test_roundtrip() -> Result<()> {
-    env_logger::builder().filter_level(LevelFilter::Info).init();
+    let _ = env_logger::builder().filter_level(LevelFilter::Info).try_init();
 
     let cwd = std::env::current_dir()?;
     let assets_dir = cwd.join("tests/assets");
@@ -141,6 +140,85 @@ fn test_roundtrip() -> Result<()> {
     Ok(())
 }
 
+/// Same as [`test_roundtrip`] but links with the built-in linker, so it needs no toolchain or network access.
+#[test]
+fn test_roundtrip_builtin_linker() -> Result<()> {
+    let _ = env_logger::builder().filter_level(LevelFilter::Info).try_init();
+
+    let cwd = std::env::current_dir()?;
+    let assets_dir = cwd.join("tests/assets");
+    let arm7_bios = assets_dir.join("arm7_bios.bin");
+    assert!(arm7_bios.exists());
+    assert!(arm7_bios.is_file());
+
+    let roms_dir = cwd.join("tests/roms/");
+
+    let key = BlowfishKey::from_arm7_bios_path(&arm7_bios)?;
+
+    for entry in roms_dir.read_dir()? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension() != Some(OsStr::new("nds")) {
+            continue;
+        }
+
+        // Extract ROM, in a separate directory from `test_roundtrip` which may run at the same time
+        let base_name = path.with_extension("").file_name().unwrap().to_str().unwrap().to_string();
+        let project_path = roms_dir.join(format!("{base_name}_link"));
+        let extract_path = extract_rom(&path, &project_path, &key)?;
+        let rom_config = extract_path.join("config.yaml");
+
+        // Init dsd project
+        let dsd_config_dir = dsd_init(&project_path, &rom_config, false).or_else(|e| match e
+            .downcast_ref::<AnalyzeExternalReferencesError>()
+        {
+            Some(AnalyzeExternalReferencesError::LocalFunctionNotFound { .. }) => {
+                log::info!("dsd init failed, trying again with unknown function calls");
+                dsd_init(&project_path, &rom_config, true)
+            }
+            _ => Err(e),
+        })?;
+        let dsd_config_yaml = dsd_config_dir.join("arm9/config.yaml");
+        let dsd_config = Config::from_file(&dsd_config_yaml)?;
+
+        // Delink modules
+        let delink = Delink { config_path: dsd_config_yaml.clone() };
+        delink.run()?;
+
+        // Link
+        let build_path = dsd_config_yaml.parent().unwrap().join(dsd_config.build_path);
+        let linker_out_file = build_path.join("arm9.o");
+        let link = Link { config_path: dsd_config_yaml.clone(), elf_path: linker_out_file.clone() };
+        link.run()?;
+
+        // Check modules
+        let check_modules = CheckModules { config_path: dsd_config_yaml.clone(), fail: true };
+        check_modules.run()?;
+
+        // Check symbols
+        let check_symbols =
+            CheckSymbols { config_path: dsd_config_yaml.clone(), fail: true, elf_path: linker_out_file.clone() };
+        check_symbols.run()?;
+
+        // Configure ds-rom
+        let config_rom = ConfigRom { elf: linker_out_file.clone(), config: dsd_config_yaml.clone() };
+        config_rom.run()?;
+
+        // Build ROM
+        let main_object = dsd_config_yaml.parent().unwrap().join(&dsd_config.main_module.object);
+        let build = Build {
+            config: main_object.parent().unwrap().join("rom_config.yaml"),
+            arm7_bios: Some(arm7_bios.clone()),
+            rom: project_path.join("rom.nds"),
+        };
+        build.run()?;
+
+        fs::remove_dir_all(project_path)?;
+    }
+
+    Ok(())
+}
+
 fn dsd_init(project_path: &Path, rom_config: &Path, allow_unknown_function_calls: bool) -> Result<PathBuf> {
     let dsd_config_dir = project_path.join("config");
     let build_path = project_path.join("build");
@@ -152,6 +230,7 @@ fn dsd_init(project_path: &Path, rom_config: &Path, allow_unknown_function_calls
         skip_reloc_analysis: false,
         allow_unknown_function_calls,
         provide_reloc_source: false,
+        update: false,
     };
     init.run()?;
     Ok(dsd_config_dir)
@@ -1,12 +1,15 @@
-use std::ops::Range;
+use std::{backtrace::Backtrace, ops::Range};
 
 use crate::{
-    analysis::functions::Function,
+    analysis::functions::{CalledFunction, Function, PoolConstant},
     config::{
-        module::{AnalysisOptions, ModuleKind},
-        relocations::{RelocationModuleKindNotSupportedError, Relocations, RelocationsError},
-        section::{Section, SectionKind, Sections},
-        symbol::{SymBss, SymData, SymbolMap, SymbolMapError},
+        module::{AnalysisOptions, Module, ModuleKind},
+        relocations::{
+            Relocation, RelocationFromModulesError, RelocationModule, RelocationModuleKindNotSupportedError, Relocations,
+            RelocationsError,
+        },
+        section::{Section, SectionCodeError, SectionIndex, SectionKind, Sections},
+        symbol::{SymBss, SymData, SymbolMap, SymbolMapError, SymbolMaps},
     },
     function,
 };
@@ -43,6 +46,9 @@ pub fn find_local_data_from_pools(
     let address_range = None;
 
     for pool_constant in function.iter_pool_constants(code, base_address) {
+        if relocations.is_not_pointer(pool_constant.address) {
+            continue;
+        }
         let pointer = pool_constant.value;
         let Some((_, section)) = sections.get_by_contained_address(pointer) else {
             // Not a pointer, or points to a different module
@@ -74,9 +80,47 @@ pub fn find_local_data_from_pools(
         }
     }
 
+    add_pointer_table_relocations(
+        function.iter_pointer_table_entries(code, base_address),
+        relocations,
+        module_kind,
+        analysis_options,
+    )?;
+
+    Ok(())
+}
+
+/// Relocates jump destinations so that pointer tables survive relinking. Thumb destinations are relocated to the
+/// instruction with an addend of 1, so that the linker sets the Thumb bit again.
+fn add_pointer_table_relocations(
+    entries: impl Iterator<Item = PoolConstant>,
+    relocations: &mut Relocations,
+    module_kind: ModuleKind,
+    analysis_options: &AnalysisOptions,
+) -> Result<(), FindLocalDataError> {
+    for entry in entries {
+        if relocations.is_not_pointer(entry.address) {
+            continue;
+        }
+        let thumb_bit = entry.value & 1;
+        let reloc = relocations.add_load(entry.address, entry.value & !1, thumb_bit as i32, module_kind.try_into()?)?;
+        if analysis_options.provide_reloc_source {
+            reloc.source = Some(function!().to_string());
+        }
+    }
     Ok(())
 }
 
+/// Data words scoring below this are assumed to be integers which happen to look like pointers. Each of these adds to the
+/// score:
+/// - 2 if the word points to a known symbol, or to a function with the same instruction mode
+/// - 2 if the word points to a 4-byte aligned address in a data or bss section
+/// - 1 for each neighboring word which points into the module
+///
+/// So aligned pointers to data pass on their own, while misaligned ones, which are mostly to strings and byte arrays, need a
+/// symbol or two neighboring pointers.
+const MIN_POINTER_SCORE: i32 = 2;
+
 pub fn find_local_data_from_section(
     section: &Section,
     options: FindLocalDataOptions,
@@ -86,14 +130,31 @@ pub fn find_local_data_from_section(
 
     let address_range = options.address_range.clone().unwrap_or(section.address_range());
 
-    for word in section.iter_words(code, Some(address_range.clone())) {
+    // Score every word before adding any symbols, so that the result doesn't depend on the order of the words
+    let words = section.iter_words(code, Some(address_range.clone())).collect::<Vec<_>>();
+    let mut pointers = vec![];
+    for (i, word) in words.iter().enumerate() {
         let pointer = word.value;
-        let Some((_, section)) = options.sections.get_by_contained_address(pointer) else {
+        let Some((_, section)) = sections.get_by_contained_address(pointer) else {
             continue;
         };
+        if relocations.is_not_pointer(word.address) {
+            continue;
+        }
+        let neighbors = [i.checked_sub(1).and_then(|i| words.get(i)), words.get(i + 1)];
+        let score = pointer_score(pointer, section, neighbors.into_iter().flatten().map(|w| w.value), sections, symbol_map)?;
+        if score < MIN_POINTER_SCORE {
+            log::debug!("Word {pointer:#010x} at {:#010x} scored {score} and is likely not a pointer", word.address);
+            continue;
+        }
+        pointers.push((word.address, pointer));
+    }
+
+    for (address, pointer) in pointers {
+        let (_, section) = sections.get_by_contained_address(pointer).unwrap();
         add_symbol_from_pointer(
             section,
-            word.address,
+            address,
             pointer,
             FindLocalDataOptions {
                 sections,
@@ -111,6 +172,41 @@ pub fn find_local_data_from_section(
     Ok(())
 }
 
+/// Scores how likely a data word is to be a pointer into `section`, based on where it points to and the words around it.
+/// See [`MIN_POINTER_SCORE`].
+fn pointer_score(
+    pointer: u32,
+    section: &Section,
+    neighbors: impl Iterator<Item = u32>,
+    sections: &Sections,
+    symbol_map: &SymbolMap,
+) -> Result<i32, FindLocalDataError> {
+    let mut score = 0;
+
+    if section.kind() == SectionKind::Code {
+        // Function pointers must point to the start of a function with the same instruction mode
+        let thumb = (pointer & 1) != 0;
+        match symbol_map.get_function(pointer)? {
+            Some((function, _)) if function.mode.into_thumb() == Some(thumb) => score += 2,
+            _ => return Ok(i32::MIN),
+        }
+    } else {
+        if symbol_map.for_address(pointer).is_some() {
+            score += 2;
+        }
+        // Integers rarely land on an aligned address in the module, but misaligned pointers are mostly to strings and byte
+        // arrays, which integers are just as likely to look like
+        if (pointer & 3) == 0 {
+            score += 2;
+        }
+    }
+
+    // Pointers tend to be surrounded by other pointers, like in vtables and tables of structs
+    score += neighbors.filter(|&value| sections.get_by_contained_address(value).is_some()).count() as i32;
+
+    Ok(score)
+}
+
 fn add_symbol_from_pointer(
     section: &Section,
     address: u32,
@@ -151,3 +247,359 @@ fn add_symbol_from_pointer(
 
     Ok(())
 }
+
+pub struct AnalyzeExternalReferencesOptions<'a> {
+    pub modules: &'a [Module<'a>],
+    pub module_index: usize,
+    pub symbol_maps: &'a mut SymbolMaps,
+}
+
+#[derive(Debug, Snafu)]
+pub enum AnalyzeExternalReferencesError {
+    #[snafu(display(
+        "local function call from {from:#010x} in {module_kind} to {to:#010x} leads to no function:\n{backtrace}"
+    ))]
+    LocalFunctionNotFound { from: u32, to: u32, module_kind: ModuleKind, backtrace: Backtrace },
+    #[snafu(display("external reference to {address:#010x} has no symbol candidates:\n{backtrace}"))]
+    #[snafu(visibility(pub(crate)))]
+    NoSymbolCandidates { address: u32, backtrace: Backtrace },
+    #[snafu(transparent)]
+    SymbolMap { source: SymbolMapError },
+    #[snafu(transparent)]
+    RelocationModuleKindNotSupported { source: RelocationModuleKindNotSupportedError },
+    #[snafu(transparent)]
+    SectionCode { source: SectionCodeError },
+    #[snafu(transparent)]
+    RelocationFromModules { source: RelocationFromModulesError },
+    #[snafu(transparent)]
+    Relocations { source: RelocationsError },
+}
+
+pub fn analyze_external_references(
+    options: AnalyzeExternalReferencesOptions,
+    analysis_options: &AnalysisOptions,
+) -> Result<RelocationResult, AnalyzeExternalReferencesError> {
+    let AnalyzeExternalReferencesOptions { modules, module_index, symbol_maps } = options;
+
+    let mut result = RelocationResult::new();
+    find_relocations_in_functions(
+        &mut result,
+        AnalyzeExternalReferencesOptions { modules, module_index, symbol_maps },
+        analysis_options,
+    )?;
+    find_external_references_in_sections(modules, module_index, &mut result)?;
+    Ok(result)
+}
+
+fn find_external_references_in_sections(
+    modules: &[Module],
+    module_index: usize,
+    result: &mut RelocationResult,
+) -> Result<(), AnalyzeExternalReferencesError> {
+    for section in modules[module_index].sections().iter() {
+        match section.kind() {
+            SectionKind::Data => {}
+            SectionKind::Code | SectionKind::Bss => continue,
+        }
+
+        let code = section.code(modules[module_index].code(), modules[module_index].base_address())?.unwrap();
+        for word in section.iter_words(code, None) {
+            find_external_data(modules, module_index, word.address, word.value, result)?;
+        }
+    }
+    Ok(())
+}
+
+fn find_relocations_in_functions(
+    result: &mut RelocationResult,
+    options: AnalyzeExternalReferencesOptions,
+    analysis_options: &AnalysisOptions,
+) -> Result<(), AnalyzeExternalReferencesError> {
+    let AnalyzeExternalReferencesOptions { modules, module_index, symbol_maps } = options;
+
+    for section in modules[module_index].sections().iter() {
+        for function in section.functions().values() {
+            add_function_calls_as_relocations(
+                function,
+                result,
+                AnalyzeExternalReferencesOptions { modules, module_index, symbol_maps },
+                analysis_options,
+            )?;
+            find_external_data_from_pools(modules, module_index, function, result)?;
+        }
+    }
+    Ok(())
+}
+
+fn add_function_calls_as_relocations(
+    function: &Function,
+    result: &mut RelocationResult,
+    options: AnalyzeExternalReferencesOptions,
+    analysis_options: &AnalysisOptions,
+) -> Result<(), AnalyzeExternalReferencesError> {
+    let AnalyzeExternalReferencesOptions { modules, module_index, symbol_maps } = options;
+
+    for (&address, &called_function) in function.function_calls() {
+        if called_function.ins.is_conditional() {
+            // Dumb mwld linker bug removes the condition code from relocated call instructions
+            continue;
+        }
+        let called_function = skip_veneer(modules, module_index, called_function);
+
+        let local_module = &modules[module_index];
+        let is_local = local_module.sections().get_by_contained_address(called_function.address).is_some();
+
+        let module: RelocationModule = if is_local {
+            let module_kind = local_module.kind();
+            let symbol_map = symbol_maps.get_mut(module_kind);
+            let symbol = match symbol_map.get_function_containing(called_function.address) {
+                Some((_, symbol)) => symbol,
+                None => {
+                    if !analysis_options.allow_unknown_function_calls {
+                        let error =
+                            LocalFunctionNotFoundSnafu { from: address, to: called_function.address, module_kind }.build();
+                        log::error!("{error}");
+                        return Err(error);
+                    } else {
+                        log::warn!("Local function call from {:#010x} in {} to {:#010x} leads to no function, inserting an unknown function symbol",
+                        address,
+                        module_kind,
+                        called_function.address);
+                        let thumb_bit = if called_function.thumb { 1 } else { 0 };
+                        let function_address = called_function.address | thumb_bit;
+
+                        let name = format!("{}{:08x}_unk", local_module.default_func_prefix, function_address);
+                        let (_, symbol) = symbol_map.add_unknown_function(name, function_address, called_function.thumb);
+                        symbol
+                    }
+                }
+            };
+            if called_function.address != symbol.addr {
+                log::warn!("Local function call from {:#010x} in {} to {:#010x} goes to middle of function '{}' at {:#010x}, adding an external label symbol",
+                address, module_kind, called_function.address, symbol.name, symbol.addr);
+                symbol_map.add_external_label(called_function.address, called_function.thumb)?;
+            }
+
+            module_kind.try_into()?
+        } else {
+            let candidates = modules.iter().filter(|&module| {
+                let symbol_map = symbol_maps.get(module.kind()).unwrap();
+                let Some((function, _)) = symbol_map.get_function(called_function.address).unwrap() else {
+                    return false;
+                };
+                function.mode.into_thumb() == Some(called_function.thumb)
+            });
+            RelocationModule::from_modules(candidates)?
+        };
+
+        if module == RelocationModule::None {
+            log::warn!(
+                "No functions from {address:#010x} in {} to {:#010x}:",
+                modules[module_index].kind(),
+                called_function.address
+            );
+        }
+
+        if called_function.ins.mnemonic() == "b" {
+            result.relocations.push(Relocation::new_branch(address, called_function.address, module));
+        } else {
+            result.relocations.push(Relocation::new_call(
+                address,
+                called_function.address,
+                module,
+                function.is_thumb(),
+                called_function.thumb,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Follows a call through a linker-generated veneer, so that it gets relocated to the veneer's target instead. The linker
+/// will generate the veneer again if it's needed.
+fn skip_veneer(modules: &[Module], module_index: usize, called_function: CalledFunction) -> CalledFunction {
+    let local_module = &modules[module_index];
+    let is_local = local_module.sections().get_by_contained_address(called_function.address).is_some();
+    let candidates = if is_local { std::slice::from_ref(local_module) } else { modules };
+
+    let veneer = candidates.iter().find_map(|module| {
+        let (_, section) = module.sections().get_by_contained_address(called_function.address)?;
+        section.functions().get(&called_function.address)?.veneer()
+    });
+    match veneer {
+        Some(veneer) => {
+            CalledFunction { ins: called_function.ins, address: veneer.target & !1, thumb: (veneer.target & 1) != 0 }
+        }
+        None => called_function,
+    }
+}
+
+fn find_external_data_from_pools(
+    modules: &[Module<'_>],
+    module_index: usize,
+    function: &Function,
+    result: &mut RelocationResult,
+) -> Result<(), AnalyzeExternalReferencesError> {
+    let module = &modules[module_index];
+    for pool_constant in function.iter_pool_constants(module.code(), module.base_address()) {
+        find_external_data(modules, module_index, pool_constant.address, pool_constant.value, result)?;
+    }
+    Ok(())
+}
+
+fn find_external_data(
+    modules: &[Module],
+    module_index: usize,
+    address: u32,
+    pointer: u32,
+    result: &mut RelocationResult,
+) -> Result<(), AnalyzeExternalReferencesError> {
+    let local_module = &modules[module_index];
+    if local_module.relocations().is_not_pointer(address) {
+        return Ok(());
+    }
+    let is_local = local_module.sections().get_by_contained_address(pointer).is_some();
+    if is_local {
+        return Ok(());
+    }
+
+    let candidates = find_symbol_candidates(modules, module_index, pointer);
+    if candidates.is_empty() {
+        // Probably not a pointer
+        return Ok(());
+    }
+
+    let candidate_modules = candidates.iter().map(|c| &modules[c.module_index]);
+    let module = RelocationModule::from_modules(candidate_modules)?;
+
+    result.relocations.push(Relocation::new_load(address, pointer, 0, module));
+    result.external_symbols.push(ExternalSymbol { candidates, address: pointer });
+    Ok(())
+}
+
+fn find_symbol_candidates(modules: &[Module], module_index: usize, pointer: u32) -> Vec<SymbolCandidate> {
+    modules
+        .iter()
+        .enumerate()
+        .filter_map(|(index, module)| {
+            if index == module_index {
+                return None;
+            }
+            let (section_index, section) = module.sections().get_by_contained_address(pointer)?;
+            if section.kind() == SectionKind::Code {
+                let function = section.functions().get(&(pointer & !1))?;
+                let thumb = (pointer & 1) != 0;
+                if function.is_thumb() != thumb {
+                    return None;
+                }
+            };
+            Some(SymbolCandidate { module_index: index, section_index })
+        })
+        .collect::<Vec<_>>()
+}
+
+pub struct RelocationResult {
+    pub relocations: Vec<Relocation>,
+    pub external_symbols: Vec<ExternalSymbol>,
+}
+
+impl RelocationResult {
+    fn new() -> Self {
+        Self { relocations: vec![], external_symbols: vec![] }
+    }
+}
+
+pub struct ExternalSymbol {
+    pub candidates: Vec<SymbolCandidate>,
+    pub address: u32,
+}
+
+pub struct SymbolCandidate {
+    pub module_index: usize,
+    pub section_index: SectionIndex,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::symbol::{InstructionMode, SymFunction, Symbol, SymbolKind};
+
+    fn sections() -> Sections {
+        let mut sections = Sections::new();
+        sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x02001000, 32).unwrap()).unwrap();
+        sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02001000, 0x02002000, 32).unwrap()).unwrap();
+        sections
+    }
+
+    fn thumb_function(addr: u32) -> Symbol {
+        let function = SymFunction { mode: InstructionMode::Thumb, size: 0x10, unknown: false, veneer: false };
+        Symbol { name: format!("func_{addr:08x}"), kind: SymbolKind::Function(function), addr, ambiguous: false, local: false }
+    }
+
+    fn score(pointer: u32, neighbors: &[u32], symbol_map: &SymbolMap) -> i32 {
+        let sections = sections();
+        let (_, section) = sections.get_by_contained_address(pointer).unwrap();
+        pointer_score(pointer, section, neighbors.iter().copied(), &sections, symbol_map).unwrap()
+    }
+
+    #[test]
+    fn test_misaligned_integer() {
+        let symbol_map = SymbolMap::new();
+        assert!(score(0x02001101, &[0x00000005, 0x00000000], &symbol_map) < MIN_POINTER_SCORE);
+        assert!(score(0x02001102, &[0x02001100], &symbol_map) < MIN_POINTER_SCORE);
+
+        let symbol_map =
+            SymbolMap::from_symbols(vec![Symbol::new_data("data_02001101".to_string(), 0x02001101, SymData::Any, false)]);
+        assert!(score(0x02001101, &[0x00000005, 0x00000000], &symbol_map) >= MIN_POINTER_SCORE);
+    }
+
+    #[test]
+    fn test_isolated_data_pointer() {
+        // Pointer to data without a symbol yet, between two integers like in a struct
+        let symbol_map = SymbolMap::new();
+        assert!(score(0x02001100, &[0x00000005, 0x00000000], &symbol_map) >= MIN_POINTER_SCORE);
+        assert!(score(0x02001100, &[], &symbol_map) >= MIN_POINTER_SCORE);
+    }
+
+    #[test]
+    fn test_vtable() {
+        let symbol_map =
+            SymbolMap::from_symbols(vec![thumb_function(0x02000100), thumb_function(0x02000200), thumb_function(0x02000300)]);
+        assert!(score(0x02000201, &[0x02000101, 0x02000301], &symbol_map) >= MIN_POINTER_SCORE);
+
+        // Table of pointers to data without symbols yet
+        let symbol_map = SymbolMap::new();
+        assert!(score(0x02001200, &[0x02001100, 0x02001300], &symbol_map) >= MIN_POINTER_SCORE);
+    }
+
+    #[test]
+    fn test_thumb_pointer_table() {
+        let entries = [
+            PoolConstant { address: 0x02000010, value: 0x02000021 },
+            PoolConstant { address: 0x02000014, value: 0x02000031 },
+            PoolConstant { address: 0x02000018, value: 0x02000041 },
+        ];
+        let mut relocations = Relocations::new();
+        relocations.add_not_pointer(0x02000018);
+        let analysis_options = AnalysisOptions {
+            allow_unknown_function_calls: false,
+            provide_reloc_source: false,
+            not_pointers: Default::default(),
+            hints: Default::default(),
+        };
+        add_pointer_table_relocations(entries.into_iter(), &mut relocations, ModuleKind::Arm9, &analysis_options).unwrap();
+
+        let relocation = relocations.get(0x02000010).unwrap();
+        assert_eq!(relocation.to_address(), 0x02000020);
+        assert_eq!(relocation.addend(), 1);
+        assert_eq!(relocations.get(0x02000014).unwrap().to_address(), 0x02000030);
+        assert!(relocations.get(0x02000018).is_none());
+    }
+
+    #[test]
+    fn test_thumb_bit_mismatch() {
+        let symbol_map = SymbolMap::from_symbols(vec![thumb_function(0x02000100)]);
+        assert!(score(0x02000101, &[], &symbol_map) >= MIN_POINTER_SCORE);
+        assert_eq!(score(0x02000100, &[0x02000101, 0x02001100], &symbol_map), i32::MIN);
+    }
+}
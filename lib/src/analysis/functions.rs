@@ -1,12 +1,13 @@
 use std::{
     backtrace::Backtrace,
     collections::{BTreeMap, BTreeSet},
+    ops::Range,
 };
 
 use snafu::Snafu;
 use unarm::{
     args::{Argument, Reg, Register},
-    thumb, ArmVersion, Endian, Ins, ParseFlags, ParseMode, ParsedIns, Parser,
+    arm, thumb, ArmVersion, Endian, Ins, ParseFlags, ParseMode, ParsedIns, Parser,
 };
 
 use crate::{
@@ -19,8 +20,9 @@ use super::{
     function_start::is_valid_function_start,
     illegal_code::IllegalCodeState,
     inline_table::{InlineTable, InlineTableState},
-    jump_table::{JumpTable, JumpTableState},
-    secure_area::SecureAreaState,
+    jump_table::{JumpTable, JumpTableState, PointerTable, PointerTableJump},
+    secure_area::{SecureAreaFunction, SecureAreaState},
+    veneer::Veneer,
 };
 
 // All keys in the types below are instruction addresses
@@ -28,6 +30,7 @@ pub type Labels = BTreeSet<u32>;
 pub type PoolConstants = BTreeSet<u32>;
 pub type JumpTables = BTreeMap<u32, JumpTable>;
 pub type InlineTables = BTreeMap<u32, InlineTable>;
+pub type PointerTables = BTreeMap<u32, PointerTable>;
 pub type FunctionCalls = BTreeMap<u32, CalledFunction>;
 pub type DataLoads = BTreeMap<u32, u32>;
 
@@ -42,7 +45,9 @@ pub struct Function {
     pool_constants: PoolConstants,
     jump_tables: JumpTables,
     inline_tables: InlineTables,
+    pointer_tables: PointerTables,
     function_calls: FunctionCalls,
+    veneer: Option<Veneer>,
 }
 
 #[derive(Debug, Snafu)]
@@ -190,6 +195,9 @@ impl Function {
         options: FunctionParseOptions,
     ) -> Result<ParseFunctionResult, FunctionAnalysisError> {
         let thumb = parser.mode == ParseMode::Thumb;
+        if let Some(function) = Self::parse_veneer(thumb, &options) {
+            return Ok(ParseFunctionResult::Found(function));
+        }
         let mut context = ParseFunctionContext::new(thumb, options);
 
         let Some((address, ins, parsed_ins)) = parser.next() else { return Ok(ParseFunctionResult::NoEpilogue) };
@@ -233,6 +241,32 @@ impl Function {
         Ok(ParseFunctionResult::Found(function))
     }
 
+    fn parse_veneer(thumb: bool, options: &FunctionParseOptions) -> Option<Function> {
+        let FunctionParseOptions { name, start_address, base_address, module_code, .. } = options;
+
+        let start = (start_address - base_address) as usize;
+        let veneer = Veneer::parse(*start_address, thumb, module_code.get(start..)?)?;
+        let end_address = start_address + veneer.size;
+        if end_address > options.module_end_address {
+            return None;
+        }
+
+        Some(Function {
+            name: name.clone(),
+            start_address: *start_address,
+            end_address,
+            first_instruction_address: *start_address,
+            thumb,
+            labels: Labels::new(),
+            pool_constants: PoolConstants::from([start_address + veneer.target_offset()]),
+            jump_tables: JumpTables::new(),
+            inline_tables: InlineTables::new(),
+            pointer_tables: PointerTables::new(),
+            function_calls: FunctionCalls::new(),
+            veneer: Some(veneer),
+        })
+    }
+
     pub fn parse_function(options: FunctionParseOptions) -> Result<ParseFunctionResult, FunctionAnalysisError> {
         let FunctionParseOptions { start_address, base_address, module_code, parse_options, .. } = &options;
 
@@ -276,148 +310,189 @@ impl Function {
 
         let mut last_function_address = search_options.last_function_address.unwrap_or(end_address);
         let mut address = start_address;
+        let forced_functions = search_options.forced_functions.cloned().unwrap_or_default();
+        let forced_end = search_options.last_function_address.map_or(end_address, |last| last + 1).min(end_address);
+        let mut resume_address = start_address;
+
+        'search: loop {
+            while !function_code.is_empty() && address <= last_function_address {
+                if let Some(range) =
+                    search_options.data_ranges.and_then(|ranges| ranges.iter().find(|range| range.contains(&address)))
+                {
+                    if range.end >= end_address {
+                        break;
+                    }
+                    address = range.end;
+                    function_code = &module_code[(address - base_address) as usize..];
+                    continue;
+                }
 
-        while !function_code.is_empty() && address <= last_function_address {
-            let thumb = Function::is_thumb_function(address, function_code);
-
-            let parse_mode = if thumb { ParseMode::Thumb } else { ParseMode::Arm };
-            let parser = Parser::new(
-                parse_mode,
-                address,
-                Endian::Little,
-                ParseFlags { version: ArmVersion::V5Te, ual: false },
-                function_code,
-            );
+                let thumb = forced_functions
+                    .get(&address)
+                    .copied()
+                    .unwrap_or_else(|| Function::is_thumb_function(address, function_code));
+
+                let parse_mode = if thumb { ParseMode::Thumb } else { ParseMode::Arm };
+                let parser = Parser::new(
+                    parse_mode,
+                    address,
+                    Endian::Little,
+                    ParseFlags { version: ArmVersion::V5Te, ual: false },
+                    function_code,
+                );
 
-            let (name, new) = if let Some((_, symbol)) = symbol_map.by_address(address)? {
-                (symbol.name.clone(), false)
-            } else {
-                (format!("{}{:08x}", default_name_prefix, address), true)
-            };
+                let (name, new) = if let Some((_, symbol)) = symbol_map.by_address(address)? {
+                    (symbol.name.clone(), false)
+                } else {
+                    (format!("{}{:08x}", default_name_prefix, address), true)
+                };
 
-            let function_result = Function::function_parser_loop(
-                parser,
-                FunctionParseOptions {
-                    name,
-                    start_address: address,
-                    base_address,
-                    module_code,
-                    known_end_address: None,
-                    module_start_address,
-                    module_end_address,
-                    existing_functions: search_options.existing_functions,
-                    parse_options: Default::default(),
-                },
-            )?;
-            let function = match function_result {
-                ParseFunctionResult::Found(function) => function,
-                ParseFunctionResult::IllegalIns { address: illegal_address, ins, .. } => {
-                    if search_options.keep_searching_for_valid_function_start {
-                        // It's possible that we've attempted to analyze pool constants as code, which can happen if the
-                        // function has a constant pool ahead of its code.
-                        let mut next_address = (address + 1).next_multiple_of(4);
-                        if let Some(function_addresses) = search_options.function_addresses.as_ref() {
-                            if let Some(&next_function) = function_addresses.range(address + 1..).next() {
-                                next_address = next_function;
+                let function_result = Function::function_parser_loop(
+                    parser,
+                    FunctionParseOptions {
+                        name,
+                        start_address: address,
+                        base_address,
+                        module_code,
+                        known_end_address: None,
+                        module_start_address,
+                        module_end_address,
+                        existing_functions: search_options.existing_functions,
+                        next_function_address: [
+                            search_options
+                                .function_addresses
+                                .as_ref()
+                                .and_then(|function_addresses| function_addresses.range(address + 1..).next().copied()),
+                            forced_functions.range(address + 1..).next().map(|(&forced, _)| forced),
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .min(),
+                        parse_options: Default::default(),
+                    },
+                )?;
+                let function = match function_result {
+                    ParseFunctionResult::Found(function) => function,
+                    ParseFunctionResult::IllegalIns { address: illegal_address, ins, .. } => {
+                        if search_options.keep_searching_for_valid_function_start {
+                            // It's possible that we've attempted to analyze pool constants as code, which can happen if the
+                            // function has a constant pool ahead of its code.
+                            let mut next_address = (address + 1).next_multiple_of(4);
+                            if let Some(function_addresses) = search_options.function_addresses.as_ref() {
+                                if let Some(&next_function) = function_addresses.range(address + 1..).next() {
+                                    next_address = next_function;
+                                }
                             }
-                        }
-                        address = next_address;
-                        function_code = &module_code[(address - base_address) as usize..];
-                        continue;
-                    } else {
-                        if thumb {
-                            log::debug!(
-                                "Terminating function analysis due to illegal instruction at {:#010x}: {:04x}",
-                                illegal_address,
-                                ins.code()
-                            );
+                            address = next_address;
+                            function_code = &module_code[(address - base_address) as usize..];
+                            continue;
                         } else {
-                            log::debug!(
-                                "Terminating function analysis due to illegal instruction at {:#010x}: {:08x}",
-                                illegal_address,
-                                ins.code()
-                            );
+                            if thumb {
+                                log::debug!(
+                                    "Terminating function analysis due to illegal instruction at {:#010x}: {:04x}",
+                                    illegal_address,
+                                    ins.code()
+                                );
+                            } else {
+                                log::debug!(
+                                    "Terminating function analysis due to illegal instruction at {:#010x}: {:08x}",
+                                    illegal_address,
+                                    ins.code()
+                                );
+                            }
+                            break;
                         }
+                    }
+                    ParseFunctionResult::NoEpilogue => {
+                        log::debug!(
+                            "Terminating function analysis due to no epilogue in function starting from {:#010x}",
+                            address
+                        );
                         break;
                     }
-                }
-                ParseFunctionResult::NoEpilogue => {
-                    log::debug!(
-                        "Terminating function analysis due to no epilogue in function starting from {:#010x}",
-                        address
-                    );
-                    break;
-                }
-                ParseFunctionResult::InvalidStart { address: start_address, ins, parsed_ins } => {
-                    if search_options.keep_searching_for_valid_function_start {
-                        let ins_size = parse_mode.instruction_size(0);
-                        address += ins_size as u32;
-                        function_code = &function_code[ins_size..];
-                        continue;
-                    } else {
-                        if thumb {
-                            log::debug!(
-                                "Terminating function analysis due to invalid function start at {:#010x}: {:04x} {}",
-                                start_address,
-                                ins.code(),
-                                parsed_ins.display(Default::default())
-                            );
+                    ParseFunctionResult::InvalidStart { address: start_address, ins, parsed_ins } => {
+                        if search_options.keep_searching_for_valid_function_start {
+                            let ins_size = parse_mode.instruction_size(0);
+                            address += ins_size as u32;
+                            function_code = &function_code[ins_size..];
+                            continue;
                         } else {
-                            log::debug!(
-                                "Terminating function analysis due to invalid function start at {:#010x}: {:08x} {}",
-                                start_address,
-                                ins.code(),
-                                parsed_ins.display(Default::default())
-                            );
+                            if thumb {
+                                log::debug!(
+                                    "Terminating function analysis due to invalid function start at {:#010x}: {:04x} {}",
+                                    start_address,
+                                    ins.code(),
+                                    parsed_ins.display(Default::default())
+                                );
+                            } else {
+                                log::debug!(
+                                    "Terminating function analysis due to invalid function start at {:#010x}: {:08x} {}",
+                                    start_address,
+                                    ins.code(),
+                                    parsed_ins.display(Default::default())
+                                );
+                            }
+                            break;
                         }
-                        break;
                     }
-                }
-            };
+                };
 
-            if new {
-                symbol_map.add_function(&function);
-            }
-            function.add_local_symbols_to_map(symbol_map)?;
+                if new {
+                    symbol_map.add_function(&function);
+                }
+                function.add_local_symbols_to_map(symbol_map)?;
 
-            address = function.end_address;
-            function_code = &module_code[(address - base_address) as usize..];
+                address = function.end_address;
+                function_code = &module_code[(address - base_address) as usize..];
 
-            // Look for pointers to data in this module, to use as an upper bound for finding functions
-            if search_options.use_data_as_upper_bound {
-                for pool_constant in function.iter_pool_constants(module_code, base_address) {
-                    let pointer_value = pool_constant.value & !1;
-                    if pointer_value >= last_function_address {
-                        continue;
-                    }
-                    if pointer_value >= start_address && pointer_value >= address {
-                        let offset = (pointer_value - base_address) as usize;
-                        if offset < module_code.len() {
-                            let thumb = Function::is_thumb_function(pointer_value, &module_code[offset..]);
-                            let mut parser = Parser::new(
-                                if thumb { ParseMode::Thumb } else { ParseMode::Arm },
-                                pointer_value,
-                                Endian::Little,
-                                ParseFlags { ual: false, version: ArmVersion::V5Te },
-                                &module_code[offset..],
-                            );
-                            let (address, ins, parsed_ins) = parser.next().unwrap();
-                            if !is_valid_function_start(address, ins, &parsed_ins) {
-                                // The pool constant points to data, limit the upper bound
-                                last_function_address = pointer_value;
-                                log::debug!(
+                // Look for pointers to data in this module, to use as an upper bound for finding functions
+                if search_options.use_data_as_upper_bound {
+                    for pool_constant in function.iter_pool_constants(module_code, base_address) {
+                        let pointer_value = pool_constant.value & !1;
+                        if pointer_value >= last_function_address {
+                            continue;
+                        }
+                        if pointer_value >= start_address && pointer_value >= address {
+                            let offset = (pointer_value - base_address) as usize;
+                            if offset < module_code.len() {
+                                let thumb = Function::is_thumb_function(pointer_value, &module_code[offset..]);
+                                let mut parser = Parser::new(
+                                    if thumb { ParseMode::Thumb } else { ParseMode::Arm },
+                                    pointer_value,
+                                    Endian::Little,
+                                    ParseFlags { ual: false, version: ArmVersion::V5Te },
+                                    &module_code[offset..],
+                                );
+                                let (address, ins, parsed_ins) = parser.next().unwrap();
+                                if !is_valid_function_start(address, ins, &parsed_ins) {
+                                    // The pool constant points to data, limit the upper bound
+                                    last_function_address = pointer_value;
+                                    log::debug!(
                                     "Upper bound found: address to data at {:#010x} from pool constant at {:#010x} from function {}",
                                     pool_constant.value,
                                     pool_constant.address,
                                     function.name
                                 );
+                                }
                             }
                         }
                     }
                 }
+
+                functions.insert(function.first_instruction_address, function);
             }
 
-            functions.insert(function.first_instruction_address, function);
+            // Continue at functions which are known to exist, but which the search ended before
+            let Some(&next_address) = forced_functions.keys().find(|&&forced| {
+                forced >= resume_address.max(address) && forced < forced_end && !functions.contains_key(&forced)
+            }) else {
+                break 'search;
+            };
+            log::debug!("Continuing function search at {next_address:#010x} as given by hints");
+            address = next_address;
+            resume_address = next_address + 1;
+            last_function_address = last_function_address.max(next_address);
+            function_code = &module_code[(address - base_address) as usize..];
         }
         Ok(functions)
     }
@@ -435,6 +510,9 @@ impl Function {
         for inline_table in self.inline_tables().values() {
             symbol_map.add_data(None, inline_table.address, (*inline_table).into())?;
         }
+        for pointer_table in self.pointer_tables().values() {
+            symbol_map.add_data(None, pointer_table.address, (*pointer_table).into())?;
+        }
         Ok(())
     }
 
@@ -444,38 +522,56 @@ impl Function {
         symbol_map: &mut SymbolMap,
     ) -> BTreeMap<u32, Function> {
         let mut functions = BTreeMap::new();
+        for wrapper in Self::find_swi_wrappers(module_code, base_addr, true) {
+            let function = Self::from_swi_wrapper(wrapper.name().to_string(), &wrapper);
+            symbol_map.add_function(&function);
+            functions.insert(function.first_instruction_address, function);
+        }
+        functions
+    }
+
+    /// Finds functions which only call a BIOS function, like `swi #0x9; bx lr`.
+    pub fn find_swi_wrappers(module_code: &[u8], base_addr: u32, thumb: bool) -> Vec<SecureAreaFunction> {
+        let mut wrappers = vec![];
 
         let parse_flags = ParseFlags { ual: false, version: ArmVersion::V5Te };
+        let ins_size = if thumb { 2 } else { 4 };
 
         let mut address = base_addr;
         let mut state = SecureAreaState::default();
-        for ins_code in module_code.chunks_exact(2) {
-            let ins_code = u16::from_le_slice(ins_code);
-            let ins = thumb::Ins::new(ins_code as u32, &parse_flags);
-            let parsed_ins = ins.parse(&parse_flags);
-
-            state = state.handle(address, &parsed_ins);
-            if let Some(function) = state.get_function() {
-                let function = Function {
-                    name: function.name().to_string(),
-                    start_address: function.start(),
-                    end_address: function.end(),
-                    first_instruction_address: function.start(),
-                    thumb: true,
-                    labels: Labels::new(),
-                    pool_constants: PoolConstants::new(),
-                    jump_tables: JumpTables::new(),
-                    inline_tables: InlineTables::new(),
-                    function_calls: FunctionCalls::new(),
-                };
-                symbol_map.add_function(&function);
-                functions.insert(function.first_instruction_address, function);
+        for ins_code in module_code.chunks_exact(ins_size) {
+            let parsed_ins = if thumb {
+                thumb::Ins::new(u16::from_le_slice(ins_code) as u32, &parse_flags).parse(&parse_flags)
+            } else {
+                arm::Ins::new(u32::from_le_slice(ins_code), &parse_flags).parse(&parse_flags)
+            };
+
+            state = state.handle(address, thumb, &parsed_ins);
+            if let Some(wrapper) = state.get_function() {
+                wrappers.push(wrapper);
             }
 
-            address += 2;
+            address += ins_size as u32;
         }
 
-        functions
+        wrappers
+    }
+
+    pub fn from_swi_wrapper(name: String, wrapper: &SecureAreaFunction) -> Self {
+        Function {
+            name,
+            start_address: wrapper.start(),
+            end_address: wrapper.end(),
+            first_instruction_address: wrapper.start(),
+            thumb: wrapper.thumb(),
+            labels: Labels::new(),
+            pool_constants: PoolConstants::new(),
+            jump_tables: JumpTables::new(),
+            inline_tables: InlineTables::new(),
+            pointer_tables: PointerTables::new(),
+            function_calls: FunctionCalls::new(),
+            veneer: None,
+        }
     }
 
     pub fn parser<'a>(&'a self, module_code: &'a [u8], base_address: u32) -> Parser<'a> {
@@ -534,6 +630,24 @@ impl Function {
         inline_tables.values().find(|table| address >= table.address && address < table.address + table.size)
     }
 
+    pub fn pointer_tables(&self) -> &PointerTables {
+        &self.pointer_tables
+    }
+
+    /// Iterates over the entries of every pointer table.
+    pub fn iter_pointer_table_entries<'a>(
+        &'a self,
+        module_code: &'a [u8],
+        base_address: u32,
+    ) -> impl Iterator<Item = PoolConstant> + 'a {
+        self.pointer_tables.values().flat_map(move |table| {
+            (table.address..table.address + table.size).step_by(4).map(move |address| {
+                let start = (address - base_address) as usize;
+                PoolConstant { address, value: u32::from_le_slice(&module_code[start..]) }
+            })
+        })
+    }
+
     pub fn pool_constants(&self) -> &PoolConstants {
         &self.pool_constants
     }
@@ -553,6 +667,11 @@ impl Function {
     pub fn function_calls(&self) -> &FunctionCalls {
         &self.function_calls
     }
+
+    /// Returns the veneer if this function is a linker-generated stub.
+    pub fn veneer(&self) -> Option<&Veneer> {
+        self.veneer.as_ref()
+    }
 }
 
 #[derive(Default)]
@@ -565,6 +684,8 @@ pub struct FunctionParseOptions<'a> {
     pub module_start_address: u32,
     pub module_end_address: u32,
     pub existing_functions: Option<&'a BTreeMap<u32, Function>>,
+    /// Start of the next function known to exist after this one.
+    pub next_function_address: Option<u32>,
 
     pub parse_options: ParseFunctionOptions,
 }
@@ -590,11 +711,15 @@ struct ParseFunctionContext<'a> {
     pool_constants: PoolConstants,
     jump_tables: JumpTables,
     inline_tables: InlineTables,
+    pointer_tables: PointerTables,
     function_calls: FunctionCalls,
 
+    base_address: u32,
+    module_code: &'a [u8],
     module_start_address: u32,
     module_end_address: u32,
     existing_functions: Option<&'a BTreeMap<u32, Function>>,
+    next_function_address: Option<u32>,
 
     /// Address of last conditional instruction, so we can detect the final return instruction
     last_conditional_destination: Option<u32>,
@@ -609,6 +734,9 @@ struct ParseFunctionContext<'a> {
     /// State machine for detecting illegal code sequences
     illegal_code_state: IllegalCodeState,
 
+    /// Registers most recently loaded from a pool constant, and the address of that constant
+    pool_loads: Vec<(Register, u32)>,
+
     prev_ins: Option<Ins>,
     prev_parsed_ins: Option<ParsedIns>,
     prev_address: Option<u32>,
@@ -625,10 +753,13 @@ impl<'a> ParseFunctionContext<'a> {
         let FunctionParseOptions {
             name,
             start_address,
+            base_address,
+            module_code,
             known_end_address,
             module_start_address,
             module_end_address,
             existing_functions,
+            next_function_address,
             ..
         } = options;
 
@@ -642,11 +773,15 @@ impl<'a> ParseFunctionContext<'a> {
             pool_constants: PoolConstants::new(),
             jump_tables: JumpTables::new(),
             inline_tables: InlineTables::new(),
+            pointer_tables: PointerTables::new(),
             function_calls: FunctionCalls::new(),
 
+            base_address,
+            module_code,
             module_start_address,
             module_end_address,
             existing_functions,
+            next_function_address,
 
             last_conditional_destination: None,
             last_pool_address: None,
@@ -659,6 +794,8 @@ impl<'a> ParseFunctionContext<'a> {
             inline_table_state: Default::default(),
             illegal_code_state: Default::default(),
 
+            pool_loads: vec![],
+
             prev_ins: None,
             prev_parsed_ins: None,
             prev_address: None,
@@ -666,6 +803,16 @@ impl<'a> ParseFunctionContext<'a> {
     }
 
     fn handle_ins_inner(&mut self, parser: &mut Parser, address: u32, ins: Ins, parsed_ins: &ParsedIns) -> ParseFunctionState {
+        if let Some(next_function) = self.next_function_address.filter(|&next| address >= next) {
+            log::debug!(
+                "Function {} at {:#010x} runs into the known function at {:#010x}, ending it there",
+                self.name,
+                self.start_address,
+                next_function
+            );
+            self.end_address = Some(next_function);
+            return ParseFunctionState::Done;
+        }
         if self.pool_constants.contains(&address) {
             parser.seek_forward(address + 4);
             return ParseFunctionState::Continue;
@@ -674,6 +821,12 @@ impl<'a> ParseFunctionContext<'a> {
             parser.seek_forward(inline_table.address + inline_table.size);
             return ParseFunctionState::Continue;
         }
+        if let Some(pointer_table) =
+            self.pointer_tables.values().find(|table| address >= table.address && address < table.address + table.size)
+        {
+            parser.seek_forward(pointer_table.address + pointer_table.size);
+            return ParseFunctionState::Continue;
+        }
 
         self.jump_table_state = self.jump_table_state.handle(address, ins, parsed_ins, &mut self.jump_tables);
         self.last_conditional_destination = self.last_conditional_destination.max(self.jump_table_state.table_end_address());
@@ -681,6 +834,9 @@ impl<'a> ParseFunctionContext<'a> {
             self.labels.insert(label);
             self.last_conditional_destination = self.last_conditional_destination.max(Some(label));
         }
+        if let Some(jump) = self.jump_table_state.get_pointer_table_jump() {
+            self.handle_pointer_table_jump(address, jump);
+        }
 
         if self.jump_table_state.is_numerical_jump_offset() {
             // Not an instruction, continue
@@ -784,11 +940,16 @@ impl<'a> ParseFunctionContext<'a> {
             self.pool_constants.insert(pool_address);
             self.last_pool_address = self.last_pool_address.max(Some(pool_address));
         }
+        self.update_pool_loads(address, ins, parsed_ins);
 
         self.inline_table_state = self.inline_table_state.handle(self.thumb, address, parsed_ins);
         if let Some(table) = self.inline_table_state.get_table() {
-            log::debug!("Inline table found at {:#x}, size {:#x}", table.address, table.size);
-            self.inline_tables.insert(table.address, table);
+            // Tables which would run past this function are misdetected, and skipping them would skip real code
+            let table_end = table.address.checked_add(table.size);
+            if table.address >= self.base_address && table_end.is_some_and(|end| end <= self.function_end_bound()) {
+                log::debug!("Inline table found at {:#x}, size {:#x}", table.address, table.size);
+                self.inline_tables.insert(table.address, table);
+            }
         }
 
         if let Some(called_function) = Function::is_function_call(ins, parsed_ins, address, self.thumb) {
@@ -806,6 +967,78 @@ impl<'a> ParseFunctionContext<'a> {
         state
     }
 
+    fn update_pool_loads(&mut self, address: u32, ins: Ins, parsed_ins: &ParsedIns) {
+        let Argument::Reg(Reg { reg: dest, deref: false, .. }) = parsed_ins.args[0] else { return };
+        let mnemonic = ins.mnemonic();
+        if matches!(mnemonic, "cmp" | "cmn" | "tst" | "teq" | "bx")
+            || mnemonic.starts_with("str")
+            || mnemonic.starts_with("stm")
+        {
+            return;
+        }
+        self.pool_loads.retain(|&(reg, _)| reg != dest);
+        if let Some(pool_address) = Function::is_pool_load(ins, parsed_ins, address, self.thumb) {
+            self.pool_loads.push((dest, pool_address));
+        }
+    }
+
+    /// Adds a pointer table and labels its entries, if the table address is known and every entry points into this
+    /// function.
+    fn handle_pointer_table_jump(&mut self, address: u32, jump: PointerTableJump) {
+        let (table_address, inline) = if jump.base == Register::Pc {
+            (address + 8, true)
+        } else {
+            let Some(&(_, pool_address)) = self.pool_loads.iter().find(|&&(reg, _)| reg == jump.base) else { return };
+            let start = (pool_address - self.base_address) as usize;
+            let Some(bytes) = self.module_code.get(start..start + 4) else { return };
+            (u32::from_le_slice(bytes), false)
+        };
+
+        let Some(size) = jump.limit.checked_add(1).and_then(|count| count.checked_mul(4)) else { return };
+        let Some(table_end) = table_address.checked_add(size) else { return };
+        let module_range = self.base_address..self.base_address + self.module_code.len() as u32;
+        if !module_range.contains(&table_address) || table_end > module_range.end || (table_address & 3) != 0 {
+            return;
+        }
+
+        // An entry past the next function would make this function absorb it
+        let end_bound = self.function_end_bound();
+        if inline && table_end > end_bound {
+            return;
+        }
+
+        let start = (table_address - self.base_address) as usize;
+        let entries = self.module_code[start..start + size as usize]
+            .chunks_exact(4)
+            .map(|bytes| u32::from_le_slice(bytes) & !1)
+            .collect::<Vec<_>>();
+        if !entries.iter().all(|&entry| entry >= self.start_address && entry < end_bound) {
+            return;
+        }
+
+        log::debug!("Pointer table found at {:#x}, size {:#x}", table_address, size);
+        self.pointer_tables.insert(table_address, PointerTable { address: table_address, size, inline });
+        for entry in entries {
+            self.labels.insert(entry);
+            self.last_conditional_destination = self.last_conditional_destination.max(Some(entry));
+        }
+        if inline {
+            self.last_conditional_destination = self.last_conditional_destination.max(Some(table_end));
+        }
+    }
+
+    /// Returns the highest address this function can possibly end at, which is the start of the next known function or
+    /// the end of the module.
+    fn function_end_bound(&self) -> u32 {
+        let next_existing_function = self
+            .existing_functions
+            .and_then(|functions| functions.range(self.start_address + 1..).next().map(|(&address, _)| address));
+        [self.known_end_address, self.next_function_address, next_existing_function]
+            .into_iter()
+            .flatten()
+            .fold(self.module_end_address, u32::min)
+    }
+
     fn handle_label(
         &mut self,
         destination: u32,
@@ -868,6 +1101,7 @@ impl<'a> ParseFunctionContext<'a> {
         let end_address = self
             .known_end_address
             .unwrap_or(end_address.max(self.last_pool_address.map(|a| a + 4).unwrap_or(0)).next_multiple_of(4));
+        let end_address = self.next_function_address.map_or(end_address, |next| end_address.min(next));
         if end_address > self.module_end_address {
             return Ok(ParseFunctionResult::NoEpilogue);
         }
@@ -882,7 +1116,9 @@ impl<'a> ParseFunctionContext<'a> {
             pool_constants: self.pool_constants,
             jump_tables: self.jump_tables,
             inline_tables: self.inline_tables,
+            pointer_tables: self.pointer_tables,
             function_calls: self.function_calls,
+            veneer: None,
         }))
     }
 }
@@ -937,6 +1173,11 @@ pub struct FunctionSearchOptions<'a> {
     /// If the function branch is unconditional, it will also be treated as a tail call and terminate the analysis of the
     /// current function.
     pub existing_functions: Option<&'a BTreeMap<u32, Function>>,
+    /// Functions which are known to exist, and whether they are Thumb functions. The search continues at these functions
+    /// if it would otherwise end before them, and a function which would run into them ends at their start.
+    pub forced_functions: Option<&'a BTreeMap<u32, bool>>,
+    /// Ranges which contain no code and are skipped by the search.
+    pub data_ranges: Option<&'a [Range<u32>]>,
 }
 
 #[derive(Clone, Copy, Debug)]
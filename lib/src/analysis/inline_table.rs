@@ -1,5 +1,5 @@
 use unarm::{
-    args::{Argument, Reg, Register, Shift, ShiftImm},
+    args::{Argument, OffsetReg, Reg, Register, Shift, ShiftImm},
     ParsedIns,
 };
 
@@ -9,59 +9,235 @@ use crate::config::symbol::SymData;
 /// not be generated from C/C++. We need to detect them so the function boundary detector does not run into "illegal"
 /// instructions within these tables.
 ///
-/// This implementation is somewhat hardcoded, as it not always simple to calculate the size of the table. Instead, we're
-/// assuming the table size based on unusual (assembly-like) instruction patterns associated with the inline tables.
+/// A table is detected when a PC-relative address is used as the base of an indexed load. The table size is calculated from
+/// the range of the index, which is known from a bounds check (`cmp` followed by a conditional branch), a mask (`and`) or a
+/// right shift (`lsr`).
 #[derive(Clone, Copy, Default, Debug)]
-pub enum InlineTableState {
+pub struct InlineTableState {
+    /// Most recently bounded register, which may be used to index a table
+    bound: Option<IndexBound>,
+    /// Register and immediate of the previous instruction if it was a `cmp`, which bounds the register if a conditional
+    /// branch follows
+    compare: Option<(Register, u32)>,
+    phase: InlineTablePhase,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct IndexBound {
+    index: Register,
+    /// Number of possible index values
+    count: u32,
+    /// Factor which the index has been multiplied by, to turn it into a byte offset
+    scale: u32,
+}
+
+/// Max number of instructions between the table address calculation and the load from the table
+const MAX_TABLE_BASE_DISTANCE: u32 = 4;
+
+/// Max number of entries in a table, larger index ranges are not considered bounded
+const MAX_TABLE_ENTRIES: u32 = 0x100;
+
+#[derive(Clone, Copy, Default, Debug)]
+enum InlineTablePhase {
     #[default]
     Start,
-    SubPc {
-        table_base: Register,
-        table_address: u32,
-        size: u32,
-    },
+
+    /// `sub base, pc, #imm`            calculate table address, OR
+    /// `add base, pc, #imm`            same as above
+    TableBase { base: Register, base_address: u32, sub_pc: bool, remaining: u32 },
+
+    /// `ldrb value, [base, index]`     load from the table
     ValidTable(InlineTable),
 }
 
 impl InlineTableState {
     pub fn handle(self, thumb: bool, address: u32, parsed_ins: &ParsedIns) -> Self {
-        let args = &parsed_ins.args;
-        match self {
-            Self::Start => match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3]) {
-                (
-                    "sub",
-                    Argument::Reg(Reg { reg, .. }),
-                    Argument::Reg(Reg { reg: Register::Pc, .. }),
-                    Argument::UImm(offset),
-                    Argument::None,
-                ) => Self::SubPc {
-                    table_base: reg,
-                    table_address: 0x100 + address - offset + if thumb { 4 } else { 8 },
-                    size: 0x100,
-                },
-                _ => Self::default(),
-            },
-            Self::SubPc { table_base, table_address, size } => {
-                match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3], args[4]) {
-                    (
-                        "ldrb",
-                        Argument::Reg(Reg { .. }),
-                        Argument::Reg(Reg { deref: true, reg, .. }),
-                        Argument::OffsetReg(_),
-                        Argument::ShiftImm(ShiftImm { op: Shift::Lsr, .. }),
-                        Argument::None,
-                    ) if reg == table_base => {
-                        Self::ValidTable(InlineTable { address: table_address, size, kind: InlineTableKind::Byte })
-                    }
-                    _ => Self::default(),
+        let bound = self.next_bound(parsed_ins);
+        let compare = Self::check_compare(parsed_ins);
+        let phase = match self.phase {
+            InlineTablePhase::Start | InlineTablePhase::ValidTable(_) => {
+                Self::check_table_base(thumb, address, parsed_ins).unwrap_or_default()
+            }
+            InlineTablePhase::TableBase { base, base_address, sub_pc, remaining } => {
+                if let Some(table) = self.check_table_load(parsed_ins, base, base_address, sub_pc) {
+                    InlineTablePhase::ValidTable(table)
+                } else if let Some(phase) = Self::check_table_base(thumb, address, parsed_ins) {
+                    phase
+                } else if remaining == 0 || Self::writes_register(parsed_ins, base) {
+                    InlineTablePhase::Start
+                } else {
+                    InlineTablePhase::TableBase { base, base_address, sub_pc, remaining: remaining - 1 }
                 }
             }
-            Self::ValidTable(_) => Self::default(),
+        };
+        Self { bound, compare, phase }
+    }
+
+    fn check_table_base(thumb: bool, address: u32, parsed_ins: &ParsedIns) -> Option<InlineTablePhase> {
+        let args = &parsed_ins.args;
+        let (base, base_address, sub_pc) = match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3]) {
+            (
+                "sub",
+                Argument::Reg(Reg { reg, .. }),
+                Argument::Reg(Reg { reg: Register::Pc, .. }),
+                Argument::UImm(offset),
+                Argument::None,
+            ) => (reg, (address + if thumb { 4 } else { 8 }).checked_sub(offset)?, true),
+            (
+                "add",
+                Argument::Reg(Reg { reg, .. }),
+                Argument::Reg(Reg { reg: Register::Pc, .. }),
+                Argument::UImm(offset),
+                Argument::None,
+            ) => {
+                let pc = if thumb { (address + 4) & !3 } else { address + 8 };
+                (reg, pc.checked_add(offset)?, false)
+            }
+            _ => return None,
+        };
+        Some(InlineTablePhase::TableBase { base, base_address, sub_pc, remaining: MAX_TABLE_BASE_DISTANCE })
+    }
+
+    fn check_table_load(
+        &self,
+        parsed_ins: &ParsedIns,
+        base: Register,
+        base_address: u32,
+        sub_pc: bool,
+    ) -> Option<InlineTable> {
+        let args = &parsed_ins.args;
+        let (Argument::Reg(Reg { reg: a, deref: true, .. }), Argument::OffsetReg(OffsetReg { reg: b, .. })) =
+            (args[1], args[2])
+        else {
+            return None;
+        };
+        let index = if a == base {
+            b
+        } else if b == base {
+            a
+        } else {
+            return None;
+        };
+
+        let (kind, count) = match (parsed_ins.mnemonic, args[0], args[3], args[4]) {
+            (
+                "ldrb" | "ldrsb",
+                Argument::Reg(_),
+                Argument::ShiftImm(ShiftImm { op: Shift::Lsr, imm: shift @ 1..=31 }),
+                Argument::None,
+            ) => {
+                // The index range is limited by the shift. Tables reached through `sub` have been observed to start 0x100
+                // bytes past the calculated address.
+                let count = (1 << (32 - shift)).min(MAX_TABLE_ENTRIES);
+                let address = base_address.checked_add(if sub_pc { 0x100 } else { 0 })?;
+                return Some(InlineTable { address, size: count, kind: InlineTableKind::Byte });
+            }
+            ("ldrb" | "ldrsb", Argument::Reg(_), Argument::None, _) => (InlineTableKind::Byte, self.count(index, 1)?),
+            ("ldrh" | "ldrsh", Argument::Reg(_), Argument::None, _) => (InlineTableKind::Short, self.count(index, 2)?),
+            ("ldr", Argument::Reg(_), Argument::ShiftImm(ShiftImm { op: Shift::Lsl, imm: 2 }), Argument::None) => {
+                (InlineTableKind::Word, self.count(index, 1)?)
+            }
+            ("ldr", Argument::Reg(_), Argument::None, _) => (InlineTableKind::Word, self.count(index, 4)?),
+            _ => return None,
+        };
+        Some(InlineTable { address: base_address, size: count.checked_mul(kind.size())?, kind })
+    }
+
+    /// Returns the number of possible values of an index register, if it has been bounded and scaled by `scale`.
+    fn count(&self, index: Register, scale: u32) -> Option<u32> {
+        self.bound.filter(|bound| bound.index == index && bound.scale == scale).map(|bound| bound.count)
+    }
+
+    fn check_compare(parsed_ins: &ParsedIns) -> Option<(Register, u32)> {
+        match (parsed_ins.mnemonic, parsed_ins.args[0], parsed_ins.args[1], parsed_ins.args[2]) {
+            ("cmp", Argument::Reg(Reg { reg, .. }), Argument::UImm(limit), Argument::None) => Some((reg, limit)),
+            _ => None,
+        }
+    }
+
+    /// Creates a bound for `count` possible index values, if the count is small enough to be a table.
+    fn bound(index: Register, count: u32) -> Option<IndexBound> {
+        (1..=MAX_TABLE_ENTRIES).contains(&count).then_some(IndexBound { index, count, scale: 1 })
+    }
+
+    fn next_bound(&self, parsed_ins: &ParsedIns) -> Option<IndexBound> {
+        let args = &parsed_ins.args;
+        let scaled = |dest: Register, src: Register, factor: u32| {
+            self.bound.filter(|bound| bound.index == src).and_then(|bound| {
+                Some(IndexBound { index: dest, count: bound.count, scale: bound.scale.checked_mul(factor)? })
+            })
+        };
+        match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3]) {
+            // `cmp index, #limit` guarded by a branch when `index > limit` or `index >= limit`
+            ("bhi" | "bls", Argument::BranchDest(_), Argument::None, _, _) if self.compare.is_some() => {
+                let (index, limit) = self.compare.unwrap();
+                Self::bound(index, limit.checked_add(1)?)
+            }
+            ("bcs" | "bhs" | "bcc" | "blo", Argument::BranchDest(_), Argument::None, _, _) if self.compare.is_some() => {
+                let (index, limit) = self.compare.unwrap();
+                Self::bound(index, limit)
+            }
+            (
+                "and" | "ands",
+                Argument::Reg(Reg { reg: index, .. }),
+                Argument::Reg(_),
+                Argument::UImm(mask),
+                Argument::None,
+            ) if mask.wrapping_add(1).is_power_of_two() => Self::bound(index, mask + 1),
+            (
+                "mov" | "movs",
+                Argument::Reg(Reg { reg: index, .. }),
+                Argument::Reg(_),
+                Argument::ShiftImm(ShiftImm { op: Shift::Lsr, imm: shift @ 1..=31 }),
+                Argument::None,
+            )
+            | (
+                "lsr" | "lsrs",
+                Argument::Reg(Reg { reg: index, .. }),
+                Argument::Reg(_),
+                Argument::UImm(shift @ 1..=31),
+                Argument::None,
+            ) => Self::bound(index, 1 << (32 - shift)),
+            (
+                "mov" | "movs",
+                Argument::Reg(Reg { reg: dest, .. }),
+                Argument::Reg(Reg { reg: src, .. }),
+                Argument::ShiftImm(ShiftImm { op: Shift::Lsl, imm: shift @ 1..=2 }),
+                Argument::None,
+            )
+            | (
+                "lsl" | "lsls",
+                Argument::Reg(Reg { reg: dest, .. }),
+                Argument::Reg(Reg { reg: src, .. }),
+                Argument::UImm(shift @ 1..=2),
+                Argument::None,
+            ) if self.count(src, 1).is_some() => scaled(dest, src, 1 << shift),
+            (
+                "add" | "adds",
+                Argument::Reg(Reg { reg: dest, .. }),
+                Argument::Reg(Reg { reg: a, .. }),
+                Argument::Reg(Reg { reg: b, .. }),
+                Argument::None,
+            ) if a == b => scaled(dest, a, 2).or_else(|| self.next_bound_if_preserved(parsed_ins)),
+            _ => self.next_bound_if_preserved(parsed_ins),
+        }
+    }
+
+    /// Keeps the current bound unless the instruction overwrites the bounded register.
+    fn next_bound_if_preserved(&self, parsed_ins: &ParsedIns) -> Option<IndexBound> {
+        self.bound.filter(|bound| !Self::writes_register(parsed_ins, bound.index))
+    }
+
+    fn writes_register(parsed_ins: &ParsedIns, register: Register) -> bool {
+        let mnemonic = parsed_ins.mnemonic;
+        if mnemonic == "bx" || ["cmp", "cmn", "tst", "teq", "str", "stm"].iter().any(|prefix| mnemonic.starts_with(prefix)) {
+            return false;
         }
+        matches!(parsed_ins.args[0], Argument::Reg(Reg { reg, deref: false, .. }) if reg == register)
     }
 
     pub fn get_table(self) -> Option<InlineTable> {
-        let Self::ValidTable(table) = self else { return None };
+        let InlineTablePhase::ValidTable(table) = self.phase else { return None };
         Some(table)
     }
 }
@@ -83,6 +259,8 @@ impl From<InlineTable> for SymData {
     fn from(val: InlineTable) -> Self {
         match val.kind {
             InlineTableKind::Byte => SymData::Byte { count: Some(val.count()) },
+            InlineTableKind::Short => SymData::Short { count: Some(val.count()) },
+            InlineTableKind::Word => SymData::Word { count: Some(val.count()) },
         }
     }
 }
@@ -90,12 +268,16 @@ impl From<InlineTable> for SymData {
 #[derive(Clone, Copy, Debug)]
 pub enum InlineTableKind {
     Byte,
+    Short,
+    Word,
 }
 
 impl InlineTableKind {
     pub fn size(self) -> u32 {
         match self {
             Self::Byte => 1,
+            Self::Short => 2,
+            Self::Word => 4,
         }
     }
 }
@@ -0,0 +1,300 @@
+/// Hardware I/O registers and memory regions of the ARM9, used to name constant addresses.
+#[derive(Clone, Copy, Debug)]
+pub struct IoRegister {
+    pub name: &'static str,
+    pub address: u32,
+    pub size: u32,
+    pub kind: IoRegisterKind,
+    /// Only exists on the DSi
+    pub dsi: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IoRegisterKind {
+    Register,
+    Memory,
+}
+
+impl IoRegister {
+    const fn new(name: &'static str, address: u32, size: u32, kind: IoRegisterKind) -> Self {
+        Self { name, address, size, kind, dsi: false }
+    }
+
+    const fn dsi(name: &'static str, address: u32, size: u32, kind: IoRegisterKind) -> Self {
+        Self { name, address, size, kind, dsi: true }
+    }
+
+    /// Returns the register or memory region which starts at the given address.
+    pub fn by_address(address: u32) -> Option<&'static Self> {
+        IO_REGISTERS.binary_search_by_key(&address, |r| r.address).ok().map(|index| &IO_REGISTERS[index])
+    }
+
+    pub fn iter() -> impl Iterator<Item = &'static Self> {
+        IO_REGISTERS.iter()
+    }
+}
+
+/// Sorted by address
+const IO_REGISTERS: &[IoRegister] = &[
+    IoRegister::new("HW_ITCM", 0x01ff8000, 0x8000, IoRegisterKind::Memory),
+    IoRegister::new("HW_MAIN_MEM", 0x02000000, 0x400000, IoRegisterKind::Memory),
+    IoRegister::new("HW_SHARED_WRAM", 0x03000000, 0x8000, IoRegisterKind::Memory),
+    IoRegister::new("REG_DISPCNT", 0x04000000, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DISPSTAT", 0x04000004, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_VCOUNT", 0x04000006, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG0CNT", 0x04000008, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG1CNT", 0x0400000a, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2CNT", 0x0400000c, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3CNT", 0x0400000e, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG0HOFS", 0x04000010, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG0VOFS", 0x04000012, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG1HOFS", 0x04000014, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG1VOFS", 0x04000016, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2HOFS", 0x04000018, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2VOFS", 0x0400001a, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3HOFS", 0x0400001c, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3VOFS", 0x0400001e, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2PA", 0x04000020, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2PB", 0x04000022, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2PC", 0x04000024, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2PD", 0x04000026, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2X", 0x04000028, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2Y", 0x0400002c, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3PA", 0x04000030, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3PB", 0x04000032, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3PC", 0x04000034, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3PD", 0x04000036, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3X", 0x04000038, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3Y", 0x0400003c, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_WIN0H", 0x04000040, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_WIN1H", 0x04000042, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_WIN0V", 0x04000044, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_WIN1V", 0x04000046, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_WININ", 0x04000048, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_WINOUT", 0x0400004a, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_MOSAIC", 0x0400004c, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BLDCNT", 0x04000050, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BLDALPHA", 0x04000052, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BLDY", 0x04000054, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_DISP3DCNT", 0x04000060, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_DISPCAPCNT", 0x04000064, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DISP_MMEM_FIFO", 0x04000068, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MASTER_BRIGHT", 0x0400006c, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA0SAD", 0x040000b0, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA0DAD", 0x040000b4, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA0CNT", 0x040000b8, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA1SAD", 0x040000bc, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA1DAD", 0x040000c0, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA1CNT", 0x040000c4, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA2SAD", 0x040000c8, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA2DAD", 0x040000cc, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA2CNT", 0x040000d0, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA3SAD", 0x040000d4, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA3DAD", 0x040000d8, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA3CNT", 0x040000dc, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA0FILL", 0x040000e0, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA1FILL", 0x040000e4, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA2FILL", 0x040000e8, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DMA3FILL", 0x040000ec, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_TM0CNT_L", 0x04000100, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_TM0CNT_H", 0x04000102, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_TM1CNT_L", 0x04000104, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_TM1CNT_H", 0x04000106, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_TM2CNT_L", 0x04000108, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_TM2CNT_H", 0x0400010a, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_TM3CNT_L", 0x0400010c, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_TM3CNT_H", 0x0400010e, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_KEYINPUT", 0x04000130, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_KEYCNT", 0x04000132, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_IPCSYNC", 0x04000180, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_IPCFIFOCNT", 0x04000184, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_IPCFIFOSEND", 0x04000188, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_AUXSPICNT", 0x040001a0, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_AUXSPIDATA", 0x040001a2, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_ROMCTRL", 0x040001a4, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_CARDCMD", 0x040001a8, 0x8, IoRegisterKind::Register),
+    IoRegister::new("REG_EXMEMCNT", 0x04000204, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_IME", 0x04000208, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_IE", 0x04000210, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_IF", 0x04000214, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_VRAMCNT_A", 0x04000240, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_VRAMCNT_B", 0x04000241, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_VRAMCNT_C", 0x04000242, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_VRAMCNT_D", 0x04000243, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_VRAMCNT_E", 0x04000244, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_VRAMCNT_F", 0x04000245, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_VRAMCNT_G", 0x04000246, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_WRAMCNT", 0x04000247, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_VRAMCNT_H", 0x04000248, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_VRAMCNT_I", 0x04000249, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_DIVCNT", 0x04000280, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_DIV_NUMER", 0x04000290, 0x8, IoRegisterKind::Register),
+    IoRegister::new("REG_DIV_DENOM", 0x04000298, 0x8, IoRegisterKind::Register),
+    IoRegister::new("REG_DIV_RESULT", 0x040002a0, 0x8, IoRegisterKind::Register),
+    IoRegister::new("REG_DIVREM_RESULT", 0x040002a8, 0x8, IoRegisterKind::Register),
+    IoRegister::new("REG_SQRTCNT", 0x040002b0, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_SQRT_RESULT", 0x040002b4, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_SQRT_PARAM", 0x040002b8, 0x8, IoRegisterKind::Register),
+    IoRegister::new("REG_POSTFLG", 0x04000300, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_POWCNT1", 0x04000304, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_RDLINES_COUNT", 0x04000320, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_EDGE_COLOR", 0x04000330, 0x10, IoRegisterKind::Register),
+    IoRegister::new("REG_ALPHA_TEST_REF", 0x04000340, 0x1, IoRegisterKind::Register),
+    IoRegister::new("REG_CLEAR_COLOR", 0x04000350, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_CLEAR_DEPTH", 0x04000354, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_CLRIMAGE_OFFSET", 0x04000356, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_FOG_COLOR", 0x04000358, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_FOG_OFFSET", 0x0400035c, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_FOG_TABLE", 0x04000360, 0x20, IoRegisterKind::Register),
+    IoRegister::new("REG_TOON_TABLE", 0x04000380, 0x40, IoRegisterKind::Register),
+    IoRegister::new("REG_GXFIFO", 0x04000400, 0x40, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_MODE", 0x04000440, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_PUSH", 0x04000444, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_POP", 0x04000448, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_STORE", 0x0400044c, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_RESTORE", 0x04000450, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_IDENTITY", 0x04000454, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_LOAD_4x4", 0x04000458, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_LOAD_4x3", 0x0400045c, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_MULT_4x4", 0x04000460, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_MULT_4x3", 0x04000464, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_MULT_3x3", 0x04000468, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_SCALE", 0x0400046c, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_MTX_TRANS", 0x04000470, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_COLOR", 0x04000480, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_NORMAL", 0x04000484, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_TEXCOORD", 0x04000488, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_VTX_16", 0x0400048c, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_VTX_10", 0x04000490, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_VTX_XY", 0x04000494, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_VTX_XZ", 0x04000498, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_VTX_YZ", 0x0400049c, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_VTX_DIFF", 0x040004a0, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_POLYGON_ATTR", 0x040004a4, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_TEXIMAGE_PARAM", 0x040004a8, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_PLTT_BASE", 0x040004ac, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DIF_AMB", 0x040004c0, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_SPE_EMI", 0x040004c4, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_LIGHT_VECTOR", 0x040004c8, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_LIGHT_COLOR", 0x040004cc, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_SHININESS", 0x040004d0, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_BEGIN_VTXS", 0x04000500, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_END_VTXS", 0x04000504, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_SWAP_BUFFERS", 0x04000540, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_VIEWPORT", 0x04000580, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_BOX_TEST", 0x040005c0, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_POS_TEST", 0x040005c4, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_VEC_TEST", 0x040005c8, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_GXSTAT", 0x04000600, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_RAM_COUNT", 0x04000604, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_DISP_1DOT_DEPTH", 0x04000610, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_POS_RESULT", 0x04000620, 0x10, IoRegisterKind::Register),
+    IoRegister::new("REG_VEC_RESULT", 0x04000630, 0x6, IoRegisterKind::Register),
+    IoRegister::new("REG_CLIPMTX_RESULT", 0x04000640, 0x40, IoRegisterKind::Register),
+    IoRegister::new("REG_VECMTX_RESULT", 0x04000680, 0x24, IoRegisterKind::Register),
+    IoRegister::new("REG_DISPCNT_SUB", 0x04001000, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_BG0CNT_SUB", 0x04001008, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG1CNT_SUB", 0x0400100a, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2CNT_SUB", 0x0400100c, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3CNT_SUB", 0x0400100e, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG0HOFS_SUB", 0x04001010, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG0VOFS_SUB", 0x04001012, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG1HOFS_SUB", 0x04001014, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG1VOFS_SUB", 0x04001016, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2HOFS_SUB", 0x04001018, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2VOFS_SUB", 0x0400101a, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3HOFS_SUB", 0x0400101c, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3VOFS_SUB", 0x0400101e, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2PA_SUB", 0x04001020, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2PB_SUB", 0x04001022, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2PC_SUB", 0x04001024, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2PD_SUB", 0x04001026, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2X_SUB", 0x04001028, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_BG2Y_SUB", 0x0400102c, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3PA_SUB", 0x04001030, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3PB_SUB", 0x04001032, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3PC_SUB", 0x04001034, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3PD_SUB", 0x04001036, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3X_SUB", 0x04001038, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_BG3Y_SUB", 0x0400103c, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_WIN0H_SUB", 0x04001040, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_WIN1H_SUB", 0x04001042, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_WIN0V_SUB", 0x04001044, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_WIN1V_SUB", 0x04001046, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_WININ_SUB", 0x04001048, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_WINOUT_SUB", 0x0400104a, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_MOSAIC_SUB", 0x0400104c, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BLDCNT_SUB", 0x04001050, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BLDALPHA_SUB", 0x04001052, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_BLDY_SUB", 0x04001054, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_MASTER_BRIGHT_SUB", 0x0400106c, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_SCFG_A9ROM", 0x04004000, 0x1, IoRegisterKind::Register),
+    IoRegister::dsi("REG_SCFG_CLK", 0x04004004, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_SCFG_RST", 0x04004006, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_SCFG_EXT", 0x04004008, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_SCFG_MC", 0x04004010, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMAGCNT", 0x04004100, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA0SAD", 0x04004104, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA0DAD", 0x04004108, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA0TCNT", 0x0400410c, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA0WCNT", 0x04004110, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA0BCNT", 0x04004114, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA0FDATA", 0x04004118, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA0CNT", 0x0400411c, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA1SAD", 0x04004120, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA1DAD", 0x04004124, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA1TCNT", 0x04004128, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA1WCNT", 0x0400412c, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA1BCNT", 0x04004130, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA1FDATA", 0x04004134, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA1CNT", 0x04004138, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA2SAD", 0x0400413c, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA2DAD", 0x04004140, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA2TCNT", 0x04004144, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA2WCNT", 0x04004148, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA2BCNT", 0x0400414c, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA2FDATA", 0x04004150, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA2CNT", 0x04004154, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA3SAD", 0x04004158, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA3DAD", 0x0400415c, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA3TCNT", 0x04004160, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA3WCNT", 0x04004164, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA3BCNT", 0x04004168, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA3FDATA", 0x0400416c, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_NDMA3CNT", 0x04004170, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_CAM_MCNT", 0x04004200, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_CAM_CNT", 0x04004202, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_CAM_DATA", 0x04004204, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_CAM_SOFS", 0x04004210, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_CAM_EOFS", 0x04004214, 0x4, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_PDATA", 0x04004300, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_PADR", 0x04004304, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_PCFG", 0x04004308, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_PSTS", 0x0400430c, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_PSEM", 0x04004310, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_PMASK", 0x04004314, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_PCLEAR", 0x04004318, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_SEM", 0x0400431c, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_CMD0", 0x04004320, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_REP0", 0x04004324, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_CMD1", 0x04004328, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_REP1", 0x0400432c, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_CMD2", 0x04004330, 0x2, IoRegisterKind::Register),
+    IoRegister::dsi("REG_DSP_REP2", 0x04004334, 0x2, IoRegisterKind::Register),
+    IoRegister::new("REG_IPCFIFORECV", 0x04100000, 0x4, IoRegisterKind::Register),
+    IoRegister::new("REG_CARD_DATA_RD", 0x04100010, 0x4, IoRegisterKind::Register),
+    IoRegister::new("HW_BG_PLTT", 0x05000000, 0x200, IoRegisterKind::Memory),
+    IoRegister::new("HW_OBJ_PLTT", 0x05000200, 0x200, IoRegisterKind::Memory),
+    IoRegister::new("HW_BG_PLTT_SUB", 0x05000400, 0x200, IoRegisterKind::Memory),
+    IoRegister::new("HW_OBJ_PLTT_SUB", 0x05000600, 0x200, IoRegisterKind::Memory),
+    IoRegister::new("HW_BG_VRAM", 0x06000000, 0x80000, IoRegisterKind::Memory),
+    IoRegister::new("HW_BG_VRAM_SUB", 0x06200000, 0x20000, IoRegisterKind::Memory),
+    IoRegister::new("HW_OBJ_VRAM", 0x06400000, 0x40000, IoRegisterKind::Memory),
+    IoRegister::new("HW_OBJ_VRAM_SUB", 0x06600000, 0x20000, IoRegisterKind::Memory),
+    IoRegister::new("HW_LCDC_VRAM", 0x06800000, 0xa4000, IoRegisterKind::Memory),
+    IoRegister::new("HW_OAM", 0x07000000, 0x400, IoRegisterKind::Memory),
+    IoRegister::new("HW_OAM_SUB", 0x07000400, 0x400, IoRegisterKind::Memory),
+    IoRegister::new("HW_GBA_ROM", 0x08000000, 0x2000000, IoRegisterKind::Memory),
+    IoRegister::new("HW_GBA_RAM", 0x0a000000, 0x10000, IoRegisterKind::Memory),
+    IoRegister::new("HW_BIOS", 0xffff0000, 0x8000, IoRegisterKind::Memory),
+];
@@ -1,8 +1,10 @@
 use unarm::{
-    args::{Argument, OffsetImm, Reg, Register, Shift, ShiftImm},
+    args::{Argument, OffsetImm, OffsetReg, Reg, Register, Shift, ShiftImm},
     Ins, ParsedIns,
 };
 
+use crate::config::symbol::SymData;
+
 use super::functions::JumpTables;
 
 #[derive(Debug, Clone)]
@@ -13,6 +15,34 @@ pub struct JumpTable {
     pub code: bool,
 }
 
+/// A table of absolute addresses to jump to, as in `ldr pc, [table, index, lsl #0x2]`.
+#[derive(Debug, Clone, Copy)]
+pub struct PointerTable {
+    pub address: u32,
+    pub size: u32,
+    /// If true, the table is located within the function. Otherwise, it is in a data section.
+    pub inline: bool,
+}
+
+impl PointerTable {
+    pub fn count(&self) -> u32 {
+        self.size / 4
+    }
+}
+
+impl From<PointerTable> for SymData {
+    fn from(val: PointerTable) -> Self {
+        SymData::Word { count: Some(val.count()) }
+    }
+}
+
+/// A jump through a [`PointerTable`] whose address is in `base`, or follows the jump instruction if `base` is PC.
+#[derive(Clone, Copy, Debug)]
+pub struct PointerTableJump {
+    pub base: Register,
+    pub limit: u32,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum JumpTableState {
     Arm(JumpTableStateArm),
@@ -47,6 +77,23 @@ impl JumpTableState {
             Self::Thumb(state) => state.is_numerical_jump_offset(),
         }
     }
+
+    /// Returns the pointer table jump which was just detected, if any.
+    pub fn get_pointer_table_jump(&self) -> Option<PointerTableJump> {
+        match self {
+            Self::Arm(state) => state.get_pointer_table_jump(),
+            Self::Thumb(state) => state.get_pointer_table_jump(),
+        }
+    }
+}
+
+fn is_indirect_jump(parsed_ins: &ParsedIns, jump: Register) -> bool {
+    let args = &parsed_ins.args;
+    match (parsed_ins.mnemonic, args[0], args[1], args[2]) {
+        ("bx", Argument::Reg(Reg { reg, .. }), Argument::None, _) => reg == jump,
+        ("mov", Argument::Reg(Reg { reg: Register::Pc, .. }), Argument::Reg(Reg { reg, .. }), Argument::None) => reg == jump,
+        _ => false,
+    }
 }
 
 #[derive(Clone, Copy, Default, Debug)]
@@ -70,9 +117,43 @@ pub enum JumpTableStateArm {
 
     /// valid table detected, starts from `table_address` with a size of `limit`
     ValidJumpTable { table_address: u32, limit: u32 },
+
+    /// if a pointer was loaded with `ldr jump, [base, index, lsl #0x2]`:
+    /// `bx jump`                       jump to the loaded pointer
+    BranchPointer { jump: Register, base: Register, limit: u32 },
+
+    /// valid pointer table jump detected, the table address is in `base` or follows the jump if `base` is PC
+    ValidPointerTable(PointerTableJump),
 }
 
 impl JumpTableStateArm {
+    /// `ldrls pc, [base, index, lsl #0x2]`     jump to a pointer in a table, OR
+    /// `ldrls jump, [base, index, lsl #0x2]`   load a pointer from a table
+    fn check_pointer_table_load(ins: Ins, parsed_ins: &ParsedIns, index: Register, limit: u32) -> Option<Self> {
+        if ins.mnemonic() != "ldr" {
+            return None;
+        }
+        let args = &parsed_ins.args;
+        match (args[0], args[1], args[2], args[3], args[4]) {
+            (
+                Argument::Reg(Reg { reg: dest, .. }),
+                Argument::Reg(Reg { reg: base, deref: true, .. }),
+                Argument::OffsetReg(OffsetReg { reg, .. }),
+                Argument::ShiftImm(ShiftImm { imm: 2, op: Shift::Lsl }),
+                Argument::None,
+            ) if reg == index => {
+                if dest == Register::Pc {
+                    Some(Self::ValidPointerTable(PointerTableJump { base, limit }))
+                } else if base != Register::Pc {
+                    Some(Self::BranchPointer { jump: dest, base, limit })
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
     fn check_start(self, parsed_ins: &ParsedIns) -> Option<Self> {
         let args = &parsed_ins.args;
         match (parsed_ins.mnemonic, args[0], args[1], args[2]) {
@@ -114,8 +195,15 @@ impl JumpTableStateArm {
                     ("bgt", Argument::BranchDest(_), Argument::None, Argument::None, Argument::None, Argument::None) => {
                         Self::SignedBaseline { index, limit }
                     }
-                    _ if ins.updates_condition_flags() => Self::default(),
-                    _ => self,
+                    _ => {
+                        if let Some(state) = Self::check_pointer_table_load(ins, parsed_ins, index, limit) {
+                            state
+                        } else if ins.updates_condition_flags() {
+                            Self::default()
+                        } else {
+                            self
+                        }
+                    }
                 }
             }
             Self::SignedBaseline { index, limit } => match (parsed_ins.mnemonic, args[0], args[1], args[2]) {
@@ -138,8 +226,15 @@ impl JumpTableStateArm {
                     jump_tables.insert(table_address, JumpTable { address: table_address, size, code: true });
                     Self::ValidJumpTable { table_address: address + 8, limit }
                 }
-                _ if ins.updates_condition_flags() => Self::default(),
-                _ => self,
+                _ => {
+                    if let Some(state) = Self::check_pointer_table_load(ins, parsed_ins, index, limit) {
+                        state
+                    } else if ins.updates_condition_flags() {
+                        Self::default()
+                    } else {
+                        self
+                    }
+                }
             },
             Self::ValidJumpTable { table_address, limit } => {
                 let end = table_address + limit * 4;
@@ -149,6 +244,14 @@ impl JumpTableStateArm {
                     self
                 }
             }
+            Self::BranchPointer { jump, base, limit } => {
+                if is_indirect_jump(parsed_ins, jump) {
+                    Self::ValidPointerTable(PointerTableJump { base, limit })
+                } else {
+                    Self::default()
+                }
+            }
+            Self::ValidPointerTable(_) => Self::default(),
         }
     }
 
@@ -158,6 +261,13 @@ impl JumpTableStateArm {
             _ => None,
         }
     }
+
+    pub fn get_pointer_table_jump(&self) -> Option<PointerTableJump> {
+        match self {
+            Self::ValidPointerTable(jump) => Some(*jump),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Default, Debug)]
@@ -187,9 +297,21 @@ pub enum JumpTableStateThumb {
     /// `bmi @skip`                     same as above but for subtraction
     BranchNegative { index: Register, limit: u32 },
 
-    /// `add offset, index, index`      multiply index by 2 to calculate jump table offset
+    /// `add offset, index, index`      multiply index by 2 to calculate jump table offset, OR
+    /// `lsl offset, index, #0x2`       multiply index by 4 to calculate pointer table offset
     AddRegReg { index: Register, limit: u32 },
 
+    /// if [`JumpTableStateThumb::AddRegReg`] was lsl:
+    /// `ldr base, [pc, #imm]`          load pointer table address from constant pool, then
+    /// `ldr jump, [base, offset]`      load pointer from table
+    LoadPointer { offset: Register, limit: u32 },
+
+    /// `bx jump`                       jump to the loaded pointer
+    BranchPointer { jump: Register, base: Register, limit: u32 },
+
+    /// valid pointer table jump detected, the table address is in `base`
+    ValidPointerTable(PointerTableJump),
+
     /// `add offset, pc`                turn jump table offset into a PC-relative address
     AddRegPc { offset: Register, limit: u32 },
 
@@ -272,8 +394,43 @@ impl JumpTableStateThumb {
                         Self::default()
                     }
                 }
+                (
+                    "lsl",
+                    Argument::Reg(Reg { reg: table_offset, .. }),
+                    Argument::Reg(Reg { reg, .. }),
+                    Argument::UImm(2),
+                    Argument::None,
+                ) if reg == index => Self::LoadPointer { offset: table_offset, limit },
+                _ => Self::default(),
+            },
+            Self::LoadPointer { offset, limit } => match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3]) {
+                (
+                    "ldr",
+                    Argument::Reg(Reg { reg, .. }),
+                    Argument::Reg(Reg { reg: Register::Pc, deref: true, .. }),
+                    Argument::OffsetImm(_),
+                    Argument::None,
+                ) if reg != offset => self,
+                (
+                    "ldr",
+                    Argument::Reg(Reg { reg: jump, .. }),
+                    Argument::Reg(Reg { reg: a, deref: true, .. }),
+                    Argument::OffsetReg(OffsetReg { reg: b, .. }),
+                    Argument::None,
+                ) if a == offset || b == offset => {
+                    let base = if a == offset { b } else { a };
+                    Self::BranchPointer { jump, base, limit }
+                }
                 _ => Self::default(),
             },
+            Self::BranchPointer { jump, base, limit } => {
+                if is_indirect_jump(parsed_ins, jump) {
+                    Self::ValidPointerTable(PointerTableJump { base, limit })
+                } else {
+                    Self::default()
+                }
+            }
+            Self::ValidPointerTable(_) => Self::default(),
             Self::AddRegPc { offset, limit } => match (parsed_ins.mnemonic, args[0], args[1], args[2]) {
                 ("add", Argument::Reg(Reg { reg, .. }), Argument::Reg(Reg { reg: Register::Pc, .. }), Argument::None) => {
                     if reg == offset {
@@ -370,4 +527,11 @@ impl JumpTableStateThumb {
     pub fn is_numerical_jump_offset(&self) -> bool {
         matches!(self, JumpTableStateThumb::ValidJumpTable { .. })
     }
+
+    pub fn get_pointer_table_jump(&self) -> Option<PointerTableJump> {
+        match self {
+            Self::ValidPointerTable(jump) => Some(*jump),
+            _ => None,
+        }
+    }
 }
@@ -1,10 +1,13 @@
 pub(crate) mod ctor;
-pub(crate) mod data;
+pub mod data;
 mod function_branch;
 mod function_start;
 pub mod functions;
 mod illegal_code;
 mod inline_table;
+pub mod io_registers;
 pub(crate) mod jump_table;
 pub(crate) mod main;
+pub mod overlay_groups;
 pub mod secure_area;
+pub mod veneer;
@@ -1,5 +1,7 @@
-use anyhow::{bail, Result};
+use std::backtrace::Backtrace;
+
 use ds_rom::rom::Overlay;
+use snafu::Snafu;
 
 pub struct OverlayGroups {
     groups: Vec<OverlayGroup>,
@@ -15,8 +17,14 @@ pub struct OverlayGroup {
     pub after: Vec<OverlayIndex>,
 }
 
+#[derive(Debug, Snafu)]
+pub enum OverlayGroupsError {
+    #[snafu(display("no more overlay groups to connect to, are there gaps between overlays?\n{backtrace}"))]
+    NoGroupToConnect { backtrace: Backtrace },
+}
+
 impl OverlayGroups {
-    pub fn analyze(main_end_address: u32, overlays: &[Overlay]) -> Result<OverlayGroups> {
+    pub fn analyze(main_end_address: u32, overlays: &[Overlay]) -> Result<OverlayGroups, OverlayGroupsError> {
         let (first_group, first_group_end, mut ungrouped_overlays) =
             overlays.iter().fold((vec![], 0, vec![]), |(mut first_group, mut first_group_end, mut rest), overlay| {
                 if overlay.base_address() == main_end_address {
@@ -40,7 +48,7 @@ impl OverlayGroups {
         let mut groups_to_connect = vec![0u16]; // list of groups (indices) which may be preceded by ungrouped overlays
         while !ungrouped_overlays.is_empty() {
             let Some(connect_index) = groups_to_connect.pop() else {
-                bail!("No more overlay groups to connect to, are there gaps between overlays?");
+                return NoGroupToConnectSnafu.fail();
             };
             let connect_index = connect_index as usize;
 
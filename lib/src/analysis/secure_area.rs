@@ -17,18 +17,12 @@ pub enum SecureAreaState {
 }
 
 impl SecureAreaState {
-    pub fn handle(self, address: u32, parsed_ins: &ParsedIns) -> Self {
+    pub fn handle(self, address: u32, thumb: bool, parsed_ins: &ParsedIns) -> Self {
         let args = &parsed_ins.args;
         match self {
-            Self::Swi => match (parsed_ins.mnemonic, args[0], args[1]) {
-                ("swi", Argument::UImm(interrupt), Argument::None) | ("svc", Argument::UImm(interrupt), Argument::None) => {
-                    if let Ok(function) = interrupt.try_into() {
-                        Self::Return { start: address, function, return_reg: Register::R0 }
-                    } else {
-                        Self::default()
-                    }
-                }
-                _ => Self::default(),
+            Self::Swi => match SwiFunction::from_ins(parsed_ins, thumb) {
+                Some(function) => Self::Return { start: address, function, return_reg: Register::R0 },
+                None => Self::default(),
             },
             Self::Return { start, function, return_reg } => match (parsed_ins.mnemonic, args[0], args[1], args[2]) {
                 ("mov", Argument::Reg(Reg { reg: dest, .. }), Argument::Reg(Reg { reg: src, .. }), Argument::None)
@@ -37,7 +31,8 @@ impl SecureAreaState {
                     Self::Return { start, function, return_reg: src }
                 }
                 ("bx", Argument::Reg(Reg { reg: Register::Lr, .. }), Argument::None, Argument::None) => {
-                    Self::ValidFunction(SecureAreaFunction { function, return_reg, start, end: address + 2 })
+                    let end = address + if thumb { 2 } else { 4 };
+                    Self::ValidFunction(SecureAreaFunction { function, return_reg, start, end, thumb })
                 }
                 _ => Self::default(),
             },
@@ -51,6 +46,7 @@ impl SecureAreaState {
     }
 }
 
+/// BIOS functions of the ARM9, ARM7 and DSi, called with `swi`.
 #[derive(Clone, Copy, Debug)]
 pub enum SwiFunction {
     SoftReset,
@@ -58,6 +54,10 @@ pub enum SwiFunction {
     IntrWait,
     VBlankIntrWait,
     Halt,
+    /// ARM7 only
+    Sleep,
+    /// ARM7 only
+    SoundBias,
     Div,
     Mod,
     CpuSet,
@@ -71,6 +71,40 @@ pub enum SwiFunction {
     HuffUnCompReadByCallback,
     RLUnCompReadNormalWrite8bit,
     RLUnCompReadByCallbackWrite16bit,
+    /// ARM9 only
+    Diff8bitUnFilterWrite8bit,
+    /// ARM9 only
+    Diff16bitUnFilter,
+    /// ARM7 only
+    GetSineTable,
+    /// ARM7 only
+    GetPitchTable,
+    /// ARM7 only
+    GetVolumeTable,
+    /// ARM7 only
+    GetBootProcs,
+    /// ARM7 only
+    CustomPost,
+    /// DSi only
+    RsaInitCryptoHeap,
+    /// DSi only
+    RsaDecrypt,
+    /// DSi only
+    RsaDecryptUnpad,
+    /// DSi only
+    RsaDecryptUnpadGetSha1,
+    /// DSi only
+    Sha1Init,
+    /// DSi only
+    Sha1Update,
+    /// DSi only
+    Sha1Finish,
+    /// DSi only
+    Sha1InitUpdateFinish,
+    /// DSi only
+    Sha1Compare,
+    /// DSi only
+    Sha1Random,
 }
 
 impl SwiFunction {
@@ -81,6 +115,8 @@ impl SwiFunction {
             Self::IntrWait => 0x4,
             Self::VBlankIntrWait => 0x5,
             Self::Halt => 0x6,
+            Self::Sleep => 0x7,
+            Self::SoundBias => 0x8,
             Self::Div | Self::Mod => 0x9,
             Self::CpuSet => 0xb,
             Self::CpuFastSet => 0xc,
@@ -93,30 +129,85 @@ impl SwiFunction {
             Self::HuffUnCompReadByCallback => 0x13,
             Self::RLUnCompReadNormalWrite8bit => 0x14,
             Self::RLUnCompReadByCallbackWrite16bit => 0x15,
+            Self::Diff8bitUnFilterWrite8bit => 0x16,
+            Self::Diff16bitUnFilter => 0x18,
+            Self::GetSineTable => 0x1a,
+            Self::GetPitchTable => 0x1b,
+            Self::GetVolumeTable => 0x1c,
+            Self::GetBootProcs => 0x1d,
+            Self::CustomPost => 0x1f,
+            Self::RsaInitCryptoHeap => 0x20,
+            Self::RsaDecrypt => 0x21,
+            Self::RsaDecryptUnpad => 0x22,
+            Self::RsaDecryptUnpadGetSha1 => 0x23,
+            Self::Sha1Init => 0x24,
+            Self::Sha1Update => 0x25,
+            Self::Sha1Finish => 0x26,
+            Self::Sha1InitUpdateFinish => 0x27,
+            Self::Sha1Compare => 0x28,
+            Self::Sha1Random => 0x29,
         }
     }
 
     pub fn name(self, return_reg: Register) -> &'static str {
         match (self, return_reg) {
-            (Self::SoftReset, _) => "SoftReset",
-            (Self::WaitByLoop, _) => "WaitByLoop",
-            (Self::IntrWait, _) => "IntrWait",
-            (Self::VBlankIntrWait, _) => "VBlankIntrWait",
-            (Self::Halt, _) => "Halt",
             (Self::Div, Register::R1) => "Mod",
-            (Self::Div, _) => "Div",
-            (Self::Mod, _) => "Mod",
-            (Self::CpuSet, _) => "CpuSet",
-            (Self::CpuFastSet, _) => "CpuFastSet",
-            (Self::Sqrt, _) => "Sqrt",
-            (Self::GetCRC16, _) => "GetCRC16",
-            (Self::IsDebugger, _) => "IsDebugger",
-            (Self::BitUnPack, _) => "BitUnPack",
-            (Self::LZ77UnCompReadNormalWrite8bit, _) => "LZ77UnCompReadNormalWrite8bit",
-            (Self::LZ77UnCompReadByCallbackWrite16bit, _) => "LZ77UnCompReadByCallbackWrite16bit",
-            (Self::HuffUnCompReadByCallback, _) => "HuffUnCompReadByCallback",
-            (Self::RLUnCompReadNormalWrite8bit, _) => "RLUnCompReadNormalWrite8bit",
-            (Self::RLUnCompReadByCallbackWrite16bit, _) => "RLUnCompReadByCallbackWrite16bit",
+            _ => self.interrupt_name(),
+        }
+    }
+
+    /// Name of the BIOS function, regardless of which result is returned.
+    pub fn interrupt_name(self) -> &'static str {
+        match self {
+            Self::SoftReset => "SoftReset",
+            Self::WaitByLoop => "WaitByLoop",
+            Self::IntrWait => "IntrWait",
+            Self::VBlankIntrWait => "VBlankIntrWait",
+            Self::Halt => "Halt",
+            Self::Sleep => "Sleep",
+            Self::SoundBias => "SoundBias",
+            Self::Div => "Div",
+            Self::Mod => "Mod",
+            Self::CpuSet => "CpuSet",
+            Self::CpuFastSet => "CpuFastSet",
+            Self::Sqrt => "Sqrt",
+            Self::GetCRC16 => "GetCRC16",
+            Self::IsDebugger => "IsDebugger",
+            Self::BitUnPack => "BitUnPack",
+            Self::LZ77UnCompReadNormalWrite8bit => "LZ77UnCompReadNormalWrite8bit",
+            Self::LZ77UnCompReadByCallbackWrite16bit => "LZ77UnCompReadByCallbackWrite16bit",
+            Self::HuffUnCompReadByCallback => "HuffUnCompReadByCallback",
+            Self::RLUnCompReadNormalWrite8bit => "RLUnCompReadNormalWrite8bit",
+            Self::RLUnCompReadByCallbackWrite16bit => "RLUnCompReadByCallbackWrite16bit",
+            Self::Diff8bitUnFilterWrite8bit => "Diff8bitUnFilterWrite8bit",
+            Self::Diff16bitUnFilter => "Diff16bitUnFilter",
+            Self::GetSineTable => "GetSineTable",
+            Self::GetPitchTable => "GetPitchTable",
+            Self::GetVolumeTable => "GetVolumeTable",
+            Self::GetBootProcs => "GetBootProcs",
+            Self::CustomPost => "CustomPost",
+            Self::RsaInitCryptoHeap => "RsaInitCryptoHeap",
+            Self::RsaDecrypt => "RsaDecrypt",
+            Self::RsaDecryptUnpad => "RsaDecryptUnpad",
+            Self::RsaDecryptUnpadGetSha1 => "RsaDecryptUnpadGetSha1",
+            Self::Sha1Init => "Sha1Init",
+            Self::Sha1Update => "Sha1Update",
+            Self::Sha1Finish => "Sha1Finish",
+            Self::Sha1InitUpdateFinish => "Sha1InitUpdateFinish",
+            Self::Sha1Compare => "Sha1Compare",
+            Self::Sha1Random => "Sha1Random",
+        }
+    }
+
+    /// Returns the BIOS function called by a `swi` instruction, if any.
+    pub fn from_ins(parsed_ins: &ParsedIns, thumb: bool) -> Option<Self> {
+        match (parsed_ins.mnemonic, parsed_ins.args[0], parsed_ins.args[1]) {
+            ("swi", Argument::UImm(interrupt), Argument::None) | ("svc", Argument::UImm(interrupt), Argument::None) => {
+                // The BIOS reads the interrupt value from bits 16-23 of ARM instructions
+                let interrupt = if thumb { interrupt & 0xff } else { (interrupt >> 16) & 0xff };
+                interrupt.try_into().ok()
+            }
+            _ => None,
         }
     }
 }
@@ -137,6 +228,8 @@ impl TryFrom<u32> for SwiFunction {
             0x4 => Ok(Self::IntrWait),
             0x5 => Ok(Self::VBlankIntrWait),
             0x6 => Ok(Self::Halt),
+            0x7 => Ok(Self::Sleep),
+            0x8 => Ok(Self::SoundBias),
             0x9 => Ok(Self::Div),
             0xb => Ok(Self::CpuSet),
             0xc => Ok(Self::CpuFastSet),
@@ -149,6 +242,23 @@ impl TryFrom<u32> for SwiFunction {
             0x13 => Ok(Self::HuffUnCompReadByCallback),
             0x14 => Ok(Self::RLUnCompReadNormalWrite8bit),
             0x15 => Ok(Self::RLUnCompReadByCallbackWrite16bit),
+            0x16 => Ok(Self::Diff8bitUnFilterWrite8bit),
+            0x18 => Ok(Self::Diff16bitUnFilter),
+            0x1a => Ok(Self::GetSineTable),
+            0x1b => Ok(Self::GetPitchTable),
+            0x1c => Ok(Self::GetVolumeTable),
+            0x1d => Ok(Self::GetBootProcs),
+            0x1f => Ok(Self::CustomPost),
+            0x20 => Ok(Self::RsaInitCryptoHeap),
+            0x21 => Ok(Self::RsaDecrypt),
+            0x22 => Ok(Self::RsaDecryptUnpad),
+            0x23 => Ok(Self::RsaDecryptUnpadGetSha1),
+            0x24 => Ok(Self::Sha1Init),
+            0x25 => Ok(Self::Sha1Update),
+            0x26 => Ok(Self::Sha1Finish),
+            0x27 => Ok(Self::Sha1InitUpdateFinish),
+            0x28 => Ok(Self::Sha1Compare),
+            0x29 => Ok(Self::Sha1Random),
             _ => UnknownInterruptSnafu { value }.fail(),
         }
     }
@@ -160,6 +270,7 @@ pub struct SecureAreaFunction {
     return_reg: Register,
     start: u32,
     end: u32,
+    thumb: bool,
 }
 
 impl SecureAreaFunction {
@@ -174,4 +285,8 @@ impl SecureAreaFunction {
     pub fn end(&self) -> u32 {
         self.end
     }
+
+    pub fn thumb(&self) -> bool {
+        self.thumb
+    }
 }
@@ -0,0 +1,49 @@
+use crate::util::bytes::FromSlice;
+
+/// `ldr pc, [pc, #-0x4]`
+const ARM_LDR_PC: u32 = 0xe51ff004;
+/// `ldr ip, [pc, #0x0]`
+const ARM_LDR_IP: u32 = 0xe59fc000;
+/// `bx ip`
+const ARM_BX_IP: u32 = 0xe12fff1c;
+/// `bx pc`
+const THUMB_BX_PC: u16 = 0x4778;
+/// `nop`, encoded as `mov r8, r8`
+const THUMB_NOP: u16 = 0x46c0;
+
+/// A stub inserted by the linker which jumps to a function that a call can't reach directly, either because it is too far
+/// away or because it requires switching between ARM and Thumb mode.
+#[derive(Debug, Clone, Copy)]
+pub struct Veneer {
+    pub size: u32,
+    /// Address of the real function, with the lowest bit set if it is a Thumb function
+    pub target: u32,
+}
+
+impl Veneer {
+    /// Detects a veneer at the start of `code`. Thumb veneers switch to ARM mode with `bx pc` before jumping.
+    pub fn parse(address: u32, thumb: bool, code: &[u8]) -> Option<Self> {
+        if thumb {
+            if (address & 3) != 0 || code.len() < 4 {
+                return None;
+            }
+            if u16::from_le_slice(&code[0..]) != THUMB_BX_PC || u16::from_le_slice(&code[2..]) != THUMB_NOP {
+                return None;
+            }
+            let arm_veneer = Self::parse(address + 4, false, &code[4..])?;
+            return Some(Self { size: arm_veneer.size + 4, target: arm_veneer.target });
+        }
+
+        let words = code.chunks_exact(4).take(3).map(u32::from_le_slice).collect::<Vec<_>>();
+        match words.as_slice() {
+            [ARM_LDR_PC, target, ..] => Some(Self { size: 8, target: *target }),
+            [ARM_LDR_IP, ARM_BX_IP, target] => Some(Self { size: 12, target: *target }),
+            _ => None,
+        }
+    }
+
+    /// Address of the constant containing the target address, relative to the start of the veneer.
+    pub fn target_offset(&self) -> u32 {
+        self.size - 4
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
     backtrace::Backtrace,
+    iter,
     path::{Path, PathBuf},
 };
 
@@ -7,6 +8,7 @@ use ds_rom::rom::raw::AutoloadKind;
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
 
+use super::module::ModuleKind;
 use crate::util::io::{open_file, FileError};
 
 #[derive(Serialize, Deserialize)]
@@ -32,6 +34,13 @@ impl Config {
         let file = open_file(path)?;
         serde_yml::from_reader(file).map_err(|error| SerdeYmlSnafu { path, error }.build())
     }
+
+    /// Iterates over the main module, autoloads and overlays, in that order.
+    pub fn modules(&self) -> impl Iterator<Item = (ModuleKind, &ConfigModule)> {
+        iter::once((ModuleKind::Arm9, &self.main_module))
+            .chain(self.autoloads.iter().map(|autoload| (ModuleKind::Autoload(autoload.kind), &autoload.module)))
+            .chain(self.overlays.iter().map(|overlay| (ModuleKind::Overlay(overlay.id), &overlay.module)))
+    }
 }
 
 #[derive(Serialize, Deserialize)]
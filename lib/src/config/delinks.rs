@@ -1,25 +1,44 @@
 use std::{
     backtrace::Backtrace,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::Display,
-    fs::File,
     io::{self, BufRead, BufReader, BufWriter, Lines, Write},
+    num::ParseIntError,
     path::Path,
 };
 
+use ds_rom::rom::raw::AutoloadKind;
+use petgraph::{graph::NodeIndex, Graph};
 use snafu::Snafu;
 
 use crate::util::io::{create_file, open_file, FileError};
 
 use super::{
+    config::{Config, ConfigModule},
     module::ModuleKind,
-    section::{Section, SectionInheritParseError, SectionParseError, Sections, SectionsError},
+    section::{Section, SectionError, SectionInheritParseError, SectionKind, SectionParseError, Sections, SectionsError},
+    source::SourceLines,
+    symbol::{SymbolMap, SymbolMapParseError},
     ParseContext,
 };
 
 pub struct Delinks {
     pub sections: Sections,
     pub files: Vec<DelinkFile>,
+    /// Default compiler options for the files of this module
+    pub options: CompilerOptions,
     module_kind: ModuleKind,
+    /// Lines of the loaded delinks.txt, to preserve comments and ordering when writing it back
+    source: SourceLines<DelinksLine>,
+}
+
+#[derive(Clone)]
+enum DelinksLine {
+    Section(String),
+    Option(CompilerOptionKey),
+    /// Placeholder for a file, whose lines are kept by the [`DelinkFile`] itself
+    File(String),
 }
 
 #[derive(Debug, Snafu)]
@@ -34,6 +53,8 @@ pub enum DelinksParseError {
     Sections { context: ParseContext, error: Box<SectionsError> },
     #[snafu(transparent)]
     DelinkFileParse { source: DelinkFileParseError },
+    #[snafu(transparent)]
+    CompilerOptionParse { source: CompilerOptionParseError },
 }
 
 #[derive(Debug, Snafu)]
@@ -44,9 +65,52 @@ pub enum DelinksWriteError {
     Io { source: io::Error },
 }
 
+#[derive(Debug, Snafu)]
+pub enum DelinksGapError {
+    #[snafu(display("cycle detected when sorting delink files:\n{backtrace}"))]
+    Cycle { backtrace: Backtrace },
+    #[snafu(display(
+        "{section} in file '{file}' ({start:#x}..{end:#x}) overlaps with previous file '{prev_file}' ({prev_start:#x}..{prev_end:#x}):\n{backtrace}"
+    ))]
+    Overlap {
+        section: String,
+        file: String,
+        start: u32,
+        end: u32,
+        prev_file: String,
+        prev_start: u32,
+        prev_end: u32,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("file '{file}' has mixed section order with previous file, see {section}:\n{backtrace}"))]
+    MixedSectionOrder { file: String, section: String, backtrace: Backtrace },
+    #[snafu(display("failed to combine gaps {first} and {second}: {error}"))]
+    CombineGaps { first: String, second: String, error: Box<SectionsError> },
+    #[snafu(display("unknown autoload kind '{kind}':\n{backtrace}"))]
+    UnknownAutoloadKind { kind: AutoloadKind, backtrace: Backtrace },
+    #[snafu(display(
+        "veneer at {address:#010x} is in file '{file}', but veneers are generated by the linker and must be left to gaps:\n{backtrace}"
+    ))]
+    VeneerInFile { address: u32, file: String, backtrace: Backtrace },
+    #[snafu(transparent)]
+    Section { source: SectionError },
+    #[snafu(transparent)]
+    Sections { source: SectionsError },
+}
+
+#[derive(Debug, Snafu)]
+pub enum DelinksLoadError {
+    #[snafu(transparent)]
+    DelinksParse { source: DelinksParseError },
+    #[snafu(transparent)]
+    SymbolMapParse { source: SymbolMapParseError },
+    #[snafu(transparent)]
+    DelinksGap { source: DelinksGapError },
+}
+
 impl Delinks {
     pub fn new(sections: Sections, files: Vec<DelinkFile>, module_kind: ModuleKind) -> Self {
-        Self { sections, files, module_kind }
+        Self { sections, files, options: CompilerOptions::default(), module_kind, source: SourceLines::new() }
     }
 
     pub fn from_file<P: AsRef<Path>>(path: P, module_kind: ModuleKind) -> Result<Self, DelinksParseError> {
@@ -58,46 +122,320 @@ impl Delinks {
 
         let mut sections: Sections = Sections::new();
         let mut files = vec![];
+        let mut options = CompilerOptions::default();
+        let mut source = SourceLines::new();
 
         let mut lines = reader.lines();
         while let Some(line) = lines.next() {
             context.row += 1;
 
-            let line = line?;
-            let comment_start = line.find("//").unwrap_or(line.len());
-            let line = &line[..comment_start];
-
-            if Self::try_parse_delink_file(line, &mut lines, &mut context, &mut files, &sections)? {
+            let text = line?;
+            if Self::try_parse_delink_file(&text, &mut lines, &mut context, &mut files, &sections, &mut source)? {
                 break;
             }
+
+            let comment_start = text.find("//").unwrap_or(text.len());
+            let line = &text[..comment_start];
+            if let Some(key) = options.parse_line(line.trim(), &context)? {
+                let display = options.display(key).unwrap();
+                source.push_entry(text, DelinksLine::Option(key), display);
+                continue;
+            }
             let Some(section) = Section::parse(line, &context)? else {
+                source.push_text(text);
                 continue;
             };
+            source.push_entry(text, DelinksLine::Section(section.name().to_string()), section.to_string());
             sections.add(section).map_err(|error| SectionsSnafu { context: context.clone(), error }.build())?;
         }
 
         while let Some(line) = lines.next() {
             context.row += 1;
 
-            let line = line?;
-            let comment_start = line.find("//").unwrap_or(line.len());
-            let line = &line[..comment_start];
+            let text = line?;
+            if !Self::try_parse_delink_file(&text, &mut lines, &mut context, &mut files, &sections, &mut source)? {
+                source.push_text(text);
+            }
+        }
 
-            Self::try_parse_delink_file(line, &mut lines, &mut context, &mut files, &sections)?;
+        Ok(Self { sections, files, options, module_kind, source })
+    }
+
+    pub fn from_file_and_generate_gaps<P: AsRef<Path>>(path: P, module_kind: ModuleKind) -> Result<Self, DelinksLoadError> {
+        let mut delinks = Self::from_file(path, module_kind)?;
+        delinks.generate_gap_files()?;
+        Ok(delinks)
+    }
+
+    /// Loads the delinks of a module, with gaps generated and veneers excluded.
+    pub fn from_module_config(
+        config_path: &Path,
+        module: &ConfigModule,
+        module_kind: ModuleKind,
+    ) -> Result<Self, DelinksLoadError> {
+        let mut delinks = Self::from_file_and_generate_gaps(config_path.join(&module.delinks), module_kind)?;
+        let symbol_map = SymbolMap::from_file(config_path.join(&module.symbols))?;
+        delinks.exclude_veneers(&symbol_map)?;
+        Ok(delinks)
+    }
+
+    /// Loads the delinks of every module in a dsd config, with gaps generated and veneers excluded.
+    pub fn from_config(config_path: &Path, config: &Config) -> Result<BTreeMap<ModuleKind, Self>, DelinksLoadError> {
+        Self::for_each_module(config, |module, module_kind| Self::from_module_config(config_path, module, module_kind))
+    }
+
+    /// Loads the delinks of every module in a dsd config with gaps generated, but without excluding veneers. Meant for
+    /// commands which only look up files by address, so that a veneer in a delinked file doesn't stop them.
+    pub fn from_config_with_gaps(config_path: &Path, config: &Config) -> Result<BTreeMap<ModuleKind, Self>, DelinksLoadError> {
+        Self::for_each_module(config, |module, module_kind| {
+            Self::from_file_and_generate_gaps(config_path.join(&module.delinks), module_kind)
+        })
+    }
+
+    fn for_each_module(
+        config: &Config,
+        load: impl Fn(&ConfigModule, ModuleKind) -> Result<Self, DelinksLoadError>,
+    ) -> Result<BTreeMap<ModuleKind, Self>, DelinksLoadError> {
+        config.modules().map(|(module_kind, module)| load(module, module_kind).map(|delinks| (module_kind, delinks))).collect()
+    }
+
+    /// Splits gap files around linker-generated veneers, so that the veneers are left out of delinked objects. Veneers in
+    /// other files are an error, as the linker would generate them a second time.
+    pub fn exclude_veneers(&mut self, symbol_map: &SymbolMap) -> Result<(), DelinksGapError> {
+        let veneers = symbol_map
+            .functions()
+            .filter(|(function, _)| function.veneer)
+            .map(|(function, symbol)| symbol.addr..symbol.addr + function.size)
+            .collect::<Vec<_>>();
+        if veneers.is_empty() {
+            return Ok(());
         }
 
-        Ok(Self { sections, files, module_kind })
+        let mut files = vec![];
+        for file in std::mem::take(&mut self.files) {
+            let contains_veneer = |section: &Section| {
+                let range = section.address_range();
+                section.kind() == SectionKind::Code
+                    && veneers.iter().any(|veneer| veneer.start >= range.start && veneer.end <= range.end)
+            };
+            let Some(section) = file.sections.iter().find(|section| contains_veneer(section)) else {
+                files.push(file);
+                continue;
+            };
+            if !file.gap() {
+                let range = section.address_range();
+                let veneer = veneers.iter().find(|veneer| veneer.start >= range.start && veneer.end <= range.end).unwrap();
+                return VeneerInFileSnafu { address: veneer.start, file: file.name.clone() }.fail();
+            }
+
+            // Split each code section into the parts between the veneers. The first part of every section stays in this
+            // gap, and the other parts get a gap each since a file can only have one section of each name.
+            let mut first_sections = Sections::new();
+            let mut other_sections = vec![];
+            for section in file.sections.iter() {
+                let range = section.address_range();
+                let mut pieces = vec![];
+                let mut start = range.start;
+                if section.kind() == SectionKind::Code {
+                    for veneer in veneers.iter().filter(|veneer| veneer.start >= range.start && veneer.end <= range.end) {
+                        if start < veneer.start {
+                            pieces.push(start..veneer.start);
+                        }
+                        start = veneer.end;
+                    }
+                }
+                if start < range.end {
+                    pieces.push(start..range.end);
+                }
+
+                for (i, piece) in pieces.into_iter().enumerate() {
+                    let piece = Section::inherit(section, piece.start, piece.end)?;
+                    if i == 0 {
+                        first_sections.add(piece)?;
+                    } else {
+                        let mut sections = Sections::new();
+                        sections.add(piece)?;
+                        other_sections.push(sections);
+                    }
+                }
+            }
+
+            let pieces = Some(first_sections).filter(|sections| sections.len() > 0).into_iter().chain(other_sections);
+            for (i, sections) in pieces.enumerate() {
+                let name = if i == 0 { file.name.clone() } else { format!("{}_{i}", file.name) };
+                let mut gap = DelinkFile::new(name, sections, false);
+                gap.gap = true;
+                files.push(gap);
+            }
+        }
+        self.files = files;
+
+        Ok(())
     }
 
-    fn try_parse_delink_file(
-        line: &str,
-        lines: &mut Lines<BufReader<File>>,
+    fn generate_gap_files(&mut self) -> Result<(), DelinksGapError> {
+        self.sort_files()?;
+        self.validate_files()?;
+
+        // Find gaps in each section
+        let mut prev_section_ends =
+            self.sections.iter().map(|s| (s.name().to_string(), s.start_address())).collect::<HashMap<_, _>>();
+        let mut gap_files = vec![];
+        for file in &self.files {
+            for section in self.sections.iter() {
+                let Some((_, file_section)) = file.sections.by_name(section.name()) else { continue };
+                let prev_section_end = prev_section_ends.get_mut(section.name()).unwrap();
+                if *prev_section_end < file_section.start_address() {
+                    let mut gap = DelinkFile::new_gap(self.module_kind(), gap_files.len())?;
+                    gap.sections.add(Section::inherit(section, *prev_section_end, file_section.start_address())?)?;
+                    gap_files.push(gap);
+                }
+                *prev_section_end = file_section.end_address();
+            }
+        }
+
+        // Add gaps after last file
+        for section in self.sections.iter() {
+            let prev_section_end = *prev_section_ends.get(section.name()).unwrap();
+            if prev_section_end < section.end_address() {
+                let mut gap = DelinkFile::new_gap(self.module_kind(), gap_files.len())?;
+                gap.sections.add(Section::inherit(section, prev_section_end, section.end_address())?)?;
+                gap_files.push(gap);
+            }
+        }
+
+        // Sort gap files into files list
+        self.files.extend(gap_files);
+        self.sort_files()?;
+
+        // Combine adjacent gap files
+        for i in (1..self.files.len()).rev() {
+            let j = i - 1;
+            if self.files[i].gap && self.files[j].gap {
+                let file = self.files.remove(i);
+                for section in file.sections.into_iter() {
+                    self.files[j].sections.add(section).map_err(|error| {
+                        CombineGapsSnafu { first: file.name.clone(), second: self.files[j].name.clone(), error }.build()
+                    })?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn sort_files(&mut self) -> Result<(), DelinksGapError> {
+        let mut graph = Graph::<(), ()>::new();
+
+        for _ in 0..self.files.len() {
+            graph.add_node(());
+        }
+
+        for i in 0..self.files.len() {
+            let i_node = NodeIndex::new(i);
+            for j in i + 1..self.files.len() {
+                let j_node = NodeIndex::new(j);
+                match self.compare_files(&self.files[i], &self.files[j]) {
+                    Ordering::Less => {
+                        graph.add_edge(i_node, j_node, ());
+                    }
+                    Ordering::Equal => {}
+                    Ordering::Greater => {
+                        graph.add_edge(j_node, i_node, ());
+                    }
+                }
+            }
+        }
+
+        let mut nodes = match petgraph::algo::toposort(&graph, None) {
+            Ok(nodes) => nodes,
+            Err(_) => return CycleSnafu.fail(),
+        };
+
+        // Sort by node indices
+        for i in 0..self.files.len() {
+            if nodes[i].index() != i {
+                let mut current = i;
+                loop {
+                    let target = nodes[current].index();
+                    nodes[current] = NodeIndex::new(current);
+                    if nodes[target] == NodeIndex::new(target) {
+                        break;
+                    }
+                    self.files.swap(current, target);
+                    current = target;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compare_files(&self, a: &DelinkFile, b: &DelinkFile) -> Ordering {
+        for section in self.sections.iter() {
+            let Some((_, a_section)) = a.sections.by_name(section.name()) else {
+                continue;
+            };
+            let Some((_, b_section)) = b.sections.by_name(section.name()) else {
+                continue;
+            };
+            let ordering = a_section.start_address().cmp(&b_section.start_address());
+            if ordering.is_ne() {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+
+    /// Checks that adjacent files do not overlap and that their sections are in ascending order. Assumes that the files list
+    /// is already sorted using [`Self::sort_files`].
+    fn validate_files(&self) -> Result<(), DelinksGapError> {
+        for section in self.sections.iter() {
+            let mut prev_name = "";
+            let mut prev_start = section.start_address();
+            let mut prev_end = section.start_address();
+            for file in &self.files {
+                let Some((_, file_section)) = file.sections.by_name(section.name()) else {
+                    continue;
+                };
+                if file_section.start_address() < prev_end {
+                    if file_section.end_address() > prev_start {
+                        return OverlapSnafu {
+                            section: file_section.name(),
+                            file: &file.name,
+                            start: file_section.start_address(),
+                            end: file_section.end_address(),
+                            prev_file: prev_name,
+                            prev_start,
+                            prev_end,
+                        }
+                        .fail();
+                    } else {
+                        return MixedSectionOrderSnafu { file: &file.name, section: file_section.name() }.fail();
+                    }
+                }
+                prev_name = &file.name;
+                prev_start = file_section.start_address();
+                prev_end = file_section.end_address();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn try_parse_delink_file<R: BufRead>(
+        text: &str,
+        lines: &mut Lines<R>,
         context: &mut ParseContext,
         files: &mut Vec<DelinkFile>,
         sections: &Sections,
+        source: &mut SourceLines<DelinksLine>,
     ) -> Result<bool, DelinkFileParseError> {
+        let comment_start = text.find("//").unwrap_or(text.len());
+        let line = &text[..comment_start];
         if line.chars().next().map_or(false, |c| !c.is_whitespace()) {
-            let delink_file = DelinkFile::parse(line, lines, context, sections)?;
+            let delink_file = DelinkFile::parse(text, lines, context, sections)?;
+            source.push_entry(String::new(), DelinksLine::File(delink_file.name.clone()), String::new());
             files.push(delink_file);
             Ok(true)
         } else {
@@ -111,13 +449,96 @@ impl Delinks {
         let file = create_file(path)?;
         let mut writer = BufWriter::new(file);
 
-        write!(writer, "{}", DisplayDelinks { sections, files: &[] })?;
+        write!(writer, "{}", DisplayDelinks { sections, options: &CompilerOptions::default(), files: &[] })?;
+
+        Ok(())
+    }
+
+    /// Writes the sections and files to a delinks.txt. If these delinks were loaded from a file, its comments, blank lines and
+    /// file order are preserved, and only changed lines are rewritten. New files are added at the end.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), DelinksWriteError> {
+        let mut buffer = vec![];
+        if self.source.is_empty() {
+            write!(buffer, "{}", self.display())?;
+        } else {
+            self.write_source(&mut buffer)?;
+        }
+
+        let file = create_file(path.as_ref())?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&buffer)?;
+
+        Ok(())
+    }
+
+    fn write_source(&self, buffer: &mut Vec<u8>) -> io::Result<()> {
+        let mut source_sections = HashSet::new();
+        let mut source_options = HashSet::new();
+        let mut source_files = HashSet::new();
+        for key in self.source.keys() {
+            match key {
+                DelinksLine::Section(name) => source_sections.insert(name.as_str()),
+                DelinksLine::Option(key) => source_options.insert(*key),
+                DelinksLine::File(name) => source_files.insert(name.as_str()),
+            };
+        }
+        let last_section_line = self
+            .source
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| matches!(line.key(), Some(DelinksLine::Section(_))))
+            .map(|(i, _)| i)
+            .last();
+
+        for (i, line) in self.source.iter().enumerate() {
+            match line.key() {
+                None => line.write(buffer, None)?,
+                Some(DelinksLine::Section(name)) => {
+                    if let Some((_, section)) = self.sections.by_name(name) {
+                        line.write(buffer, Some(&section.to_string()))?;
+                    }
+                }
+                Some(DelinksLine::Option(key)) => {
+                    if let Some(display) = self.options.display(*key) {
+                        line.write(buffer, Some(&display))?;
+                    }
+                }
+                Some(DelinksLine::File(name)) => {
+                    if let Some(file) = self.files.iter().find(|file| &file.name == name) {
+                        file.write_source(buffer)?;
+                    }
+                }
+            }
+
+            if Some(i) == last_section_line {
+                for section in self.sections.sorted_by_address() {
+                    if !source_sections.contains(section.name()) {
+                        writeln!(buffer, "    {section}")?;
+                    }
+                }
+                for key in CompilerOptionKey::ALL {
+                    if let Some(display) = self.options.display(key).filter(|_| !source_options.contains(&key)) {
+                        writeln!(buffer, "    {display}")?;
+                    }
+                }
+            }
+        }
+
+        for file in &self.files {
+            if file.gap || source_files.contains(file.name.as_str()) {
+                continue;
+            }
+            if !buffer.is_empty() && !buffer.ends_with(b"\n\n") {
+                writeln!(buffer)?;
+            }
+            file.write_source(buffer)?;
+        }
 
         Ok(())
     }
 
     pub fn display(&self) -> DisplayDelinks {
-        DisplayDelinks { sections: &self.sections, files: &self.files }
+        DisplayDelinks { sections: &self.sections, options: &self.options, files: &self.files }
     }
 
     pub fn module_kind(&self) -> ModuleKind {
@@ -126,6 +547,7 @@ impl Delinks {
 }
 pub struct DisplayDelinks<'a> {
     sections: &'a Sections,
+    options: &'a CompilerOptions,
     files: &'a [DelinkFile],
 }
 
@@ -134,6 +556,7 @@ impl<'a> Display for DisplayDelinks<'a> {
         for section in self.sections.sorted_by_address() {
             writeln!(f, "    {section}")?;
         }
+        write!(f, "{}", self.options)?;
         writeln!(f)?;
         for file in self.files {
             writeln!(f, "{file}")?;
@@ -146,7 +569,19 @@ pub struct DelinkFile {
     pub name: String,
     pub sections: Sections,
     pub complete: bool,
+    /// Compiler options which override the module's default options
+    pub options: CompilerOptions,
     pub gap: bool,
+    /// Lines of this file in the loaded delinks.txt, including the blank line which ends it
+    source: SourceLines<DelinkFileLine>,
+}
+
+#[derive(Clone)]
+enum DelinkFileLine {
+    Name,
+    Complete,
+    Option(CompilerOptionKey),
+    Section(String),
 }
 
 #[derive(Debug, Snafu)]
@@ -159,43 +594,145 @@ pub enum DelinkFileParseError {
     SectionInheritParse { source: SectionInheritParseError },
     #[snafu(transparent)]
     Sections { source: SectionsError },
+    #[snafu(transparent)]
+    CompilerOptionParse { source: CompilerOptionParseError },
 }
 
 impl DelinkFile {
     pub fn new(name: String, sections: Sections, complete: bool) -> Self {
-        Self { name, sections, complete, gap: false }
+        Self { name, sections, complete, options: CompilerOptions::default(), gap: false, source: SourceLines::new() }
     }
 
-    pub fn parse(
+    pub fn parse<R: BufRead>(
         first_line: &str,
-        lines: &mut Lines<BufReader<File>>,
+        lines: &mut Lines<R>,
         context: &mut ParseContext,
         inherit_sections: &Sections,
     ) -> Result<Self, DelinkFileParseError> {
-        let name = first_line
+        let comment_start = first_line.find("//").unwrap_or(first_line.len());
+        let name = first_line[..comment_start]
             .trim()
             .strip_suffix(':')
             .ok_or_else(|| MissingColonSnafu { context: context.clone() }.build())?
             .to_string();
 
+        let mut source = SourceLines::new();
+        source.push_entry(first_line.to_string(), DelinkFileLine::Name, format!("{name}:"));
+
         let mut complete = false;
+        let mut options = CompilerOptions::default();
         let mut sections = Sections::new();
         for line in lines.by_ref() {
             context.row += 1;
-            let line = line?;
-            let line = line.trim();
-            if line.is_empty() {
+            let text = line?;
+            if text.trim().is_empty() {
+                source.push_text(text);
                 break;
             }
+            let comment_start = text.find("//").unwrap_or(text.len());
+            let line = text[..comment_start].trim();
+            if line.is_empty() {
+                source.push_text(text);
+                continue;
+            }
             if line == "complete" {
                 complete = true;
+                source.push_entry(text, DelinkFileLine::Complete, "complete".to_string());
+                continue;
+            }
+            if let Some(key) = options.parse_line(line, context)? {
+                let display = options.display(key).unwrap();
+                source.push_entry(text, DelinkFileLine::Option(key), display);
                 continue;
             }
             let section = Section::parse_inherit(line, context, inherit_sections)?.unwrap();
+            let display = DisplayFileSection(&section).to_string();
+            source.push_entry(text, DelinkFileLine::Section(section.name().to_string()), display);
             sections.add(section)?;
         }
 
-        Ok(DelinkFile { name, sections, complete, gap: false })
+        Ok(DelinkFile { name, sections, complete, options, gap: false, source })
+    }
+
+    fn write_source<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        if self.source.is_empty() {
+            return write!(writer, "{self}");
+        }
+
+        let mut has_complete = false;
+        let mut source_options = HashSet::new();
+        let mut source_sections = HashSet::new();
+        for key in self.source.keys() {
+            match key {
+                DelinkFileLine::Name => {}
+                DelinkFileLine::Complete => has_complete = true,
+                DelinkFileLine::Option(key) => {
+                    source_options.insert(*key);
+                }
+                DelinkFileLine::Section(name) => {
+                    source_sections.insert(name.as_str());
+                }
+            }
+        }
+        let last_entry_line = self.source.iter().enumerate().filter(|(_, line)| line.key().is_some()).map(|(i, _)| i).last();
+
+        for (i, line) in self.source.iter().enumerate() {
+            match line.key() {
+                None => line.write(writer, None)?,
+                Some(DelinkFileLine::Name) => {
+                    line.write(writer, Some(&format!("{}:", self.name)))?;
+                    if self.complete && !has_complete {
+                        writeln!(writer, "    complete")?;
+                    }
+                    for key in CompilerOptionKey::ALL {
+                        if let Some(display) = self.options.display(key).filter(|_| !source_options.contains(&key)) {
+                            writeln!(writer, "    {display}")?;
+                        }
+                    }
+                }
+                Some(DelinkFileLine::Complete) => {
+                    if self.complete {
+                        line.write(writer, Some("complete"))?;
+                    }
+                }
+                Some(DelinkFileLine::Option(key)) => {
+                    if let Some(display) = self.options.display(*key) {
+                        line.write(writer, Some(&display))?;
+                    }
+                }
+                Some(DelinkFileLine::Section(name)) => {
+                    if let Some((_, section)) = self.sections.by_name(name) {
+                        line.write(writer, Some(&DisplayFileSection(section).to_string()))?;
+                    }
+                }
+            }
+
+            if Some(i) == last_entry_line {
+                for section in self.sections.sorted_by_address() {
+                    if !source_sections.contains(section.name()) {
+                        writeln!(writer, "    {}", DisplayFileSection(section))?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn new_gap(module_kind: ModuleKind, id: usize) -> Result<Self, DelinksGapError> {
+        let name = match module_kind {
+            ModuleKind::Arm9 => format!("main_{id}"),
+            ModuleKind::Overlay(overlay_id) => format!("ov{overlay_id:03}_{id}"),
+            ModuleKind::Autoload(kind) => match kind {
+                AutoloadKind::Itcm => format!("itcm_{id}"),
+                AutoloadKind::Dtcm => format!("dtcm_{id}"),
+                AutoloadKind::Unknown(_) => return UnknownAutoloadKindSnafu { kind }.fail(),
+            },
+        };
+
+        let mut gap = Self::new(name, Sections::new(), false);
+        gap.gap = true;
+        Ok(gap)
     }
 
     pub fn split_file_ext(&self) -> (&str, &str) {
@@ -210,8 +747,109 @@ impl DelinkFile {
 impl Display for DelinkFile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}:", self.name)?;
+        if self.complete {
+            writeln!(f, "    complete")?;
+        }
+        write!(f, "{}", self.options)?;
         for section in self.sections.sorted_by_address() {
-            writeln!(f, "    {section}")?;
+            writeln!(f, "    {}", DisplayFileSection(section))?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays a section of a delink file, which inherits its kind and alignment from the module's section.
+struct DisplayFileSection<'a>(&'a Section);
+
+impl<'a> Display for DisplayFileSection<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:11} start:{:#010x} end:{:#010x}", self.0.name(), self.0.start_address(), self.0.end_address())
+    }
+}
+
+/// Compiler settings for building a file and for creating decomp.me scratches of it.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct CompilerOptions {
+    /// Compiler ID as listed by decomp.me, such as `mwcc_30_126`
+    pub compiler: Option<String>,
+    pub flags: Option<String>,
+    /// decomp.me preset ID
+    pub preset: Option<u32>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum CompilerOptionKey {
+    Compiler,
+    Flags,
+    Preset,
+}
+
+impl CompilerOptionKey {
+    const ALL: [Self; 3] = [Self::Compiler, Self::Flags, Self::Preset];
+}
+
+#[derive(Debug, Snafu)]
+pub enum CompilerOptionParseError {
+    #[snafu(display("{context}: failed to parse preset ID '{value}': {error}\n{backtrace}"))]
+    ParsePreset { context: ParseContext, value: String, error: ParseIntError, backtrace: Backtrace },
+}
+
+impl CompilerOptions {
+    /// Returns whether a line is a `compiler:`, `flags:` or `preset:` line, and checks its value.
+    pub fn is_option_line(line: &str, context: &ParseContext) -> Result<bool, CompilerOptionParseError> {
+        Ok(Self::default().parse_line(line, context)?.is_some())
+    }
+
+    /// Parses a `compiler:`, `flags:` or `preset:` line. Flags are the rest of the line, so they may contain spaces.
+    fn parse_line(
+        &mut self,
+        line: &str,
+        context: &ParseContext,
+    ) -> Result<Option<CompilerOptionKey>, CompilerOptionParseError> {
+        let Some((key, value)) = line.split_once(':') else { return Ok(None) };
+        let value = value.trim();
+        match key {
+            "compiler" => {
+                self.compiler = Some(value.to_string());
+                Ok(Some(CompilerOptionKey::Compiler))
+            }
+            "flags" => {
+                self.flags = Some(value.to_string());
+                Ok(Some(CompilerOptionKey::Flags))
+            }
+            "preset" => {
+                let preset = value.parse().map_err(|error| ParsePresetSnafu { context, value, error }.build())?;
+                self.preset = Some(preset);
+                Ok(Some(CompilerOptionKey::Preset))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn display(&self, key: CompilerOptionKey) -> Option<String> {
+        match key {
+            CompilerOptionKey::Compiler => self.compiler.as_ref().map(|compiler| format!("compiler:{compiler}")),
+            CompilerOptionKey::Flags => self.flags.as_ref().map(|flags| format!("flags:{flags}")),
+            CompilerOptionKey::Preset => self.preset.map(|preset| format!("preset:{preset}")),
+        }
+    }
+
+    /// Returns these options, with unset options taken from `defaults`.
+    pub fn or(&self, defaults: &CompilerOptions) -> CompilerOptions {
+        CompilerOptions {
+            compiler: self.compiler.clone().or_else(|| defaults.compiler.clone()),
+            flags: self.flags.clone().or_else(|| defaults.flags.clone()),
+            preset: self.preset.or(defaults.preset),
+        }
+    }
+}
+
+impl Display for CompilerOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for key in CompilerOptionKey::ALL {
+            if let Some(display) = self.display(key) {
+                writeln!(f, "    {display}")?;
+            }
         }
         Ok(())
     }
@@ -0,0 +1,210 @@
+use std::{
+    backtrace::Backtrace,
+    collections::{BTreeMap, BTreeSet},
+    io::{self, BufRead, BufReader},
+    num::ParseIntError,
+    ops::Range,
+    path::Path,
+};
+
+use snafu::Snafu;
+
+use crate::util::{
+    io::{open_file, FileError},
+    parse::parse_u32,
+};
+
+use super::{
+    iter_attributes,
+    relocations::{Relocation, RelocationParseError},
+    symbol::{InstructionMode, InstructionModeParseError},
+    ParseContext,
+};
+
+/// Corrections to the analysis of a module, read from its hints.txt before `dsd init` analyzes it.
+#[derive(Clone, Default)]
+pub struct Hints {
+    /// Function start addresses and whether they are Thumb functions
+    pub functions: BTreeMap<u32, bool>,
+    /// Ranges that contain no code
+    pub data: Vec<Range<u32>>,
+    /// Section boundaries, by section name
+    pub sections: BTreeMap<String, Range<u32>>,
+    /// Symbol names, by address
+    pub names: BTreeMap<u32, String>,
+    pub relocations: Vec<Relocation>,
+    /// Addresses whose relocations are removed after the analysis. Unlike not_pointer marks, these are not written to
+    /// relocs.txt.
+    pub ignored_relocations: BTreeSet<u32>,
+}
+
+#[derive(Debug, Snafu)]
+pub enum HintsParseError {
+    #[snafu(transparent)]
+    File { source: FileError },
+    #[snafu(transparent)]
+    Io { source: io::Error },
+    #[snafu(transparent)]
+    RelocationParse { source: RelocationParseError },
+    #[snafu(transparent)]
+    InstructionModeParse { source: InstructionModeParseError },
+    #[snafu(display("{context}: failed to parse address '{value}': {error}\n{backtrace}"))]
+    ParseAddress { context: ParseContext, value: String, error: ParseIntError, backtrace: Backtrace },
+    #[snafu(display("{context}: expected hint 'function', 'data', 'section', 'symbol', 'reloc' or 'ignore_reloc' but got '{hint}':\n{backtrace}"))]
+    UnknownHint { context: ParseContext, hint: String, backtrace: Backtrace },
+    #[snafu(display("{context}: unexpected attribute '{key}' for hint '{hint}':\n{backtrace}"))]
+    UnknownAttribute { context: ParseContext, hint: String, key: String, backtrace: Backtrace },
+    #[snafu(display("{context}: missing '{attribute}' attribute:\n{backtrace}"))]
+    MissingAttribute { context: ParseContext, attribute: String, backtrace: Backtrace },
+    #[snafu(display("{context}: relocation hint could not be parsed:\n{backtrace}"))]
+    EmptyRelocation { context: ParseContext, backtrace: Backtrace },
+}
+
+impl Hints {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, HintsParseError> {
+        let path = path.as_ref();
+        let mut context = ParseContext { file_path: path.to_str().unwrap().to_string(), row: 0 };
+
+        let file = open_file(path)?;
+        let reader = BufReader::new(file);
+
+        let mut hints = Self::default();
+        for line in reader.lines() {
+            context.row += 1;
+
+            let line = line?;
+            let comment_start = line.find("//").unwrap_or(line.len());
+            hints.parse_line(&line[..comment_start], &context)?;
+        }
+
+        Ok(hints)
+    }
+
+    fn parse_line(&mut self, line: &str, context: &ParseContext) -> Result<(), HintsParseError> {
+        let mut words = line.split_whitespace();
+        let Some(hint) = words.next() else { return Ok(()) };
+
+        if hint == "reloc" {
+            let rest = words.collect::<Vec<_>>().join(" ");
+            let relocation = Relocation::parse(&rest, context)?.ok_or_else(|| EmptyRelocationSnafu { context }.build())?;
+            self.relocations.push(relocation);
+            return Ok(());
+        }
+
+        let mut address = None;
+        let mut start = None;
+        let mut end = None;
+        let mut from = None;
+        let mut name = None;
+        let mut mode = None;
+        for (key, value) in iter_attributes(words) {
+            match (hint, key) {
+                ("function" | "symbol", "addr") => address = Some(Self::parse_address(value, context)?),
+                ("function" | "symbol" | "section", "name") => name = Some(value.to_string()),
+                ("function", "mode") => mode = Some(InstructionMode::parse(value, context)?),
+                ("data" | "section", "start") => start = Some(Self::parse_address(value, context)?),
+                ("data" | "section", "end") => end = Some(Self::parse_address(value, context)?),
+                ("ignore_reloc", "from") => from = Some(Self::parse_address(value, context)?),
+                ("function" | "symbol" | "data" | "section" | "ignore_reloc", _) => {
+                    return UnknownAttributeSnafu { context, hint, key }.fail();
+                }
+                _ => return UnknownHintSnafu { context, hint }.fail(),
+            }
+        }
+
+        let missing = |attribute: &str| MissingAttributeSnafu { context, attribute }.build();
+        match hint {
+            "function" => {
+                let address = address.ok_or_else(|| missing("addr"))?;
+                let mode = mode.ok_or_else(|| missing("mode"))?;
+                self.functions.insert(address, mode == InstructionMode::Thumb);
+                if let Some(name) = name {
+                    self.names.insert(address, name);
+                }
+            }
+            "symbol" => {
+                let address = address.ok_or_else(|| missing("addr"))?;
+                self.names.insert(address, name.ok_or_else(|| missing("name"))?);
+            }
+            "data" => self.data.push(start.ok_or_else(|| missing("start"))?..end.ok_or_else(|| missing("end"))?),
+            "section" => {
+                let range = start.ok_or_else(|| missing("start"))?..end.ok_or_else(|| missing("end"))?;
+                self.sections.insert(name.ok_or_else(|| missing("name"))?, range);
+            }
+            "ignore_reloc" => {
+                self.ignored_relocations.insert(from.ok_or_else(|| missing("from"))?);
+            }
+            _ => return UnknownHintSnafu { context, hint }.fail(),
+        }
+        Ok(())
+    }
+
+    fn parse_address(value: &str, context: &ParseContext) -> Result<u32, HintsParseError> {
+        parse_u32(value).map_err(|error| ParseAddressSnafu { context, value, error }.build())
+    }
+
+    pub fn section(&self, name: &str) -> Option<Range<u32>> {
+        self.sections.get(name).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::relocations::{RelocationKind, RelocationModule};
+
+    fn parse(lines: &[&str]) -> Result<Hints, HintsParseError> {
+        let mut context = ParseContext { file_path: "hints.txt".to_string(), row: 0 };
+        let mut hints = Hints::default();
+        for line in lines {
+            context.row += 1;
+            hints.parse_line(line, &context)?;
+        }
+        Ok(hints)
+    }
+
+    #[test]
+    fn test_parse_hints() {
+        let hints = parse(&[
+            "function addr:0x02000100 mode:thumb name:Foo",
+            "function addr:0x02000200 mode:arm",
+            "data start:0x02000300 end:0x02000340",
+            "section name:.rodata start:0x02001000 end:0x02002000",
+            "symbol addr:0x02003000 name:gBar",
+            "reloc from:0x02000104 kind:thumb_call to:0x02000200 module:main",
+            "ignore_reloc from:0x02000108",
+            "",
+        ])
+        .unwrap();
+
+        assert_eq!(hints.functions, BTreeMap::from([(0x02000100, true), (0x02000200, false)]));
+        assert_eq!(hints.data, vec![0x02000300..0x02000340]);
+        assert_eq!(hints.section(".rodata"), Some(0x02001000..0x02002000));
+        assert_eq!(hints.section(".text"), None);
+        assert_eq!(hints.names, BTreeMap::from([(0x02000100, "Foo".to_string()), (0x02003000, "gBar".to_string())]));
+        assert_eq!(hints.ignored_relocations, BTreeSet::from([0x02000108]));
+
+        assert_eq!(hints.relocations.len(), 1);
+        let relocation = &hints.relocations[0];
+        assert_eq!(relocation.from_address(), 0x02000104);
+        assert_eq!(relocation.to_address(), 0x02000200);
+        assert!(relocation.kind() == RelocationKind::ThumbCall);
+        assert_eq!(relocation.module(), &RelocationModule::Main);
+    }
+
+    #[test]
+    fn test_parse_hint_errors() {
+        let cases = [
+            ("unknown hint", "func addr:0x02000000 mode:arm"),
+            ("unknown attribute", "data start:0x02000000 end:0x02000010 name:foo"),
+            ("missing mode", "function addr:0x02000000"),
+            ("missing name", "symbol addr:0x02000000"),
+            ("bad address", "ignore_reloc from:0x0200zzzz"),
+            ("empty reloc", "reloc"),
+            ("incomplete reloc", "reloc from:0x02000000 kind:load"),
+        ];
+        for (name, line) in cases {
+            assert!(parse(&[line]).is_err(), "{name}");
+        }
+    }
+}
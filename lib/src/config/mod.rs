@@ -2,9 +2,11 @@ use std::{fmt::Display, str::SplitWhitespace};
 
 pub mod config;
 pub mod delinks;
+pub mod hints;
 pub mod module;
 pub mod relocations;
 pub mod section;
+pub(crate) mod source;
 pub mod symbol;
 
 #[derive(Debug, Clone)]
@@ -18,14 +18,16 @@ use crate::analysis::{
         ParseFunctionOptions, ParseFunctionResult,
     },
     main::{MainFunction, MainFunctionError},
+    secure_area::SecureAreaFunction,
 };
 
 use self::data::FindLocalDataError;
 
 use super::{
-    relocations::Relocations,
+    hints::Hints,
+    relocations::{Relocation, Relocations},
     section::{Section, SectionCodeError, SectionError, SectionKind, Sections, SectionsError},
-    symbol::{SymData, SymbolKind, SymbolMap, SymbolMapError, SymbolMaps},
+    symbol::{SymBss, SymData, SymbolKind, SymbolMap, SymbolMapError, SymbolMaps},
 };
 
 pub struct Module<'a> {
@@ -38,6 +40,7 @@ pub struct Module<'a> {
     pub default_func_prefix: String,
     pub default_data_prefix: String,
     sections: Sections,
+    hints: Hints,
 }
 
 #[derive(Debug, Snafu)]
@@ -98,6 +101,7 @@ impl<'a> Module<'a> {
             default_func_prefix: "func_".to_string(),
             default_data_prefix: "data_".to_string(),
             sections,
+            hints: Hints::default(),
         })
     }
 
@@ -115,15 +119,19 @@ impl<'a> Module<'a> {
             default_func_prefix: "func_".to_string(),
             default_data_prefix: "data_".to_string(),
             sections: Sections::new(),
+            hints: options.hints.get(&ModuleKind::Arm9).cloned().unwrap_or_default(),
         };
+        module.add_not_pointers(options);
         let symbol_map = symbol_maps.get_mut(module.kind);
 
         module.find_sections_arm9(symbol_map, ctor_range, main_func, arm9)?;
+        module.find_swi_wrappers(symbol_map)?;
         module.find_data_from_pools(symbol_map, options)?;
         module.find_data_from_sections(symbol_map, options)?;
 
         symbol_map.rename_by_address(arm9.entry_function(), "Entry")?;
         symbol_map.rename_by_address(main_func.address, "main")?;
+        module.apply_name_hints(symbol_map)?;
 
         Ok(module)
     }
@@ -150,6 +158,7 @@ impl<'a> Module<'a> {
             default_func_prefix: format!("func_ov{:03}_", id),
             default_data_prefix: format!("data_ov{:03}_", id),
             sections,
+            hints: Hints::default(),
         })
     }
 
@@ -168,13 +177,17 @@ impl<'a> Module<'a> {
             default_func_prefix: format!("func_ov{:03}_", overlay.id()),
             default_data_prefix: format!("data_ov{:03}_", overlay.id()),
             sections: Sections::new(),
+            hints: options.hints.get(&ModuleKind::Overlay(overlay.id())).cloned().unwrap_or_default(),
         };
+        module.add_not_pointers(options);
         let symbol_map = symbol_maps.get_mut(module.kind);
 
         log::debug!("Analyzing overlay {}", overlay.id());
         module.find_sections_overlay(symbol_map, CtorRange { start: overlay.ctor_start(), end: overlay.ctor_end() })?;
+        module.find_swi_wrappers(symbol_map)?;
         module.find_data_from_pools(symbol_map, options)?;
         module.find_data_from_sections(symbol_map, options)?;
+        module.apply_name_hints(symbol_map)?;
 
         Ok(module)
     }
@@ -201,6 +214,7 @@ impl<'a> Module<'a> {
             default_func_prefix: "func_".to_string(),
             default_data_prefix: "data_".to_string(),
             sections,
+            hints: Hints::default(),
         })
     }
 
@@ -219,11 +233,15 @@ impl<'a> Module<'a> {
             default_func_prefix: "func_".to_string(),
             default_data_prefix: "data_".to_string(),
             sections: Sections::new(),
+            hints: options.hints.get(&ModuleKind::Autoload(AutoloadKind::Itcm)).cloned().unwrap_or_default(),
         };
+        module.add_not_pointers(options);
         let symbol_map = symbol_maps.get_mut(module.kind);
 
         module.find_sections_itcm(symbol_map)?;
+        module.find_swi_wrappers(symbol_map)?;
         module.find_data_from_pools(symbol_map, options)?;
+        module.apply_name_hints(symbol_map)?;
 
         Ok(module)
     }
@@ -243,11 +261,14 @@ impl<'a> Module<'a> {
             default_func_prefix: "func_".to_string(),
             default_data_prefix: "data_".to_string(),
             sections: Sections::new(),
+            hints: options.hints.get(&ModuleKind::Autoload(AutoloadKind::Dtcm)).cloned().unwrap_or_default(),
         };
+        module.add_not_pointers(options);
         let symbol_map = symbol_maps.get_mut(module.kind);
 
         module.find_sections_dtcm()?;
         module.find_data_from_sections(symbol_map, options)?;
+        module.apply_name_hints(symbol_map)?;
 
         Ok(module)
     }
@@ -291,6 +312,11 @@ impl<'a> Module<'a> {
         symbol_map: &mut SymbolMap,
         search_options: FunctionSearchOptions,
     ) -> Result<Option<FoundFunctions>, ModuleError> {
+        let search_options = FunctionSearchOptions {
+            forced_functions: Some(&self.hints.functions),
+            data_ranges: Some(&self.hints.data),
+            ..search_options
+        };
         let functions = Function::find_functions(FindFunctionsOptions {
             default_name_prefix: &self.default_func_prefix,
             base_address: self.base_address,
@@ -421,10 +447,13 @@ impl<'a> Module<'a> {
             ctor.start
         };
 
-        let rodata_start = if let Some(functions_result) = self.find_functions(
+        let text_end = self.hints.section(".text").map_or(rodata_end, |range| range.end);
+        let rodata_start = if let Some(mut functions_result) = self.find_functions(
             symbol_map,
-            FunctionSearchOptions { end_address: Some(rodata_end), use_data_as_upper_bound: true, ..Default::default() },
+            FunctionSearchOptions { end_address: Some(text_end), use_data_as_upper_bound: true, ..Default::default() },
         )? {
+            (functions_result.start, functions_result.end) =
+                self.hinted_range(".text", functions_result.start, functions_result.end);
             let end = functions_result.end;
             self.add_text_section(functions_result)?;
             end
@@ -432,10 +461,11 @@ impl<'a> Module<'a> {
             self.base_address
         };
 
+        let (rodata_start, rodata_end) = self.hinted_range(".rodata", rodata_start, rodata_end);
         self.add_rodata_section(rodata_start, rodata_end)?;
 
-        let data_start = ctor.end.next_multiple_of(32);
-        let data_end = self.base_address + self.code.len() as u32;
+        let (data_start, data_end) =
+            self.hinted_range(".data", ctor.end.next_multiple_of(32), self.base_address + self.code.len() as u32);
         self.add_data_section(data_start, data_end)?;
         self.add_bss_section(data_end)?;
 
@@ -483,6 +513,7 @@ impl<'a> Module<'a> {
             module_end_address: self.end_address(),
             parse_options: Default::default(),
             existing_functions: Some(&functions),
+            next_function_address: None,
         })?;
         let autoload_function = match parse_result {
             ParseFunctionResult::Found(function) => function,
@@ -511,7 +542,7 @@ impl<'a> Module<'a> {
                 symbol_map,
                 FunctionSearchOptions {
                     start_address: Some(main_func.address),
-                    end_address: Some(read_only_end),
+                    end_address: Some(self.hints.section(".text").map_or(read_only_end, |range| range.end)),
                     // Skips over segments of strange EOR instructions which are never executed
                     keep_searching_for_valid_function_start: true,
                     use_data_as_upper_bound: true,
@@ -522,20 +553,20 @@ impl<'a> Module<'a> {
         if text_end != read_only_end && has_init_section {
             log::warn!("Expected .text to end ({text_end:#x}) where .init starts ({read_only_end:#x})");
         }
-        let text_start = self.base_address;
         if has_init_section {
             text_end = read_only_end;
         }
+        let (text_start, text_end) = self.hinted_range(".text", self.base_address, text_end);
         functions.extend(text_functions);
         self.add_text_section(FoundFunctions { functions, start: text_start, end: text_end })?;
 
         // .rodata
-        let rodata_start = rodata_start.unwrap_or(text_end);
-        self.add_rodata_section(rodata_start, ctor.start)?;
+        let (rodata_start, rodata_end) = self.hinted_range(".rodata", rodata_start.unwrap_or(text_end), ctor.start);
+        self.add_rodata_section(rodata_start, rodata_end)?;
 
         // .data and .bss
-        let data_start = ctor.end.next_multiple_of(32);
-        let data_end = self.base_address + self.code.len() as u32;
+        let (data_start, data_end) =
+            self.hinted_range(".data", ctor.end.next_multiple_of(32), self.base_address + self.code.len() as u32);
         self.add_data_section(data_start, data_end)?;
         let bss_start = data_end.next_multiple_of(32);
         self.add_bss_section(bss_start)?;
@@ -544,7 +575,7 @@ impl<'a> Module<'a> {
     }
 
     fn find_sections_itcm(&mut self, symbol_map: &mut SymbolMap) -> Result<(), ModuleError> {
-        let text_functions = self
+        let mut text_functions = self
             .find_functions(
                 symbol_map,
                 FunctionSearchOptions {
@@ -554,6 +585,7 @@ impl<'a> Module<'a> {
                 },
             )?
             .ok_or_else(|| NoItcmFunctionsSnafu.build())?;
+        (text_functions.start, text_functions.end) = self.hinted_range(".text", text_functions.start, text_functions.end);
         let text_end = text_functions.end;
         self.add_text_section(text_functions)?;
 
@@ -564,8 +596,7 @@ impl<'a> Module<'a> {
     }
 
     fn find_sections_dtcm(&mut self) -> Result<(), ModuleError> {
-        let data_start = self.base_address;
-        let data_end = data_start + self.code.len() as u32;
+        let (data_start, data_end) = self.hinted_range(".data", self.base_address, self.base_address + self.code.len() as u32);
         self.add_data_section(data_start, data_end)?;
 
         let bss_start = data_end.next_multiple_of(32);
@@ -574,6 +605,122 @@ impl<'a> Module<'a> {
         Ok(())
     }
 
+    /// Returns the boundaries of a section given by the hints, or else the ones found by the analysis.
+    fn hinted_range(&self, section_name: &str, start: u32, end: u32) -> (u32, u32) {
+        match self.hints.section(section_name) {
+            Some(range) => {
+                log::debug!("Using {section_name} boundaries {:#010x}..{:#010x} from hints", range.start, range.end);
+                (range.start, range.end)
+            }
+            None => (start, end),
+        }
+    }
+
+    fn add_not_pointers(&mut self, options: &AnalysisOptions) {
+        for &address in options.not_pointers.get(&self.kind).into_iter().flatten() {
+            self.relocations.add_not_pointer(address);
+        }
+    }
+
+    /// Names symbols as given by the hints, adding data symbols where there are none.
+    fn apply_name_hints(&self, symbol_map: &mut SymbolMap) -> Result<(), ModuleError> {
+        for (&address, name) in &self.hints.names {
+            if symbol_map.for_address(address).is_some() {
+                symbol_map.rename_by_address(address, name)?;
+                continue;
+            }
+            match self.sections.get_by_contained_address(address).map(|(_, section)| section.kind()) {
+                Some(SectionKind::Data) => {
+                    symbol_map.add_data(Some(name.clone()), address, SymData::Any)?;
+                }
+                Some(SectionKind::Bss) => {
+                    symbol_map.add_bss(Some(name.clone()), address, SymBss { size: None })?;
+                }
+                Some(SectionKind::Code) | None => {
+                    log::warn!("No symbol at {address:#010x} in {} to name '{name}'", self.kind);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds and removes relocations as given by the hints. Must be called after all relocations have been found, so that
+    /// the hints replace what the analysis found. Returns the relocations which were removed by `ignore_reloc` hints.
+    pub fn apply_relocation_hints(&mut self) -> Vec<Relocation> {
+        let ignored =
+            self.hints.ignored_relocations.iter().filter_map(|&from| self.relocations.remove(from)).collect::<Vec<_>>();
+        for relocation in &self.hints.relocations {
+            self.relocations.replace(relocation.clone());
+        }
+        ignored
+    }
+
+    /// Names SWI wrappers after the BIOS function they call, and adds wrappers which the function search missed if they are
+    /// called or placed right after a known function.
+    fn find_swi_wrappers(&mut self, symbol_map: &mut SymbolMap) -> Result<(), ModuleError> {
+        let mut wrappers = vec![];
+        for section in self.sections.iter().filter(|s| s.kind() == SectionKind::Code) {
+            let Some(code) = section.code(self.code, self.base_address)? else { continue };
+            wrappers.extend(Function::find_swi_wrappers(code, section.start_address(), true));
+            wrappers.extend(Function::find_swi_wrappers(code, section.start_address(), false));
+        }
+
+        for wrapper in wrappers {
+            let address = wrapper.start();
+            let existing = symbol_map
+                .for_address(address)
+                .and_then(|mut symbols| symbols.find(|(_, s)| matches!(s.kind, SymbolKind::Function(_))))
+                .map(|(_, symbol)| symbol.name.starts_with(&self.default_func_prefix));
+            match existing {
+                Some(true) => {
+                    let name = self.swi_wrapper_name(symbol_map, &wrapper);
+                    symbol_map.rename_by_address(address, &name)?;
+                }
+                Some(false) => {}
+                None => {
+                    if symbol_map.get_function_containing(address).is_some() {
+                        // Part of a larger function
+                        continue;
+                    }
+                    if !self.is_known_function_boundary(address) {
+                        // An SWI instruction followed by a return may just as well be data
+                        log::debug!(
+                            "Ignoring possible {} wrapper at {address:#010x} in {} as it is neither called nor placed after a \
+                             known function",
+                            wrapper.name(),
+                            self.kind
+                        );
+                        continue;
+                    }
+                    let name = self.swi_wrapper_name(symbol_map, &wrapper);
+                    let function = Function::from_swi_wrapper(name, &wrapper);
+                    symbol_map.add_function(&function);
+                    self.sections.add_function(function);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether a known function calls `address` or ends right before it.
+    fn is_known_function_boundary(&self, address: u32) -> bool {
+        self.sections.functions().any(|function| {
+            let end = function.end_address();
+            (end..=end.next_multiple_of(4)).contains(&address)
+                || function.function_calls().values().any(|called| called.address & !1 == address)
+        })
+    }
+
+    /// Wrappers outside of the main module get an address suffix, since every module may have its own copy.
+    fn swi_wrapper_name(&self, symbol_map: &SymbolMap, wrapper: &SecureAreaFunction) -> String {
+        let name = wrapper.name();
+        if self.kind == ModuleKind::Arm9 && symbol_map.for_name(name).is_none() {
+            name.to_string()
+        } else {
+            format!("{name}_{:08x}", wrapper.start())
+        }
+    }
+
     fn find_data_from_pools(&mut self, symbol_map: &mut SymbolMap, options: &AnalysisOptions) -> Result<(), ModuleError> {
         for function in self.sections.functions() {
             data::find_local_data_from_pools(
@@ -750,4 +897,8 @@ pub struct AnalysisOptions {
     pub allow_unknown_function_calls: bool,
     /// If true, every relocation in relocs.txt will have a comment explaining where/why it was generated.
     pub provide_reloc_source: bool,
+    /// Words which have been marked as not being pointers, per module.
+    pub not_pointers: BTreeMap<ModuleKind, BTreeSet<u32>>,
+    /// Corrections to the analysis, per module.
+    pub hints: BTreeMap<ModuleKind, Hints>,
 }
@@ -1,6 +1,6 @@
 use std::{
     backtrace::Backtrace,
-    collections::{btree_map, BTreeMap},
+    collections::{btree_map, BTreeMap, BTreeSet, HashSet},
     fmt::Display,
     io::{self, BufRead, BufReader, BufWriter, Write},
     iter,
@@ -10,6 +10,7 @@ use std::{
 };
 
 use ds_rom::rom::raw::AutoloadKind;
+use object::elf::{R_ARM_ABS32, R_ARM_PC24, R_ARM_THM_PC22, R_ARM_XPC25};
 use snafu::Snafu;
 
 use crate::util::{
@@ -20,11 +21,16 @@ use crate::util::{
 use super::{
     iter_attributes,
     module::{Module, ModuleKind},
+    source::SourceLines,
     ParseContext,
 };
 
 pub struct Relocations {
     relocations: BTreeMap<u32, Relocation>,
+    /// Addresses of words which look like pointers but are not, so analysis must not relocate them
+    not_pointers: BTreeSet<u32>,
+    /// Lines of the loaded relocs.txt keyed by "from" address, to preserve comments and ordering when writing it back
+    source: SourceLines<u32>,
 }
 
 #[derive(Debug, Snafu)]
@@ -35,6 +41,8 @@ pub enum RelocationsParseError {
     Io { source: io::Error },
     #[snafu(transparent)]
     RelocationParse { source: RelocationParseError },
+    #[snafu(display("{context}: duplicate relocation from {from:#010x}:\n{backtrace}"))]
+    DuplicateFrom { context: ParseContext, from: u32, backtrace: Backtrace },
 }
 
 #[derive(Debug, Snafu)]
@@ -53,7 +61,7 @@ pub enum RelocationsError {
 
 impl Relocations {
     pub fn new() -> Self {
-        Self { relocations: BTreeMap::new() }
+        Self { relocations: BTreeMap::new(), not_pointers: BTreeSet::new(), source: SourceLines::new() }
     }
 
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, RelocationsParseError> {
@@ -64,20 +72,35 @@ impl Relocations {
         let reader = BufReader::new(file);
 
         let mut relocations = BTreeMap::new();
+        let mut not_pointers = BTreeSet::new();
+        let mut source = SourceLines::new();
         for line in reader.lines() {
             context.row += 1;
 
-            let line = line?;
-            let comment_start = line.find("//").unwrap_or(line.len());
-            let line = &line[..comment_start];
+            let text = line?;
+            let comment_start = text.find("//").unwrap_or(text.len());
+            let line = &text[..comment_start];
 
+            if let Some(address) = parse_not_pointer(line, &context)? {
+                if relocations.contains_key(&address) || not_pointers.contains(&address) {
+                    return DuplicateFromSnafu { context, from: address }.fail();
+                }
+                source.push_entry(text, address, not_pointer_display(address));
+                not_pointers.insert(address);
+                continue;
+            }
             let Some(relocation) = Relocation::parse(line, &context)? else {
+                source.push_text(text);
                 continue;
             };
+            if relocations.contains_key(&relocation.from) || not_pointers.contains(&relocation.from) {
+                return DuplicateFromSnafu { context, from: relocation.from }.fail();
+            }
+            source.push_entry(text, relocation.from, relocation.to_string());
             relocations.insert(relocation.from, relocation);
         }
 
-        Ok(Self { relocations })
+        Ok(Self { relocations, not_pointers, source })
     }
 
     pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), RelocationsWriteError> {
@@ -86,13 +109,40 @@ impl Relocations {
         let file = create_file(path)?;
         let mut writer = BufWriter::new(file);
 
-        for relocation in self.relocations.values() {
-            writeln!(writer, "{relocation}")?;
-        }
+        let source_addresses = self.source.keys().copied().collect::<HashSet<_>>();
+        let mut new_lines = self
+            .relocations
+            .values()
+            .filter(|relocation| !source_addresses.contains(&relocation.from))
+            .map(|relocation| (relocation.from, relocation.to_string()))
+            .chain(
+                self.not_pointers
+                    .iter()
+                    .filter(|address| !source_addresses.contains(address))
+                    .map(|&address| (address, not_pointer_display(address))),
+            )
+            .collect::<Vec<_>>();
+        new_lines.sort_by_key(|&(address, _)| address);
+
+        self.source.write_sorted(
+            &mut writer,
+            |from| {
+                if let Some(relocation) = self.relocations.get(from) {
+                    Some((relocation.from, relocation.to_string()))
+                } else {
+                    self.not_pointers.contains(from).then(|| (*from, not_pointer_display(*from)))
+                }
+            },
+            new_lines,
+        )?;
         Ok(())
     }
 
+    /// Adds a relocation, replacing any not-a-pointer mark at the same address. Fails if a different relocation exists.
     pub fn add(&mut self, relocation: Relocation) -> Result<&mut Relocation, RelocationsError> {
+        if self.not_pointers.remove(&relocation.from) {
+            log::warn!("Relocation from {:#010x} replaces a not_pointer mark at the same address", relocation.from);
+        }
         match self.relocations.entry(relocation.from) {
             btree_map::Entry::Vacant(entry) => Ok(entry.insert(relocation)),
             btree_map::Entry::Occupied(entry) => {
@@ -142,20 +192,70 @@ impl Relocations {
         self.add(Relocation::new_load(from, to, addend, module))
     }
 
+    /// Adds a relocation, replacing any existing relocation or not-a-pointer mark at the same address.
+    pub fn replace(&mut self, relocation: Relocation) -> &mut Relocation {
+        self.not_pointers.remove(&relocation.from);
+        let from = relocation.from;
+        self.relocations.insert(from, relocation);
+        self.relocations.get_mut(&from).unwrap()
+    }
+
+    pub fn remove(&mut self, from: u32) -> Option<Relocation> {
+        self.relocations.remove(&from)
+    }
+
     pub fn get(&self, from: u32) -> Option<&Relocation> {
         self.relocations.get(&from)
     }
 
+    pub fn get_mut(&mut self, from: u32) -> Option<&mut Relocation> {
+        self.relocations.get_mut(&from)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &Relocation> {
         self.relocations.values()
     }
 
+    /// Marks the word at `address` as not being a pointer. Any relocation from it is removed.
+    pub fn add_not_pointer(&mut self, address: u32) {
+        self.relocations.remove(&address);
+        self.not_pointers.insert(address);
+    }
+
+    pub fn is_not_pointer(&self, address: u32) -> bool {
+        self.not_pointers.contains(&address)
+    }
+
+    pub fn not_pointers(&self) -> impl Iterator<Item = u32> + '_ {
+        self.not_pointers.iter().copied()
+    }
+
     pub fn iter_range(&self, range: Range<u32>) -> impl Iterator<Item = (&u32, &Relocation)> {
         self.relocations.range(range)
     }
 }
 
-#[derive(PartialEq, Eq)]
+/// Parses a line marking a word as not being a pointer, formatted as `from:FROM not_pointer`.
+pub fn parse_not_pointer(line: &str, context: &ParseContext) -> Result<Option<u32>, RelocationParseError> {
+    let mut from = None;
+    for (key, value) in iter_attributes(line.split_whitespace()) {
+        match key {
+            "from" => from = Some(value),
+            "not_pointer" => {}
+            _ => return Ok(None),
+        }
+    }
+    let Some(value) = from.filter(|_| line.split_whitespace().any(|word| word == "not_pointer")) else {
+        return Ok(None);
+    };
+    Ok(Some(parse_u32(value).map_err(|error| ParseFromSnafu { context, value, error }.build())?))
+}
+
+fn not_pointer_display(address: u32) -> String {
+    format!("from:{address:#010x} not_pointer")
+}
+
+#[derive(Clone, PartialEq, Eq)]
 pub struct Relocation {
     from: u32,
     to: u32,
@@ -186,8 +286,11 @@ pub enum RelocationParseError {
 }
 
 impl Relocation {
-    fn parse(line: &str, context: &ParseContext) -> Result<Option<Self>, RelocationParseError> {
+    pub fn parse(line: &str, context: &ParseContext) -> Result<Option<Self>, RelocationParseError> {
         let words = line.split_whitespace();
+        if line.split_whitespace().next().is_none() {
+            return Ok(None);
+        }
 
         let mut from = None;
         let mut to = None;
@@ -253,6 +356,10 @@ impl Relocation {
         &self.module
     }
 
+    pub fn set_module(&mut self, module: RelocationModule) {
+        self.module = module;
+    }
+
     pub fn addend(&self) -> i64 {
         self.addend as i64 + self.kind.addend()
     }
@@ -264,7 +371,13 @@ impl Relocation {
 
 impl Display for Relocation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "from:{:#010x} kind:{} to:{:#010x} module:{}", self.from, self.kind, self.to, self.module)?;
+        write!(f, "from:{:#010x} kind:{} to:{:#010x}", self.from, self.kind, self.to)?;
+        if self.addend > 0 {
+            write!(f, " add:{:#x}", self.addend)?;
+        } else if self.addend < 0 {
+            write!(f, " add:-{:#x}", self.addend.unsigned_abs())?;
+        }
+        write!(f, " module:{}", self.module)?;
         if let Some(source) = &self.source {
             write!(f, " // {source}")?;
         }
@@ -311,6 +424,30 @@ impl RelocationKind {
             Self::Load => 0,
         }
     }
+
+    pub fn as_obj_symbol_kind(&self) -> object::SymbolKind {
+        match self {
+            Self::ArmCall => object::SymbolKind::Text,
+            Self::ThumbCall => object::SymbolKind::Text,
+            Self::ArmCallThumb => object::SymbolKind::Text,
+            Self::ThumbCallArm => object::SymbolKind::Text,
+            Self::ArmBranch => object::SymbolKind::Text,
+            Self::Load => object::SymbolKind::Data,
+        }
+    }
+
+    pub fn as_elf_relocation_type(&self) -> u32 {
+        match self {
+            Self::ArmCall => R_ARM_PC24,
+            Self::ThumbCall => R_ARM_THM_PC22,
+            Self::ArmCallThumb => R_ARM_XPC25,
+            // Bug in mwld thinks that the range of XPC22 is only +-2MB, but it should be +-4MB. Fortunately we can use PC22 as
+            // it has the correct range, and the linker resolves BL instructions to BLX automatically anyway.
+            Self::ThumbCallArm => R_ARM_THM_PC22,
+            Self::ArmBranch => R_ARM_PC24,
+            Self::Load => R_ARM_ABS32,
+        }
+    }
 }
 
 impl Display for RelocationKind {
@@ -412,7 +549,7 @@ impl RelocationModule {
         }
     }
 
-    fn parse(text: &str, context: &ParseContext) -> Result<Self, Box<RelocationModuleParseError>> {
+    pub fn parse(text: &str, context: &ParseContext) -> Result<Self, Box<RelocationModuleParseError>> {
         let (value, options) = text.split_once('(').unwrap_or((text, ""));
         let options = options.strip_suffix(')').unwrap_or(options);
 
@@ -462,6 +599,30 @@ impl RelocationModule {
             _ => Err(Box::new(UnknownModuleSnafu { context, module: value }.build())),
         }
     }
+
+    /// Returns the first (and possibly only) module this relocation is pointing to.
+    pub fn first_module(&self) -> Option<ModuleKind> {
+        match self {
+            RelocationModule::None => None,
+            RelocationModule::Overlays { ids } => Some(ModuleKind::Overlay(*ids.first().unwrap())),
+            RelocationModule::Overlay { id } => Some(ModuleKind::Overlay(*id)),
+            RelocationModule::Main => Some(ModuleKind::Arm9),
+            RelocationModule::Itcm => Some(ModuleKind::Autoload(AutoloadKind::Itcm)),
+            RelocationModule::Dtcm => Some(ModuleKind::Autoload(AutoloadKind::Dtcm)),
+        }
+    }
+
+    /// Returns all modules other than the first that this relocation is pointing to.
+    pub fn other_modules(&self) -> Option<impl Iterator<Item = ModuleKind> + '_> {
+        match self {
+            RelocationModule::Overlays { ids } => Some(ids[1..].iter().map(|&id| ModuleKind::Overlay(id))),
+            RelocationModule::None => None,
+            RelocationModule::Overlay { .. } => None,
+            RelocationModule::Main => None,
+            RelocationModule::Itcm => None,
+            RelocationModule::Dtcm => None,
+        }
+    }
 }
 
 impl TryFrom<ModuleKind> for RelocationModule {
@@ -13,7 +13,12 @@ use crate::{
     util::{bytes::FromSlice, parse::parse_u32},
 };
 
-use super::{iter_attributes, module::Module, ParseContext};
+use super::{
+    iter_attributes,
+    module::Module,
+    relocations::{Relocation, RelocationKind},
+    ParseContext,
+};
 
 #[derive(Clone, Copy)]
 pub struct SectionIndex(pub usize);
@@ -125,7 +130,7 @@ impl Section {
         })
     }
 
-    pub(crate) fn parse(line: &str, context: &ParseContext) -> Result<Option<Self>, SectionParseError> {
+    pub fn parse(line: &str, context: &ParseContext) -> Result<Option<Self>, SectionParseError> {
         let mut words = line.split_whitespace();
         let Some(name) = words.next() else { return Ok(None) };
 
@@ -159,7 +164,7 @@ impl Section {
         ))
     }
 
-    pub(crate) fn parse_inherit(
+    pub fn parse_inherit(
         line: &str,
         context: &ParseContext,
         sections: &Sections,
@@ -212,6 +217,53 @@ impl Section {
         Ok(Some(&code[start as usize..end as usize]))
     }
 
+    /// Returns the code of this section with every relocated instruction and pointer cleared, so that the linker only sees
+    /// the implicit addend of each relocation.
+    pub fn relocatable_code(&self, module: &Module) -> Result<Option<Vec<u8>>, SectionCodeError> {
+        let Some(code) = self.code_from_module(module)? else { return Ok(None) };
+        let mut code = code.to_vec();
+
+        for relocation in self.relocations(module) {
+            let from = relocation.from_address();
+            let offset = (from - self.start_address()) as usize;
+
+            // Clear bits in `code` to treat them as the implicit addend
+            let ins = match relocation.kind() {
+                RelocationKind::ArmCall => {
+                    // R_ARM_PC24
+                    &[0xfe, 0xff, 0xff, 0xeb] // bl #0
+                }
+                RelocationKind::ArmCallThumb => {
+                    // R_ARM_XPC25
+                    &[0xfe, 0xff, 0xff, 0xfa] // blx #0
+                }
+                RelocationKind::ThumbCall => {
+                    // R_ARM_THM_PC22
+                    &[0xff, 0xf7, 0xfe, 0xff] // bl #0
+                }
+                RelocationKind::ThumbCallArm => {
+                    // R_ARM_THM_XPC22
+                    &[0xff, 0xf7, 0xfe, 0xff] // bl #0
+                }
+                RelocationKind::ArmBranch => {
+                    // R_ARM_PC24
+                    &[0xfe, 0xff, 0xff, 0xea] // b #0
+                }
+                RelocationKind::Load => {
+                    // R_ARM_ABS32
+                    &[0x00, 0x00, 0x00, 0x00]
+                }
+            };
+            code[offset..offset + 4].copy_from_slice(ins);
+        }
+
+        Ok(Some(code))
+    }
+
+    pub fn relocations<'a>(&'a self, module: &'a Module) -> impl Iterator<Item = &'a Relocation> {
+        module.relocations().iter_range(self.address_range()).map(|(_, r)| r)
+    }
+
     pub fn size(&self) -> u32 {
         self.end_address - self.start_address
     }
@@ -254,6 +306,23 @@ impl Section {
         self.alignment
     }
 
+    /// Name of this section for creating section boundary symbols, e.g. ARM9_BSS_START
+    pub fn boundary_name(&self) -> String {
+        self.name().strip_prefix('.').unwrap_or(self.name()).to_uppercase()
+    }
+
+    /// Alignment of this section in a delinked object. This is the alignment from delinks.txt, lowered to what the start
+    /// address satisfies so that the linker never pads before the section.
+    pub fn object_alignment(&self) -> u32 {
+        let start_alignment = 1u32.checked_shl(self.start_address().trailing_zeros()).unwrap_or(u32::MAX);
+        self.alignment().min(start_alignment)
+    }
+
+    /// Whether this section contains read-only data.
+    pub fn is_read_only(&self) -> bool {
+        self.kind() == SectionKind::Data && self.name() == ".rodata"
+    }
+
     pub fn overlaps_with(&self, other: &Section) -> bool {
         self.start_address < other.end_address && other.start_address < self.end_address
     }
@@ -0,0 +1,202 @@
+use std::io::{self, Write};
+
+/// The original lines of a config file. Used to write the file back without losing comments, blank lines or the order of
+/// entries, while only rewriting the lines whose entries have changed.
+#[derive(Clone)]
+pub(crate) struct SourceLines<K> {
+    lines: Vec<SourceLine<K>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct SourceLine<K> {
+    text: String,
+    /// Key of the entry parsed from this line, and what that entry looked like when it was parsed
+    entry: Option<(K, String)>,
+}
+
+impl<K> SourceLines<K> {
+    pub fn new() -> Self {
+        Self { lines: vec![] }
+    }
+
+    /// Adds a line which has no entry, such as a blank line or a comment.
+    pub fn push_text(&mut self, text: String) {
+        self.lines.push(SourceLine { text, entry: None });
+    }
+
+    /// Adds a line containing an entry. `display` is how the entry is written, and is later compared to the entry's current
+    /// state to determine whether the line has changed.
+    pub fn push_entry(&mut self, text: String, key: K, display: String) {
+        self.lines.push(SourceLine { text, entry: Some((key, display)) });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SourceLine<K>> {
+        self.lines.iter()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.lines.iter().filter_map(|line| line.entry.as_ref().map(|(key, _)| key))
+    }
+
+    /// Writes the lines back, for entries sorted by address. `current` returns the address and current display of an entry,
+    /// or `None` if it was removed. `new_lines` are entries which don't exist in the source and must be sorted by address.
+    /// They are written before the first entry of a higher address.
+    pub fn write_sorted<W, F, I>(&self, writer: &mut W, mut current: F, new_lines: I) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(&K) -> Option<(u32, String)>,
+        I: IntoIterator<Item = (u32, String)>,
+    {
+        let mut new_lines = new_lines.into_iter().peekable();
+        for line in &self.lines {
+            let Some((key, _)) = &line.entry else {
+                line.write(writer, None)?;
+                continue;
+            };
+            let Some((address, display)) = current(key) else { continue };
+            while let Some((_, new_line)) = new_lines.next_if(|&(new_address, _)| new_address < address) {
+                writeln!(writer, "{new_line}")?;
+            }
+            line.write(writer, Some(&display))?;
+        }
+        for (_, new_line) in new_lines {
+            writeln!(writer, "{new_line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<K> SourceLine<K> {
+    pub fn key(&self) -> Option<&K> {
+        self.entry.as_ref().map(|(key, _)| key)
+    }
+
+    /// Writes this line. If the entry's display has changed, the line is rewritten with the same indentation and comment.
+    pub fn write<W: Write>(&self, writer: &mut W, display: Option<&str>) -> io::Result<()> {
+        let (Some((_, original)), Some(display)) = (&self.entry, display) else {
+            return writeln!(writer, "{}", self.text);
+        };
+        if display == original {
+            return writeln!(writer, "{}", self.text);
+        }
+
+        let indent = &self.text[..self.text.len() - self.text.trim_start().len()];
+        match self.text.find("//") {
+            Some(comment_start) => writeln!(writer, "{indent}{display} {}", &self.text[comment_start..]),
+            None => writeln!(writer, "{indent}{display}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use crate::config::{
+        delinks::Delinks,
+        module::ModuleKind,
+        relocations::{RelocationModule, Relocations},
+        symbol::SymbolMap,
+    };
+
+    const SYMBOLS: &str = "\
+// Functions
+func_02000000 kind:function(arm,size=0x10) addr:0x02000000 // entry
+
+    // Data
+data_02000100 kind:data(word) addr:0x02000100
+";
+
+    const RELOCATIONS: &str = "\
+// Calls
+from:0x02000000 kind:arm_call to:0x02000040 module:main // call
+
+    // Data
+from:0x02000100 not_pointer
+from:0x02000104 kind:load to:0x02000000 module:main
+";
+
+    const DELINKS: &str = "\
+// Main module
+    .text       start:0x02000000 end:0x02000100 kind:code align:32
+    .data       start:0x02000100 end:0x02000200 kind:data align:32 // data
+    compiler:mwcc_30_131
+
+// Files
+src/main.c: // entry
+    // options
+    complete
+    .text start:0x02000000 end:0x02000040
+
+src/data.c:
+    .data start:0x02000100 end:0x02000110
+";
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("dsd_source_{}_{name}", std::process::id()))
+    }
+
+    fn round_trip(name: &str, contents: &str, load_and_write: impl FnOnce(&PathBuf, &PathBuf)) {
+        let input = temp_path(&format!("in_{name}"));
+        let output = temp_path(&format!("out_{name}"));
+        fs::write(&input, contents).unwrap();
+        load_and_write(&input, &output);
+        let written = fs::read_to_string(&output).unwrap();
+        fs::remove_file(input).unwrap();
+        fs::remove_file(output).unwrap();
+        assert_eq!(written, contents, "{name}");
+    }
+
+    #[test]
+    fn test_symbols_round_trip() {
+        round_trip("symbols.txt", SYMBOLS, |input, output| {
+            SymbolMap::from_file(input).unwrap().to_file(output).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_relocations_round_trip() {
+        round_trip("relocs.txt", RELOCATIONS, |input, output| {
+            Relocations::from_file(input).unwrap().to_file(output).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_delinks_round_trip() {
+        round_trip("delinks.txt", DELINKS, |input, output| {
+            Delinks::from_file(input, ModuleKind::Arm9).unwrap().write_to_file(output).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_relocation_replaces_not_pointer() {
+        let input = temp_path("in_not_pointer_relocs.txt");
+        let output = temp_path("out_not_pointer_relocs.txt");
+        fs::write(&input, RELOCATIONS).unwrap();
+        let mut relocations = Relocations::from_file(&input).unwrap();
+        relocations.add_load(0x02000100, 0x02000000, 0, RelocationModule::Main).unwrap();
+        relocations.to_file(&output).unwrap();
+        let written = fs::read_to_string(&output).unwrap();
+        let reloaded = Relocations::from_file(&output);
+        fs::remove_file(input).unwrap();
+        fs::remove_file(output).unwrap();
+
+        let expected =
+            RELOCATIONS.replace("from:0x02000100 not_pointer", "from:0x02000100 kind:load to:0x02000000 module:main");
+        assert_eq!(written, expected);
+        assert!(!reloaded.unwrap().is_not_pointer(0x02000100));
+    }
+
+    #[test]
+    fn test_duplicate_relocation() {
+        let path = temp_path("duplicate_relocs.txt");
+        fs::write(&path, "from:0x02000000 not_pointer\nfrom:0x02000000 kind:load to:0x02000100 module:main\n").unwrap();
+        let result = Relocations::from_file(&path);
+        fs::remove_file(path).unwrap();
+        assert!(result.is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     backtrace::Backtrace,
-    collections::{btree_map, hash_map, BTreeMap, HashMap},
+    collections::{btree_map, hash_map, BTreeMap, HashMap, HashSet},
     fmt::Display,
     io::{self, BufRead, BufReader, BufWriter, Write},
     num::ParseIntError,
@@ -19,7 +19,7 @@ use crate::{
     },
 };
 
-use super::{config::Config, iter_attributes, module::ModuleKind, ParseContext};
+use super::{config::Config, iter_attributes, module::ModuleKind, source::SourceLines, ParseContext};
 
 pub struct SymbolMaps {
     symbol_maps: Vec<SymbolMap>,
@@ -94,13 +94,15 @@ impl SymbolMaps {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SymbolIndex(usize);
 
 pub struct SymbolMap {
     symbols: Vec<Symbol>,
     symbols_by_address: BTreeMap<u32, Vec<SymbolIndex>>,
     symbols_by_name: HashMap<String, Vec<SymbolIndex>>,
+    /// Lines of the loaded symbols.txt, to preserve comments and ordering when writing it back
+    source: SourceLines<SymbolIndex>,
 }
 
 #[derive(Debug, Snafu)]
@@ -147,7 +149,7 @@ impl SymbolMap {
             symbols_by_name.entry(symbol.name.clone()).or_default().push(SymbolIndex(index));
         }
 
-        Self { symbols, symbols_by_address, symbols_by_name }
+        Self { symbols, symbols_by_address, symbols_by_name, source: SourceLines::new() }
     }
 
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, SymbolMapParseError> {
@@ -166,12 +168,17 @@ impl SymbolMap {
         for line in reader.lines() {
             context.row += 1;
 
-            let line = line?;
-            let comment_start = line.find("//").unwrap_or(line.len());
-            let line = &line[..comment_start];
-
-            let Some(symbol) = Symbol::parse(line, &context)? else { continue };
-            self.add(symbol);
+            let text = line?;
+            let comment_start = text.find("//").unwrap_or(text.len());
+            let line = &text[..comment_start];
+
+            let Some(symbol) = Symbol::parse(line, &context)? else {
+                self.source.push_text(text);
+                continue;
+            };
+            let display = symbol.to_string();
+            let (index, _) = self.add(symbol);
+            self.source.push_entry(text, index, display);
         }
         Ok(())
     }
@@ -182,14 +189,25 @@ impl SymbolMap {
         let file = create_file(path)?;
         let mut writer = BufWriter::new(file);
 
-        for indices in self.symbols_by_address.values() {
-            for &index in indices {
+        let source_indices = self.source.keys().copied().collect::<HashSet<_>>();
+        let new_lines = self
+            .symbols_by_address
+            .values()
+            .flatten()
+            .filter(|index| !source_indices.contains(index))
+            .map(|index| &self.symbols[index.0])
+            .filter(|symbol| symbol.should_write())
+            .map(|symbol| (symbol.addr, symbol.to_string()));
+
+        // Symbols which were loaded from the file are kept even if they wouldn't be written otherwise
+        self.source.write_sorted(
+            &mut writer,
+            |index| {
                 let symbol = &self.symbols[index.0];
-                if symbol.should_write() {
-                    writeln!(writer, "{symbol}")?;
-                }
-            }
-        }
+                Some((symbol.addr, symbol.to_string()))
+            },
+            new_lines,
+        )?;
 
         Ok(())
     }
@@ -225,7 +243,12 @@ impl SymbolMap {
     }
 
     pub fn iter_by_address(&self, range: Range<u32>) -> SymbolIterator {
-        SymbolIterator { symbols_by_address: self.symbols_by_address.range(range), indices: [].iter(), symbols: &self.symbols }
+        SymbolIterator {
+            symbols_by_address: self.symbols_by_address.range(range),
+            indices: [].iter(),
+            back_indices: [].iter(),
+            symbols: &self.symbols,
+        }
     }
 
     pub fn add(&mut self, symbol: Symbol) -> (SymbolIndex, &Symbol) {
@@ -417,6 +440,12 @@ impl SymbolMap {
         ensure!(symbol_indices.len() == 1, RenameMultipleSnafu { address, new_name });
 
         let symbol_index = symbol_indices[0];
+        self.rename(symbol_index, new_name);
+        Ok(())
+    }
+
+    /// Renames a symbol, keeping its kind and address.
+    pub fn rename(&mut self, symbol_index: SymbolIndex, new_name: &str) {
         let name = &self.symbols[symbol_index.0].name;
 
         match self.symbols_by_name.entry(name.clone()) {
@@ -445,14 +474,18 @@ impl SymbolMap {
         }
 
         self.symbols[symbol_index.0].name = new_name.to_string();
+    }
 
-        Ok(())
+    /// Changes the kind of a symbol, keeping its name and address.
+    pub fn set_kind(&mut self, index: SymbolIndex, kind: SymbolKind) {
+        self.symbols[index.0].kind = kind;
     }
 }
 
 pub struct SymbolIterator<'a> {
     symbols_by_address: btree_map::Range<'a, u32, Vec<SymbolIndex>>,
     indices: slice::Iter<'a, SymbolIndex>,
+    back_indices: slice::Iter<'a, SymbolIndex>,
     symbols: &'a [Symbol],
 }
 
@@ -466,7 +499,20 @@ impl<'a> Iterator for SymbolIterator<'a> {
             self.indices = indices.iter();
             self.next()
         } else {
-            None
+            self.back_indices.next().map(|&index| &self.symbols[index.0])
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for SymbolIterator<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if let Some(&index) = self.back_indices.next_back() {
+            Some(&self.symbols[index.0])
+        } else if let Some((_, indices)) = self.symbols_by_address.next_back() {
+            self.back_indices = indices.iter();
+            self.next_back()
+        } else {
+            self.indices.next_back().map(|&index| &self.symbols[index.0])
         }
     }
 }
@@ -520,7 +566,7 @@ pub enum SymbolParseError {
 }
 
 impl Symbol {
-    fn parse(line: &str, context: &ParseContext) -> Result<Option<Self>, SymbolParseError> {
+    pub fn parse(line: &str, context: &ParseContext) -> Result<Option<Self>, SymbolParseError> {
         let mut words = line.split_whitespace();
         let Some(name) = words.next() else { return Ok(None) };
 
@@ -545,7 +591,7 @@ impl Symbol {
         Ok(Some(Symbol { name, kind, addr, ambiguous, local }))
     }
 
-    fn should_write(&self) -> bool {
+    pub fn should_write(&self) -> bool {
         self.kind.should_write()
     }
 
@@ -556,6 +602,7 @@ impl Symbol {
                 mode: InstructionMode::from_thumb(function.is_thumb()),
                 size: function.size(),
                 unknown: false,
+                veneer: function.veneer().is_some(),
             }),
             addr: function.first_instruction_address() & !1,
             ambiguous: false,
@@ -566,7 +613,12 @@ impl Symbol {
     pub fn new_unknown_function(name: String, addr: u32, thumb: bool) -> Self {
         Self {
             name,
-            kind: SymbolKind::Function(SymFunction { mode: InstructionMode::from_thumb(thumb), size: 0, unknown: true }),
+            kind: SymbolKind::Function(SymFunction {
+                mode: InstructionMode::from_thumb(thumb),
+                size: 0,
+                unknown: true,
+                veneer: false,
+            }),
             addr,
             ambiguous: false,
             local: false,
@@ -612,6 +664,34 @@ impl Symbol {
     pub fn size(&self, max_address: u32) -> u32 {
         self.kind.size(max_address - self.addr)
     }
+
+    pub fn mapping_symbol_name(&self) -> Option<&str> {
+        match self.kind {
+            SymbolKind::Undefined => None,
+            SymbolKind::Function(SymFunction { mode, .. }) | SymbolKind::Label(SymLabel { mode, .. }) => match mode {
+                InstructionMode::Arm => Some("$a"),
+                InstructionMode::Thumb => Some("$t"),
+            },
+            SymbolKind::PoolConstant => Some("$d"),
+            SymbolKind::JumpTable(jump_table) => {
+                if jump_table.code {
+                    Some("$a")
+                } else {
+                    Some("$d")
+                }
+            }
+            SymbolKind::Data(_) => Some("$d"),
+            SymbolKind::Bss(_) => None,
+        }
+    }
+
+    pub fn get_obj_symbol_scope(&self) -> object::SymbolScope {
+        if self.local {
+            object::SymbolScope::Compilation
+        } else {
+            object::SymbolScope::Dynamic
+        }
+    }
 }
 
 impl Display for Symbol {
@@ -689,6 +769,30 @@ impl SymbolKind {
             SymbolKind::Bss(bss) => bss.size.unwrap_or(max_size),
         }
     }
+
+    pub fn as_obj_symbol_kind(&self) -> object::SymbolKind {
+        match self {
+            Self::Undefined => object::SymbolKind::Unknown,
+            Self::Function(_) => object::SymbolKind::Text,
+            Self::Label { .. } => object::SymbolKind::Label,
+            Self::PoolConstant => object::SymbolKind::Data,
+            Self::JumpTable(_) => object::SymbolKind::Label,
+            Self::Data(_) => object::SymbolKind::Data,
+            Self::Bss(_) => object::SymbolKind::Data,
+        }
+    }
+
+    pub fn as_elf_symbol_type(&self) -> u8 {
+        match self {
+            Self::Undefined => object::elf::STT_NOTYPE,
+            Self::Function(_) => object::elf::STT_FUNC,
+            Self::Label { .. } => object::elf::STT_NOTYPE,
+            Self::PoolConstant => object::elf::STT_OBJECT,
+            Self::JumpTable(_) => object::elf::STT_NOTYPE,
+            Self::Data(_) => object::elf::STT_OBJECT,
+            Self::Bss(_) => object::elf::STT_OBJECT,
+        }
+    }
 }
 
 impl Display for SymbolKind {
@@ -713,6 +817,9 @@ pub struct SymFunction {
     /// Is `true` for functions that were not found during function analysis, but are being called from somewhere. This can
     /// happen if the function is encrypted.
     pub unknown: bool,
+    /// Is `true` for stubs generated by the linker, see [`Veneer`](crate::analysis::veneer::Veneer). These are left out of delinked objects, as the linker
+    /// generates them again.
+    pub veneer: bool,
 }
 
 #[derive(Debug, Snafu)]
@@ -720,7 +827,7 @@ pub enum SymFunctionParseError {
     #[snafu(display("{context}: failed to parse size '{value}': {error}\n{backtrace}"))]
     ParseFunctionSize { context: ParseContext, value: String, error: ParseIntError, backtrace: Backtrace },
     #[snafu(display(
-        "{context}: unknown function attribute '{key}', must be one of: size, unknown, arm, thumb:\n{backtrace}"
+        "{context}: unknown function attribute '{key}', must be one of: size, unknown, veneer, arm, thumb:\n{backtrace}"
     ))]
     UnknownFunctionAttribute { context: ParseContext, key: String, backtrace: Backtrace },
     #[snafu(transparent)]
@@ -736,6 +843,7 @@ impl SymFunction {
         let mut size = None;
         let mut mode = None;
         let mut unknown = false;
+        let mut veneer = false;
         for option in options.split(',') {
             if let Some((key, value)) = option.split_once('=') {
                 match key {
@@ -748,6 +856,7 @@ impl SymFunction {
             } else {
                 match option {
                     "unknown" => unknown = true,
+                    "veneer" => veneer = true,
                     _ => mode = Some(InstructionMode::parse(option, context)?),
                 }
             }
@@ -757,6 +866,7 @@ impl SymFunction {
             mode: mode.ok_or_else(|| MissingInstructionModeSnafu { context }.build())?,
             size: size.ok_or_else(|| MissingFunctionAttributeSnafu { context, attribute: "size" }.build())?,
             unknown,
+            veneer,
         })
     }
 
@@ -778,6 +888,9 @@ impl Display for SymFunction {
         if self.unknown {
             write!(f, ",unknown")?;
         }
+        if self.veneer {
+            write!(f, ",veneer")?;
+        }
         Ok(())
     }
 }
@@ -822,7 +935,7 @@ pub enum InstructionModeParseError {
 }
 
 impl InstructionMode {
-    fn parse(value: &str, context: &ParseContext) -> Result<Self, InstructionModeParseError> {
+    pub(crate) fn parse(value: &str, context: &ParseContext) -> Result<Self, InstructionModeParseError> {
         match value {
             "arm" => Ok(Self::Arm),
             "thumb" => Ok(Self::Thumb),
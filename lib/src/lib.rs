@@ -1,3 +1,5 @@
 pub mod analysis;
 pub mod config;
-pub(crate) mod util;
+pub mod link;
+pub mod project;
+pub mod util;
@@ -0,0 +1,40 @@
+/// Where modules, sections and objects are placed by the linker. Mirrors the `MEMORY` and `SECTIONS` of an LCF.
+pub struct LinkLayout {
+    /// Modules in the order they are placed. Modules placed after other modules must come after them.
+    pub modules: Vec<LinkModule>,
+    /// Name of the entry function.
+    pub entry: String,
+}
+
+/// A module, which becomes one binary.
+pub struct LinkModule {
+    /// Memory name, e.g. `ARM9`, `ITCM` or `OV000`. Used as the ELF section name and prefix of boundary symbols.
+    pub name: String,
+    pub origin: LinkOrigin,
+    /// Sections in the order they are placed.
+    pub sections: Vec<LinkSection>,
+}
+
+pub enum LinkOrigin {
+    /// Starts at a fixed address.
+    Address(u32),
+    /// Starts after the end of every listed module, like `AFTER(...)` in an LCF.
+    After(Vec<String>),
+}
+
+/// A section of a module, such as `.text`, made of the sections with the same name in each of its objects.
+pub struct LinkSection {
+    pub name: String,
+    /// Name of the section in boundary symbols, e.g. `TEXT` in `ARM9_TEXT_START`.
+    pub boundary_name: String,
+    pub alignment: u32,
+    /// Indices of the objects to place in this section, in order.
+    pub objects: Vec<usize>,
+}
+
+/// An object file to link.
+pub struct LinkObject {
+    /// Name of the object for error messages, usually its path.
+    pub name: String,
+    pub data: Vec<u8>,
+}
@@ -0,0 +1,450 @@
+use std::{
+    backtrace::Backtrace,
+    collections::{BTreeMap, HashMap},
+};
+
+use object::{
+    elf,
+    write::{
+        elf::{FileHeader, SectionHeader, SectionIndex, Sym, Writer},
+        StringId,
+    },
+    Endianness, Object, ObjectSection, ObjectSymbol, SymbolKind, SymbolSection,
+};
+use snafu::Snafu;
+
+use super::{
+    layout::{LinkLayout, LinkObject, LinkOrigin},
+    relocate::{implicit_addend, relocate, RelocateError, RelocationTarget},
+};
+
+#[derive(Debug, Snafu)]
+pub enum LinkError {
+    #[snafu(transparent)]
+    ObjectRead { source: object::read::Error },
+    #[snafu(transparent)]
+    ObjectWrite { source: object::write::Error },
+    #[snafu(display("module {module} is placed after {after} which has not been placed yet:\n{backtrace}"))]
+    ModuleOrder { module: String, after: String, backtrace: Backtrace },
+    #[snafu(display("{object} has data in section {section} which is placed after .bss in module {module}:\n{backtrace}"))]
+    DataAfterBss { object: String, section: String, module: String, backtrace: Backtrace },
+    #[snafu(display("symbol '{name}' is defined in both {first} and {second}:\n{backtrace}"))]
+    DuplicateSymbol { name: String, first: String, second: String, backtrace: Backtrace },
+    #[snafu(display("undefined symbol '{name}' in {object}:\n{backtrace}"))]
+    UndefinedSymbol { name: String, object: String, backtrace: Backtrace },
+    #[snafu(display("{object} has a relocation to a symbol in a section which is not in the layout:\n{backtrace}"))]
+    UnplacedSymbol { object: String, backtrace: Backtrace },
+    #[snafu(display("{object} has an unsupported relocation at offset {offset:#x} of section {section}:\n{backtrace}"))]
+    UnsupportedRelocation { object: String, section: String, offset: u64, backtrace: Backtrace },
+    #[snafu(display("{object}: {error}"))]
+    Relocate { object: String, error: RelocateError },
+    #[snafu(display("entry function '{name}' not found:\n{backtrace}"))]
+    EntryNotFound { name: String, backtrace: Backtrace },
+}
+
+/// The result of linking, with one binary per module.
+pub struct LinkOutput {
+    pub modules: Vec<LinkedModule>,
+    pub symbols: Vec<LinkedSymbol>,
+    pub entry: u32,
+}
+
+pub struct LinkedModule {
+    pub name: String,
+    pub base_address: u32,
+    /// Initialized sections, without .bss
+    pub code: Vec<u8>,
+    pub bss_size: u32,
+}
+
+pub struct LinkedSymbol {
+    pub name: String,
+    pub address: u32,
+    pub size: u32,
+    pub kind: LinkedSymbolKind,
+    pub local: bool,
+    /// Index of the module this symbol is in
+    pub module: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LinkedSymbolKind {
+    Function,
+    Object,
+    None,
+}
+
+/// Where a section of an object was placed
+#[derive(Clone, Copy)]
+struct Placement {
+    address: u32,
+    module: usize,
+}
+
+/// A defined global symbol
+struct GlobalSymbol {
+    target: RelocationTarget,
+    /// Index of the object defining this symbol, or `None` for boundary symbols
+    object: Option<usize>,
+    weak: bool,
+}
+
+/// Links objects into module binaries, placing them like mwld would with an equivalent LCF.
+pub fn link(layout: &LinkLayout, objects: &[LinkObject]) -> Result<LinkOutput, LinkError> {
+    let files = objects.iter().map(|object| object::File::parse(&*object.data)).collect::<Result<Vec<_>, _>>()?;
+
+    let mut placements = HashMap::new();
+    let mut modules: Vec<LinkedModule> = vec![];
+    let mut boundary_symbols = vec![];
+    for (module_index, module) in layout.modules.iter().enumerate() {
+        let base_address = match &module.origin {
+            LinkOrigin::Address(address) => *address,
+            LinkOrigin::After(after) => {
+                let mut base_address = 0;
+                for name in after {
+                    let Some(other) = modules.iter().find(|other| &other.name == name) else {
+                        return ModuleOrderSnafu { module: module.name.clone(), after: name.clone() }.fail();
+                    };
+                    base_address = base_address.max(other.base_address + other.code.len() as u32 + other.bss_size);
+                }
+                base_address
+            }
+        };
+
+        let mut address = base_address;
+        let mut code = vec![];
+        let mut bss_start = None;
+        for section in &module.sections {
+            address = address.next_multiple_of(section.alignment.max(1));
+            boundary_symbols.push((format!("{}_{}_START", module.name, section.boundary_name), address, module_index));
+
+            // Objects may have several sections with the same name, which are placed in order
+            let obj_sections = section.objects.iter().flat_map(|&object_index| {
+                files[object_index]
+                    .sections()
+                    .filter(move |obj_section| obj_section.name().is_ok_and(|name| name == section.name))
+                    .map(move |obj_section| (object_index, obj_section))
+            });
+            for (object_index, obj_section) in obj_sections {
+                address = address.next_multiple_of((obj_section.align() as u32).max(1));
+                placements.insert((object_index, obj_section.index()), Placement { address, module: module_index });
+
+                let size = obj_section.size() as u32;
+                if obj_section.kind() == object::SectionKind::UninitializedData {
+                    if size > 0 {
+                        bss_start.get_or_insert(address);
+                    }
+                } else if size > 0 {
+                    if bss_start.is_some() {
+                        return DataAfterBssSnafu {
+                            object: objects[object_index].name.clone(),
+                            section: section.name.clone(),
+                            module: module.name.clone(),
+                        }
+                        .fail();
+                    }
+                    code.resize((address - base_address) as usize, 0);
+                    code.extend_from_slice(obj_section.data()?);
+                }
+                address += size;
+            }
+
+            boundary_symbols.push((format!("{}_{}_END", module.name, section.boundary_name), address, module_index));
+        }
+
+        let bss_size = address - base_address - code.len() as u32;
+        modules.push(LinkedModule { name: module.name.clone(), base_address, code, bss_size });
+    }
+
+    // Collect symbols
+    let mut globals: HashMap<String, GlobalSymbol> = HashMap::new();
+    let mut symbols = vec![];
+    for (name, address, module) in boundary_symbols {
+        let target = RelocationTarget { address, thumb: false };
+        globals.insert(name.clone(), GlobalSymbol { target, object: None, weak: false });
+        symbols.push(LinkedSymbol { name, address, size: 0, kind: LinkedSymbolKind::None, local: false, module });
+    }
+    let thumb_maps = files.iter().map(thumb_map).collect::<Vec<_>>();
+    for (object_index, file) in files.iter().enumerate() {
+        for symbol in file.symbols() {
+            let SymbolSection::Section(section_index) = symbol.section() else {
+                continue;
+            };
+            let name = symbol.name()?;
+            if name.is_empty() || name.starts_with('$') || matches!(symbol.kind(), SymbolKind::Section | SymbolKind::File) {
+                continue;
+            }
+            // Delinked objects have a local symbol with the same name as each section
+            if symbol.is_local() && name == file.section_by_index(section_index)?.name()? {
+                continue;
+            }
+            let Some(&placement) = placements.get(&(object_index, section_index)) else {
+                continue;
+            };
+            let target = defined_target(&symbol, placement, &thumb_maps[object_index]);
+
+            let kind = match symbol.kind() {
+                SymbolKind::Text => LinkedSymbolKind::Function,
+                SymbolKind::Data | SymbolKind::Unknown if symbol.size() > 0 => LinkedSymbolKind::Object,
+                _ => LinkedSymbolKind::None,
+            };
+            symbols.push(LinkedSymbol {
+                name: name.to_string(),
+                address: target.address,
+                size: symbol.size() as u32,
+                kind,
+                local: symbol.is_local(),
+                module: placement.module,
+            });
+
+            if symbol.is_local() {
+                continue;
+            }
+            let weak = symbol.is_weak();
+            if let Some(existing) = globals.get(name) {
+                if !weak && !existing.weak {
+                    return DuplicateSymbolSnafu {
+                        name,
+                        first: existing.object.map_or("the layout".to_string(), |index| objects[index].name.clone()),
+                        second: objects[object_index].name.clone(),
+                    }
+                    .fail();
+                }
+                if weak {
+                    continue;
+                }
+            }
+            globals.insert(name.to_string(), GlobalSymbol { target, object: Some(object_index), weak });
+        }
+    }
+
+    // Apply relocations
+    for (object_index, file) in files.iter().enumerate() {
+        let object_name = &objects[object_index].name;
+        for section in file.sections() {
+            let Some(&placement) = placements.get(&(object_index, section.index())) else {
+                continue;
+            };
+            for (offset, relocation) in section.relocations() {
+                let unsupported = || UnsupportedRelocationSnafu {
+                    object: object_name.clone(),
+                    section: section.name().unwrap_or(""),
+                    offset,
+                };
+                let object::RelocationFlags::Elf { r_type } = relocation.flags() else {
+                    return unsupported().fail();
+                };
+                let object::RelocationTarget::Symbol(symbol_index) = relocation.target() else {
+                    return unsupported().fail();
+                };
+
+                let symbol = file.symbol_by_index(symbol_index)?;
+                let target = match symbol.section() {
+                    SymbolSection::Section(section_index) => {
+                        let Some(&target_placement) = placements.get(&(object_index, section_index)) else {
+                            return UnplacedSymbolSnafu { object: object_name.clone() }.fail();
+                        };
+                        defined_target(&symbol, target_placement, &thumb_maps[object_index])
+                    }
+                    _ => {
+                        let name = symbol.name()?;
+                        let Some(global) = globals.get(name) else {
+                            return UndefinedSymbolSnafu { name, object: object_name.clone() }.fail();
+                        };
+                        RelocationTarget { address: global.target.address, thumb: global.target.thumb }
+                    }
+                };
+
+                let module = &mut modules[placement.module];
+                let from = placement.address + offset as u32;
+                let code_offset = (from - module.base_address) as usize;
+                let Some(bytes) = module.code.get_mut(code_offset..code_offset + 4) else {
+                    return unsupported().fail();
+                };
+                let ins = u32::from_le_bytes(bytes.try_into().unwrap());
+                let relocate_error = |error| LinkError::Relocate { object: object_name.clone(), error };
+                let addend = if relocation.has_implicit_addend() {
+                    implicit_addend(r_type, ins).map_err(relocate_error)?
+                } else {
+                    relocation.addend()
+                };
+                let value = relocate(r_type, ins, from, &target, addend).map_err(relocate_error)?;
+                bytes.copy_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+
+    let Some(entry) = globals.get(&layout.entry) else {
+        return EntryNotFoundSnafu { name: layout.entry.clone() }.fail();
+    };
+    let entry = entry.target.address | entry.target.thumb as u32;
+
+    Ok(LinkOutput { modules, symbols, entry })
+}
+
+/// Maps each section of an object to its mapping symbols, by offset. The value is whether the code is Thumb.
+fn thumb_map(file: &object::File) -> HashMap<object::SectionIndex, BTreeMap<u64, bool>> {
+    let mut map: HashMap<_, BTreeMap<_, _>> = HashMap::new();
+    for symbol in file.symbols() {
+        let (SymbolSection::Section(section_index), Ok(name)) = (symbol.section(), symbol.name()) else {
+            continue;
+        };
+        let thumb = match name {
+            "$t" => true,
+            "$a" | "$d" => false,
+            _ => continue,
+        };
+        map.entry(section_index).or_default().insert(symbol.address(), thumb);
+    }
+    map
+}
+
+/// Returns the linked address of a symbol defined in a placed section. Functions are Thumb if bit 0 of their value is set
+/// or if they are preceded by a `$t` mapping symbol.
+fn defined_target(
+    symbol: &object::Symbol,
+    placement: Placement,
+    thumb_map: &HashMap<object::SectionIndex, BTreeMap<u64, bool>>,
+) -> RelocationTarget {
+    let value = symbol.address();
+    if symbol.kind() != SymbolKind::Text {
+        return RelocationTarget { address: placement.address + value as u32, thumb: false };
+    }
+    let offset = value & !1;
+    let mapped_thumb = symbol
+        .section_index()
+        .and_then(|index| thumb_map.get(&index))
+        .and_then(|mapping| mapping.range(..=offset).next_back())
+        .is_some_and(|(_, &thumb)| thumb);
+    RelocationTarget { address: placement.address + offset as u32, thumb: value & 1 != 0 || mapped_thumb }
+}
+
+impl LinkOutput {
+    /// Writes an executable ELF with one section per module, named after the module, and every symbol.
+    pub fn to_elf(&self) -> Result<Vec<u8>, LinkError> {
+        let mut buffer = vec![];
+        let mut writer = Writer::new(Endianness::Little, false, &mut buffer);
+
+        // Reserve the file layout
+        writer.reserve_file_header();
+        writer.reserve_null_section_index();
+        let mut code_sections: Vec<(StringId, SectionIndex, usize)> = vec![];
+        let mut bss_sections: Vec<Option<(StringId, SectionIndex)>> = vec![];
+        for module in &self.modules {
+            let name = writer.add_section_name(module.name.as_bytes());
+            let index = writer.reserve_section_index();
+            let offset = writer.reserve(module.code.len(), 4);
+            code_sections.push((name, index, offset));
+            bss_sections.push(if module.bss_size > 0 {
+                let name = writer.add_section_name(format!("{}.bss", module.name).as_bytes());
+                Some((name, writer.reserve_section_index()))
+            } else {
+                None
+            });
+        }
+
+        // Local symbols must come before global ones
+        let mut symbols = self.symbols.iter().collect::<Vec<_>>();
+        symbols.sort_by_key(|symbol| !symbol.local);
+        let symbol_sections = symbols
+            .iter()
+            .map(|symbol| {
+                let module = &self.modules[symbol.module];
+                let code_end = module.base_address + module.code.len() as u32;
+                match bss_sections[symbol.module] {
+                    Some((_, bss_index)) if symbol.address >= code_end => bss_index,
+                    _ => code_sections[symbol.module].1,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        writer.reserve_null_symbol_index();
+        let mut symbol_names = vec![];
+        for (symbol, &section) in symbols.iter().zip(symbol_sections.iter()) {
+            symbol_names.push(writer.add_string(symbol.name.as_bytes()));
+            writer.reserve_symbol_index(Some(section));
+        }
+        let num_local = 1 + symbols.iter().filter(|symbol| symbol.local).count() as u32;
+
+        writer.reserve_symtab_section_index();
+        writer.reserve_symtab();
+        writer.reserve_strtab_section_index();
+        writer.reserve_strtab();
+        writer.reserve_shstrtab_section_index();
+        writer.reserve_shstrtab();
+        writer.reserve_section_headers();
+
+        // Write the file
+        writer.write_file_header(&FileHeader {
+            os_abi: elf::ELFOSABI_NONE,
+            abi_version: 0,
+            e_type: elf::ET_EXEC,
+            e_machine: elf::EM_ARM,
+            e_entry: self.entry as u64,
+            e_flags: elf::EF_ARM_EABI_VER5,
+        })?;
+
+        for module in &self.modules {
+            writer.write_align(4);
+            writer.write(&module.code);
+        }
+
+        writer.write_null_symbol();
+        for ((symbol, &name), &section) in symbols.iter().zip(symbol_names.iter()).zip(symbol_sections.iter()) {
+            let bind = if symbol.local { elf::STB_LOCAL } else { elf::STB_GLOBAL };
+            let st_type = match symbol.kind {
+                LinkedSymbolKind::Function => elf::STT_FUNC,
+                LinkedSymbolKind::Object => elf::STT_OBJECT,
+                LinkedSymbolKind::None => elf::STT_NOTYPE,
+            };
+            writer.write_symbol(&Sym {
+                name: Some(name),
+                section: Some(section),
+                st_info: (bind << 4) | st_type,
+                st_other: elf::STV_DEFAULT,
+                st_shndx: 0,
+                st_value: symbol.address as u64,
+                st_size: symbol.size as u64,
+            });
+        }
+        writer.write_strtab();
+        writer.write_shstrtab();
+
+        writer.write_null_section_header();
+        for (module, (&(name, _, offset), bss_section)) in
+            self.modules.iter().zip(code_sections.iter().zip(bss_sections.iter()))
+        {
+            writer.write_section_header(&SectionHeader {
+                name: Some(name),
+                sh_type: elf::SHT_PROGBITS,
+                sh_flags: (elf::SHF_ALLOC | elf::SHF_WRITE | elf::SHF_EXECINSTR) as u64,
+                sh_addr: module.base_address as u64,
+                sh_offset: offset as u64,
+                sh_size: module.code.len() as u64,
+                sh_link: 0,
+                sh_info: 0,
+                sh_addralign: 4,
+                sh_entsize: 0,
+            });
+            if let Some((bss_name, _)) = bss_section {
+                writer.write_section_header(&SectionHeader {
+                    name: Some(*bss_name),
+                    sh_type: elf::SHT_NOBITS,
+                    sh_flags: (elf::SHF_ALLOC | elf::SHF_WRITE) as u64,
+                    sh_addr: (module.base_address + module.code.len() as u32) as u64,
+                    sh_offset: 0,
+                    sh_size: module.bss_size as u64,
+                    sh_link: 0,
+                    sh_info: 0,
+                    sh_addralign: 4,
+                    sh_entsize: 0,
+                });
+            }
+        }
+        writer.write_symtab_section_header(num_local);
+        writer.write_strtab_section_header();
+        writer.write_shstrtab_section_header();
+
+        Ok(buffer)
+    }
+}
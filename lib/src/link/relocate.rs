@@ -0,0 +1,145 @@
+use std::backtrace::Backtrace;
+
+use object::elf::{R_ARM_ABS32, R_ARM_PC24, R_ARM_THM_PC22, R_ARM_THM_XPC22, R_ARM_XPC25};
+use snafu::Snafu;
+
+#[derive(Debug, Snafu)]
+pub enum RelocateError {
+    #[snafu(display("unsupported relocation type {r_type}:\n{backtrace}"))]
+    UnsupportedType { r_type: u32, backtrace: Backtrace },
+    #[snafu(display("branch from {from:#010x} to {to:#010x} is out of range:\n{backtrace}"))]
+    OutOfRange { from: u32, to: u32, backtrace: Backtrace },
+    #[snafu(display("branch from {from:#010x} to Thumb code at {to:#010x} can't change instruction mode:\n{backtrace}"))]
+    ModeChange { from: u32, to: u32, backtrace: Backtrace },
+}
+
+/// The symbol a relocation points to.
+pub struct RelocationTarget {
+    pub address: u32,
+    /// Whether the target is a Thumb function, which changes `BL` to `BLX` and sets bit 0 of pointers.
+    pub thumb: bool,
+}
+
+/// Reads the addend which is stored in the instruction or pointer, for relocations without an explicit addend.
+pub fn implicit_addend(r_type: u32, ins: u32) -> Result<i64, RelocateError> {
+    match r_type {
+        R_ARM_ABS32 => Ok(ins as i32 as i64),
+        R_ARM_PC24 | R_ARM_XPC25 => {
+            let offset = (((ins & 0xffffff) << 8) as i32 >> 6) as i64;
+            let h = if is_arm_blx(ins) { ((ins >> 24) & 1) << 1 } else { 0 };
+            Ok(offset + h as i64)
+        }
+        R_ARM_THM_PC22 | R_ARM_THM_XPC22 => {
+            let high = ((((ins & 0x7ff) << 21) as i32) >> 9) as i64;
+            let low = ((ins >> 16) & 0x7ff) << 1;
+            Ok(high + low as i64)
+        }
+        _ => UnsupportedTypeSnafu { r_type }.fail(),
+    }
+}
+
+/// Applies a relocation to an instruction or pointer at `from`. Calls are changed between `BL` and `BLX` to match the
+/// instruction mode of the target, like the linker does.
+pub fn relocate(r_type: u32, ins: u32, from: u32, target: &RelocationTarget, addend: i64) -> Result<u32, RelocateError> {
+    let to = target.address;
+    match r_type {
+        R_ARM_ABS32 => Ok((to as i64 + addend) as u32 | target.thumb as u32),
+        R_ARM_PC24 | R_ARM_XPC25 => {
+            let offset = to as i64 + addend - from as i64;
+            if !(-0x2000000..0x2000000).contains(&offset) {
+                return OutOfRangeSnafu { from, to }.fail();
+            }
+            let imm = (offset as u32 >> 2) & 0xffffff;
+            let is_call = is_arm_blx(ins) || (ins & 0x0f000000) == 0x0b000000;
+            let unconditional = is_arm_blx(ins) || (ins & 0xf0000000) == 0xe0000000;
+            match (is_call && unconditional, target.thumb) {
+                (true, true) => Ok(0xfa000000 | (((offset as u32 >> 1) & 1) << 24) | imm),
+                (true, false) => Ok(0xeb000000 | imm),
+                (false, true) => ModeChangeSnafu { from, to }.fail(),
+                (false, false) => Ok((ins & 0xff000000) | imm),
+            }
+        }
+        R_ARM_THM_PC22 | R_ARM_THM_XPC22 => {
+            // BLX aligns the PC down to 4 bytes
+            let base = if target.thumb { from } else { from & !3 };
+            let offset = to as i64 + addend - base as i64;
+            if !(-0x400000..0x400000).contains(&offset) {
+                return OutOfRangeSnafu { from, to }.fail();
+            }
+            let high = 0xf000 | ((offset as u32 >> 12) & 0x7ff);
+            let low =
+                if target.thumb { 0xf800 | ((offset as u32 >> 1) & 0x7ff) } else { 0xe800 | ((offset as u32 >> 1) & 0x7fe) };
+            Ok(high | (low << 16))
+        }
+        _ => UnsupportedTypeSnafu { r_type }.fail(),
+    }
+}
+
+fn is_arm_blx(ins: u32) -> bool {
+    (ins & 0xfe000000) == 0xfa000000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FROM: u32 = 0x02000000;
+
+    fn arm(address: u32) -> RelocationTarget {
+        RelocationTarget { address, thumb: false }
+    }
+
+    fn thumb(address: u32) -> RelocationTarget {
+        RelocationTarget { address, thumb: true }
+    }
+
+    #[test]
+    fn test_implicit_addend() {
+        let cases = [
+            ("abs32", R_ARM_ABS32, 0x00000004, 4),
+            ("bl", R_ARM_PC24, 0xebfffffe, -8),
+            ("b-cond", R_ARM_PC24, 0x1afffffe, -8),
+            ("blx h=0", R_ARM_XPC25, 0xfafffffe, -8),
+            ("blx h=1", R_ARM_XPC25, 0xfb000000, 2),
+            ("thumb bl", R_ARM_THM_PC22, 0xfffef7ff, -4),
+            ("thumb blx", R_ARM_THM_XPC22, 0xeffef7ff, -4),
+        ];
+        for (name, r_type, ins, expected) in cases {
+            assert_eq!(implicit_addend(r_type, ins).unwrap(), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_relocate() {
+        let cases = [
+            ("bl to arm", R_ARM_PC24, 0xebfffffe, FROM, arm(0x02000100), -8, 0xeb00003e),
+            ("bl to thumb becomes blx with h bit", R_ARM_PC24, 0xebfffffe, FROM, thumb(0x02000102), -8, 0xfb00003e),
+            ("blx to thumb without h bit", R_ARM_XPC25, 0xfafffffe, FROM, thumb(0x02000100), -8, 0xfa00003e),
+            ("blx to arm becomes bl", R_ARM_XPC25, 0xfafffffe, FROM, arm(0x02000100), -8, 0xeb00003e),
+            ("b-cond keeps condition", R_ARM_PC24, 0x1afffffe, FROM, arm(0x02000100), -8, 0x1a00003e),
+            ("b-cond backwards", R_ARM_PC24, 0x1afffffe, 0x02000100, arm(FROM), -8, 0x1affffbe),
+            ("thumb bl to thumb", R_ARM_THM_PC22, 0xfffef7ff, FROM + 2, thumb(0x02000100), -4, 0xf87df000),
+            ("thumb bl to arm aligns pc", R_ARM_THM_PC22, 0xfffef7ff, FROM + 2, arm(0x02000100), -4, 0xe87ef000),
+            ("thumb blx to thumb becomes bl", R_ARM_THM_XPC22, 0xeffef7ff, FROM + 2, thumb(0x02000100), -4, 0xf87df000),
+            ("abs32 to arm", R_ARM_ABS32, 0, FROM, arm(0x02000100), 4, 0x02000104),
+            ("abs32 to thumb sets bit 0", R_ARM_ABS32, 0, FROM, thumb(0x02000100), 0, 0x02000101),
+        ];
+        for (name, r_type, ins, from, target, addend, expected) in cases {
+            let value = relocate(r_type, ins, from, &target, addend).unwrap();
+            assert_eq!(value, expected, "{name}: {value:#010x} != {expected:#010x}");
+        }
+    }
+
+    #[test]
+    fn test_relocate_errors() {
+        let cases = [
+            ("b-cond to thumb", R_ARM_PC24, 0x1afffffe, FROM, thumb(0x02000100), -8),
+            ("bl out of range", R_ARM_PC24, 0xebfffffe, FROM, arm(FROM + 0x2000008), -8),
+            ("thumb bl out of range", R_ARM_THM_PC22, 0xfffef7ff, FROM, thumb(FROM + 0x400004), -4),
+            ("unsupported type", object::elf::R_ARM_REL32, 0, FROM, arm(FROM), 0),
+        ];
+        for (name, r_type, ins, from, target, addend) in cases {
+            assert!(relocate(r_type, ins, from, &target, addend).is_err(), "{name}");
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use std::{backtrace::Backtrace, collections::BTreeMap};
+
+use object::{Architecture, BinaryFormat, Endianness, RelocationFlags};
+use snafu::Snafu;
+
+use crate::config::{
+    delinks::DelinkFile,
+    module::{Module, ModuleKind},
+    section::{SectionCodeError, SectionKind},
+    symbol::{Symbol, SymbolKind, SymbolMap, SymbolMapError, SymbolMaps},
+};
+
+use super::project::Project;
+
+#[derive(Debug, Snafu)]
+pub enum DelinkError {
+    #[snafu(display("module {module_kind} does not exist:\n{backtrace}"))]
+    ModuleNotFound { module_kind: ModuleKind, backtrace: Backtrace },
+    #[snafu(display("failed to delink '{name}', see errors above:\n{backtrace}"))]
+    MissingSymbols { name: String, backtrace: Backtrace },
+    #[snafu(transparent)]
+    SectionCode { source: SectionCodeError },
+    #[snafu(transparent)]
+    SymbolMap { source: SymbolMapError },
+    #[snafu(transparent)]
+    ObjectWrite { source: object::write::Error },
+}
+
+impl<'a> Project<'a> {
+    /// Creates a relocatable object of a file in a module. Relocations to other modules refer to the symbols of those
+    /// modules, so that the objects can be linked back together.
+    pub fn delink_file(
+        &self,
+        module_kind: ModuleKind,
+        delink_file: &DelinkFile,
+    ) -> Result<object::write::Object<'static>, DelinkError> {
+        let module = self.module_by_kind(module_kind).ok_or_else(|| ModuleNotFoundSnafu { module_kind }.build())?;
+        delink(self.symbol_maps(), module, delink_file)
+    }
+}
+
+/// Creates a relocatable object of a file in `module`.
+pub fn delink(
+    symbol_maps: &SymbolMaps,
+    module: &Module,
+    delink_file: &DelinkFile,
+) -> Result<object::write::Object<'static>, DelinkError> {
+    let symbol_map = symbol_maps.get(module.kind()).unwrap();
+    let mut object = object::write::Object::new(BinaryFormat::Elf, Architecture::Arm, Endianness::Little);
+    object.elf_is_rela = Some(true);
+
+    // Maps address to ObjSection/ObjSymbol
+    let mut obj_sections = BTreeMap::new();
+    let mut obj_symbols = BTreeMap::new();
+
+    let mut error = false;
+
+    for file_section in delink_file.sections.iter() {
+        // Get section data
+        let code = file_section.relocatable_code(module)?.unwrap_or_else(Vec::new);
+        let name = file_section.name().as_bytes().to_vec();
+        let kind = match file_section.kind() {
+            SectionKind::Code => object::SectionKind::Text,
+            SectionKind::Data if file_section.is_read_only() => object::SectionKind::ReadOnlyData,
+            SectionKind::Data => object::SectionKind::Data,
+            SectionKind::Bss => object::SectionKind::UninitializedData,
+        };
+        let alignment = file_section.object_alignment() as u64;
+
+        // Create section
+        let obj_section_id = object.add_section(vec![], name.clone(), kind);
+        let section = object.section_mut(obj_section_id);
+        if file_section.kind() == SectionKind::Bss {
+            section.append_bss(file_section.size() as u64, alignment);
+        } else {
+            section.set_data(code, alignment);
+        }
+
+        // Add dummy symbol to make linker notice the section
+        object.add_symbol(object::write::Symbol {
+            name, // same name as section
+            value: 0,
+            size: 0,
+            kind: object::SymbolKind::Label,
+            scope: object::SymbolScope::Compilation,
+            weak: false,
+            section: object::write::SymbolSection::Section(obj_section_id),
+            flags: object::SymbolFlags::None,
+        });
+
+        // Add symbols to section
+        let symbol_section = object::write::SymbolSection::Section(obj_section_id);
+        let mut mapping_symbols = BTreeMap::new();
+        for symbol in symbol_map.iter_by_address(file_section.address_range()) {
+            // Get symbol data
+            let max_address = symbol_end_address(symbol_map, symbol, file_section.end_address());
+            let kind = symbol.kind.as_obj_symbol_kind();
+            let scope = symbol.get_obj_symbol_scope();
+            let value = (symbol.addr - file_section.start_address()) as u64;
+
+            // Create symbol
+            let size = match symbol.kind {
+                SymbolKind::Label(_) => max_address - symbol.addr,
+                _ => symbol.size(max_address),
+            };
+            let symbol_id = object.add_symbol(object::write::Symbol {
+                name: symbol.name.clone().into_bytes(),
+                value,
+                size: size as u64,
+                kind,
+                scope,
+                weak: false,
+                section: symbol_section,
+                flags: object::SymbolFlags::None,
+            });
+            obj_symbols.insert((symbol.addr, module.kind()), symbol_id);
+
+            if let Some(name) = symbol.mapping_symbol_name() {
+                mapping_symbols.insert(symbol.addr, name);
+            }
+        }
+
+        // Data within functions must be marked with `$d`, and the code after it with `$a` or `$t`
+        let functions =
+            module.sections().functions().filter(|function| file_section.address_range().contains(&function.start_address()));
+        for function in functions {
+            let code_mapping = if function.is_thumb() { "$t" } else { "$a" };
+            let data_ranges = function
+                .pool_constants()
+                .iter()
+                .map(|&address| address..address + 4)
+                .chain(function.inline_tables().values().map(|table| table.address..table.address + table.size))
+                .chain(
+                    function.jump_tables().filter(|table| !table.code).map(|table| table.address..table.address + table.size),
+                )
+                .chain(
+                    function
+                        .pointer_tables()
+                        .values()
+                        .filter(|table| table.inline)
+                        .map(|table| table.address..table.address + table.size),
+                )
+                .collect::<Vec<_>>();
+            for table in function.jump_tables().filter(|table| table.code) {
+                mapping_symbols.insert(table.address, code_mapping);
+            }
+            for range in &data_ranges {
+                if range.end < function.end_address() {
+                    mapping_symbols.entry(range.end).or_insert(code_mapping);
+                }
+            }
+            for range in data_ranges {
+                mapping_symbols.insert(range.start, "$d");
+            }
+        }
+
+        // Create mapping symbols
+        for (address, name) in mapping_symbols {
+            object.add_symbol(object::write::Symbol {
+                name: name.to_string().into_bytes(),
+                value: (address - file_section.start_address()) as u64,
+                size: 0,
+                kind: object::SymbolKind::Label,
+                scope: object::SymbolScope::Compilation,
+                weak: false,
+                section: symbol_section,
+                flags: object::SymbolFlags::None,
+            });
+        }
+
+        obj_sections.insert(file_section.start_address(), obj_section_id);
+    }
+
+    // Must start a new loop here so we can know which section a symbol ID belongs to
+    for file_section in delink_file.sections.iter() {
+        let obj_section_id = *obj_sections.get(&file_section.start_address()).unwrap();
+
+        // Add relocations to section
+        for (_, relocation) in module.relocations().iter_range(file_section.address_range()) {
+            // Get relocation data
+            let offset = relocation.from_address() - file_section.start_address();
+            let dest_addr = relocation.to_address();
+            let reloc_module = relocation.module().first_module().unwrap();
+
+            // Get destination symbol
+            let symbol_key = (dest_addr, reloc_module);
+            let symbol_id = if let Some(obj_symbol_id) = obj_symbols.get(&symbol_key) {
+                *obj_symbol_id
+            } else {
+                // Get external symbol data
+                let external_symbol_map = symbol_maps.get(reloc_module).unwrap();
+                let symbol = if let Some((_, symbol)) = external_symbol_map.by_address(dest_addr)? {
+                    symbol
+                } else if let Some((_, symbol)) = external_symbol_map.get_function(dest_addr)? {
+                    symbol
+                } else {
+                    log::error!(
+                        "No symbol found for relocation from {:#010x} in {} to {:#010x} in {}",
+                        relocation.from_address(),
+                        module.kind(),
+                        dest_addr,
+                        reloc_module
+                    );
+                    error = true;
+                    continue;
+                };
+
+                // Add external symbol to section
+                let kind = relocation.kind().as_obj_symbol_kind();
+                let symbol_section = object::write::SymbolSection::Undefined;
+                let symbol_id = object.add_symbol(object::write::Symbol {
+                    name: symbol.name.clone().into_bytes(),
+                    value: 0,
+                    size: 0,
+                    kind,
+                    scope: object::SymbolScope::Compilation,
+                    weak: true,
+                    section: symbol_section,
+                    flags: object::SymbolFlags::None,
+                });
+                obj_symbols.insert(symbol_key, symbol_id);
+                symbol_id
+            };
+
+            // Create relocation
+            let r_type = relocation.kind().as_elf_relocation_type();
+            let addend = relocation.addend();
+            object.add_relocation(
+                obj_section_id,
+                object::write::Relocation {
+                    offset: offset as u64,
+                    symbol: symbol_id,
+                    addend,
+                    flags: RelocationFlags::Elf { r_type },
+                },
+            )?;
+        }
+    }
+
+    if error {
+        return MissingSymbolsSnafu { name: &delink_file.name }.fail();
+    }
+
+    Ok(object)
+}
+
+/// Returns where a symbol ends, which is at the next symbol at a higher address. Labels also end with their function.
+fn symbol_end_address(symbol_map: &SymbolMap, symbol: &Symbol, section_end: u32) -> u32 {
+    let next_address =
+        symbol_map.iter_by_address(symbol.addr + 1..section_end).next().map(|next| next.addr).unwrap_or(section_end);
+    match symbol.kind {
+        SymbolKind::Label(_) => match symbol_map.get_function_containing(symbol.addr) {
+            Some((function, function_symbol)) => next_address.min(function_symbol.addr + function.size),
+            None => next_address,
+        },
+        _ => next_address,
+    }
+}
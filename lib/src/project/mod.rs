@@ -0,0 +1,2 @@
+pub mod delink;
+pub mod project;
@@ -0,0 +1,241 @@
+use std::{backtrace::Backtrace, collections::BTreeMap, ops::Range, path::Path};
+
+use ds_rom::rom::{raw::RawBuildInfoError, Autoload, Rom};
+use snafu::Snafu;
+
+use crate::{
+    analysis::data::{
+        self, AnalyzeExternalReferencesError, AnalyzeExternalReferencesOptions, RelocationResult, SymbolCandidate,
+    },
+    config::{
+        config::Config,
+        delinks::{Delinks, DelinksParseError},
+        module::{AnalysisOptions, Module, ModuleError, ModuleKind},
+        relocations::{Relocation, Relocations, RelocationsParseError},
+        section::SectionKind,
+        symbol::{SymBss, SymData, SymbolMaps, SymbolMapsParseError},
+    },
+    function,
+};
+
+/// Every module of a game with their symbol maps and relocations.
+pub struct Project<'a> {
+    modules: Vec<Module<'a>>,
+    symbol_maps: SymbolMaps,
+    // Indices in modules vec above
+    main: usize,
+    overlays: Range<usize>,
+    autoloads: Range<usize>,
+}
+
+#[derive(Debug, Snafu)]
+pub enum ProjectLoadError {
+    #[snafu(transparent)]
+    SymbolMapsParse { source: SymbolMapsParseError },
+    #[snafu(transparent)]
+    DelinksParse { source: DelinksParseError },
+    #[snafu(transparent)]
+    RelocationsParse { source: RelocationsParseError },
+    #[snafu(transparent)]
+    RawBuildInfo { source: RawBuildInfoError },
+    #[snafu(transparent)]
+    Module { source: ModuleError },
+    #[snafu(display("overlay {id} not present in ROM:\n{backtrace}"))]
+    OverlayNotFound { id: u16, backtrace: Backtrace },
+    #[snafu(display("autoload {kind} not present in ROM:\n{backtrace}"))]
+    AutoloadNotFound { kind: ModuleKind, backtrace: Backtrace },
+}
+
+impl<'a> Project<'a> {
+    pub fn new(main: Module<'a>, overlays: Vec<Module<'a>>, autoloads: Vec<Module<'a>>, symbol_maps: SymbolMaps) -> Self {
+        let mut modules = vec![main];
+        let main = 0;
+
+        modules.extend(overlays);
+        let overlays = (main + 1)..modules.len();
+
+        modules.extend(autoloads);
+        let autoloads = overlays.end..modules.len();
+
+        Self { modules, symbol_maps, main, overlays, autoloads }
+    }
+
+    /// Loads every module in a dsd config. The autoloads must be the result of `rom.arm9().autoloads()`, which is passed in
+    /// separately because the modules borrow their code.
+    pub fn from_config(
+        config_path: &Path,
+        config: &Config,
+        rom: &'a Rom,
+        rom_autoloads: &'a [Autoload],
+    ) -> Result<Self, ProjectLoadError> {
+        let mut symbol_maps = SymbolMaps::from_config(config_path, config)?;
+
+        let main = {
+            let module_kind = ModuleKind::Arm9;
+            let delinks = Delinks::from_file(config_path.join(&config.main_module.delinks), module_kind)?;
+            let relocations = Relocations::from_file(config_path.join(&config.main_module.relocations))?;
+            Module::new_arm9(
+                config.main_module.name.clone(),
+                symbol_maps.get_mut(module_kind),
+                relocations,
+                delinks.sections,
+                rom.arm9().code()?,
+            )?
+        };
+
+        let overlays = config
+            .overlays
+            .iter()
+            .map(|overlay| {
+                let module_kind = ModuleKind::Overlay(overlay.id);
+                let delinks = Delinks::from_file(config_path.join(&overlay.module.delinks), module_kind)?;
+                let relocations = Relocations::from_file(config_path.join(&overlay.module.relocations))?;
+                let code = rom
+                    .arm9_overlays()
+                    .get(overlay.id as usize)
+                    .ok_or_else(|| OverlayNotFoundSnafu { id: overlay.id }.build())?
+                    .code();
+                Ok(Module::new_overlay(
+                    overlay.module.name.clone(),
+                    symbol_maps.get_mut(module_kind),
+                    relocations,
+                    delinks.sections,
+                    overlay.id,
+                    code,
+                )?)
+            })
+            .collect::<Result<Vec<_>, ProjectLoadError>>()?;
+
+        let autoloads = config
+            .autoloads
+            .iter()
+            .map(|autoload| {
+                let module_kind = ModuleKind::Autoload(autoload.kind);
+                let delinks = Delinks::from_file(config_path.join(&autoload.module.delinks), module_kind)?;
+                let relocations = Relocations::from_file(config_path.join(&autoload.module.relocations))?;
+                let code = rom_autoloads
+                    .iter()
+                    .find(|a| a.kind() == autoload.kind)
+                    .ok_or_else(|| AutoloadNotFoundSnafu { kind: module_kind }.build())?
+                    .code();
+                Ok(Module::new_autoload(
+                    autoload.module.name.clone(),
+                    symbol_maps.get_mut(module_kind),
+                    relocations,
+                    delinks.sections,
+                    autoload.kind,
+                    code,
+                )?)
+            })
+            .collect::<Result<Vec<_>, ProjectLoadError>>()?;
+
+        Ok(Self::new(main, overlays, autoloads, symbol_maps))
+    }
+
+    /// Finds function calls and pointers between modules, adding them as relocations and adding symbols for the data they
+    /// point to.
+    pub fn analyze_cross_references(&mut self, options: &AnalysisOptions) -> Result<(), AnalyzeExternalReferencesError> {
+        for module_index in 0..self.modules.len() {
+            let RelocationResult { relocations, external_symbols } = data::analyze_external_references(
+                AnalyzeExternalReferencesOptions { modules: &self.modules, module_index, symbol_maps: &mut self.symbol_maps },
+                options,
+            )?;
+
+            let module_relocations = self.modules[module_index].relocations_mut();
+            for reloc in relocations {
+                let reloc = module_relocations.add(reloc)?;
+                if options.provide_reloc_source {
+                    reloc.source = Some(function!().to_string());
+                }
+            }
+
+            for symbol in external_symbols {
+                match symbol.candidates.len() {
+                    0 => {
+                        let error = data::NoSymbolCandidatesSnafu { address: symbol.address }.build();
+                        log::error!("{error}");
+                        return Err(error);
+                    }
+                    1 => {
+                        let SymbolCandidate { module_index, section_index } = symbol.candidates[0];
+                        let section_kind = self.modules[module_index].sections().get(section_index.0).kind();
+                        let name = format!("{}{:08x}", self.modules[module_index].default_data_prefix, symbol.address);
+                        let symbol_map = self.symbol_maps.get_mut(self.modules[module_index].kind());
+                        match section_kind {
+                            SectionKind::Code => {} // Function symbol, already verified to exist
+                            SectionKind::Data => {
+                                symbol_map.add_data(Some(name), symbol.address, SymData::Any)?;
+                            }
+                            SectionKind::Bss => {
+                                symbol_map.add_bss(Some(name), symbol.address, SymBss { size: None })?;
+                            }
+                        }
+                    }
+                    _ => {
+                        for SymbolCandidate { module_index, section_index } in symbol.candidates {
+                            let section_kind = self.modules[module_index].sections().get(section_index.0).kind();
+                            let name = format!("{}{:08x}", self.modules[module_index].default_data_prefix, symbol.address);
+                            let symbol_map = self.symbol_maps.get_mut(self.modules[module_index].kind());
+                            match section_kind {
+                                SectionKind::Code => {} // Function symbol, already verified to exist
+                                SectionKind::Data => {
+                                    symbol_map.add_ambiguous_data(Some(name), symbol.address, SymData::Any)?;
+                                }
+                                SectionKind::Bss => {
+                                    symbol_map.add_ambiguous_bss(Some(name), symbol.address, SymBss { size: None })?;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the relocation hints of every module, replacing what the analysis found. Returns the relocations which were
+    /// removed by `ignore_reloc` hints, per module.
+    pub fn apply_relocation_hints(&mut self) -> BTreeMap<ModuleKind, Vec<Relocation>> {
+        self.modules.iter_mut().map(|module| (module.kind(), module.apply_relocation_hints())).collect()
+    }
+
+    pub fn main(&self) -> &Module {
+        &self.modules[self.main]
+    }
+
+    pub fn overlays(&self) -> &[Module] {
+        &self.modules[self.overlays.clone()]
+    }
+
+    pub fn autoloads(&self) -> &[Module] {
+        &self.modules[self.autoloads.clone()]
+    }
+
+    pub fn module(&self, index: usize) -> &Module {
+        &self.modules[index]
+    }
+
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    pub fn module_by_kind(&self, module_kind: ModuleKind) -> Option<&Module> {
+        self.modules.iter().find(|module| module.kind() == module_kind)
+    }
+
+    pub fn relocations_mut(&mut self, index: usize) -> &mut Relocations {
+        self.modules[index].relocations_mut()
+    }
+
+    pub fn module_mut(&mut self, index: usize) -> &mut Module<'a> {
+        &mut self.modules[index]
+    }
+
+    pub fn num_modules(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn symbol_maps(&self) -> &SymbolMaps {
+        &self.symbol_maps
+    }
+}
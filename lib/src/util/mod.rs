@@ -1,4 +1,4 @@
 pub(crate) mod bytes;
 pub(crate) mod debug;
 pub(crate) mod io;
-pub(crate) mod parse;
+pub mod parse;